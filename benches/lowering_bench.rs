@@ -0,0 +1,68 @@
+// Compare colorizing with lowered rules (as loaded by rgrc) against the same rules
+// left on the enhanced engine, for the configs that rely most on anchoring lookarounds.
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use rgrc::colorizer::colorize_regex;
+use rgrc::grc::{CompiledRegex, GrcatConfigEntry};
+use std::hint::black_box;
+
+const JSON_LINES: &[&str] = &[
+    r#"{"level":"info","ts":"2024-05-01T10:00:00Z","msg":"listening","port":8080,"ratio":0.75}"#,
+    r#"{"level":"warn","ts":"2024-05-01T10:00:01Z","msg":"slow request","path":"/api/v1/items","ms":1532}"#,
+    r#"{"level":"error","ts":"2024-05-01T10:00:02Z","error":"connection reset","retry":3,"ok":false}"#,
+    r#"{"id":42,"tags":["a","b","c"],"nested":{"x":-1.5,"y":"z"},"when":"2024-05-01 10:00"}"#,
+];
+
+const LS_LINES: &[&str] = &[
+    "drwxr-xr-x  12 root  wheel       384 Nov 30 10:21 .",
+    "-rw-r--r--   1 alice staff   1234567 Nov 29 09:01 archive.tar",
+    "-rwsr-xr-x   1 root  wheel  45678901 Nov 28  2023 setuid-tool",
+    "lrwxrwxrwx   1 bob   staff        11 Nov 27 18:45 link -> target",
+    "crw-rw-rw-   1 root  wheel    3,   2 Nov 26 08:00 null",
+    "-rw-------   1 alice staff 123456789 Nov 25 12:34 big.bin",
+];
+
+/// Same rules with every regex recompiled without lowering
+fn unlowered(rules: &[GrcatConfigEntry]) -> Vec<GrcatConfigEntry> {
+    rules
+        .iter()
+        .map(|rule| GrcatConfigEntry {
+            regex: CompiledRegex::new(rule.regex.as_str()).unwrap(),
+            ..rule.clone()
+        })
+        .collect()
+}
+
+fn colorize(input: &[u8], rules: &[GrcatConfigEntry]) -> Vec<u8> {
+    let mut reader = input;
+    let mut output = Vec::with_capacity(input.len() * 2);
+    colorize_regex(&mut reader, &mut output, rules).unwrap();
+    output
+}
+
+fn bench_config(c: &mut Criterion, name: &str, lines: &[&str]) {
+    let lowered = rgrc::load_grcat_config(format!("share/conf.{}", name));
+    let enhanced = unlowered(&lowered);
+    let input = lines.repeat(50).join("\n").into_bytes();
+
+    let mut group = c.benchmark_group(format!("conf.{}", name));
+    group.throughput(criterion::Throughput::Bytes(input.len() as u64));
+    group.bench_function("lowered", |b| {
+        b.iter(|| colorize(black_box(&input), &lowered))
+    });
+    group.bench_function("enhanced", |b| {
+        b.iter(|| colorize(black_box(&input), &enhanced))
+    });
+    group.finish();
+}
+
+fn benchmark_conf_json(c: &mut Criterion) {
+    bench_config(c, "json", JSON_LINES);
+}
+
+fn benchmark_conf_ls(c: &mut Criterion) {
+    bench_config(c, "ls", LS_LINES);
+}
+
+criterion_group!(benches, benchmark_conf_json, benchmark_conf_ls);
+criterion_main!(benches);
@@ -0,0 +1,68 @@
+// Compare colorizing with and without the literal prefilter on large rule sets,
+// where most rules cannot match a given line.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use rgrc::colorizer::colorize_regex_with_prefilter;
+use rgrc::grc::{GrcatConfigEntry, MatchLimits};
+use rgrc::prefilter::Prefilter;
+use std::hint::black_box;
+
+const JOURNALCTL_LINES: &[&str] = &[
+    "Nov 30 10:21:04 host systemd[1]: Started session-42.scope - Session 42 of User alice.",
+    "Nov 30 10:21:05 host sshd[2211]: Accepted publickey for alice from 10.0.0.7 port 52114 ssh2: ED25519",
+    "Nov 30 10:21:07 host kernel: usb 1-1: new high-speed USB device number 3 using xhci_hcd",
+    "Nov 30 10:21:09 host CRON[3120]: (root) CMD (run-parts /etc/cron.hourly)",
+    "Nov 30 10:21:11 host app[991]: request handled in 12ms",
+    "Nov 30 10:21:12 host dockerd[870]: time=\"2024-11-30T10:21:12\" level=warning msg=\"retrying\"",
+];
+
+const KUBECTL_LINES: &[&str] = &[
+    "NAME                         READY   STATUS             RESTARTS   AGE",
+    "web-7d4b9c8f6d-2xk9q         1/1     Running            0          3d4h",
+    "worker-5c6f7b8d9-abcde       0/1     CrashLoopBackOff   12         1h",
+    "2024-11-30T10:21:12.123Z INFO  server started on 0.0.0.0:8080",
+    "2024-11-30T10:21:13.456Z DEBUG handling GET /healthz from 10.1.2.3",
+    "2024-11-30T10:21:14.789Z WARN  slow query took 1532ms",
+];
+
+fn colorize(input: &[u8], rules: &[GrcatConfigEntry], prefilter: &Prefilter) -> Vec<u8> {
+    let mut reader = input;
+    let mut output = Vec::with_capacity(input.len() * 2);
+    colorize_regex_with_prefilter(
+        &mut reader,
+        &mut output,
+        rules,
+        MatchLimits::default(),
+        prefilter,
+    )
+    .unwrap();
+    output
+}
+
+fn bench_config(c: &mut Criterion, name: &str, lines: &[&str]) {
+    let rules = rgrc::load_grcat_config(format!("share/conf.{}", name));
+    let prefilter = Prefilter::new(&rules);
+    let unfiltered = Prefilter::default();
+    let input = lines.repeat(200).join("\n").into_bytes();
+
+    let mut group = c.benchmark_group(format!("conf.{}", name));
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("prefilter", |b| {
+        b.iter(|| colorize(black_box(&input), &rules, &prefilter))
+    });
+    group.bench_function("unfiltered", |b| {
+        b.iter(|| colorize(black_box(&input), &rules, &unfiltered))
+    });
+    group.finish();
+}
+
+fn benchmark_conf_journalctl(c: &mut Criterion) {
+    bench_config(c, "journalctl", JOURNALCTL_LINES);
+}
+
+fn benchmark_conf_kubectl(c: &mut Criterion) {
+    bench_config(c, "kubectl", KUBECTL_LINES);
+}
+
+criterion_group!(benches, benchmark_conf_journalctl, benchmark_conf_kubectl);
+criterion_main!(benches);
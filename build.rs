@@ -10,13 +10,15 @@ fn main() {
 }
 
 fn compress_configs() {
-    // Simpler build-time generation: scan `share/` and generate a small Rust
-    // source file (`embedded_configs.rs`) with a `pub const EMBEDDED_CONFIGS`.
-    // This avoids hard-coding and doesn't require external crates or compression.
+    // Scan `share/` and compress every `conf.*` into a single bundle
+    // (`embedded_configs.bin`), plus a small Rust source file (`embedded_configs.rs`)
+    // with the index of the bundle. Each config is compressed on its own, so loading
+    // one only decodes that config (see `src/bundle.rs` for the format).
     println!("cargo:rerun-if-changed=share/");
 
     let out_dir = env::var("OUT_DIR").unwrap();
     let embedded_path = Path::new(&out_dir).join("embedded_configs.rs");
+    let bundle_path = Path::new(&out_dir).join("embedded_configs.bin");
 
     let mut config_files: Vec<String> = Vec::new();
     if let Ok(entries) = fs::read_dir("share") {
@@ -36,21 +38,31 @@ fn compress_configs() {
 
     config_files.sort();
 
-    let mut embedded_output = String::new();
-    embedded_output.push_str("/// Embedded configuration files compiled into the binary when the `embed-configs` feature is enabled.\n");
-    embedded_output.push_str("/// Each entry is a tuple of `(filename, contents)` corresponding to files under `share/conf.*`.\n");
-    embedded_output.push_str("/// This file is generated by build.rs — do not edit.\n");
-    embedded_output.push_str("pub const EMBEDDED_CONFIGS: &[(&str, &str)] = &[\n");
-
+    let mut bundle: Vec<u8> = Vec::new();
+    let mut index = String::new();
     for f in &config_files {
-        // Use include_str! anchored to the manifest dir so the files are included
-        // from the workspace path at compile-time.
-        embedded_output.push_str(&format!(
-            "    (\"{}\", include_str!(concat!(env!(\"CARGO_MANIFEST_DIR\"), \"/share/{}\"))),\n",
-            f, f
+        let content = fs::read(Path::new("share").join(f)).unwrap();
+        let compressed = compress(&content);
+        index.push_str(&format!(
+            "    (\"{}\", {}, {}, {}),\n",
+            f,
+            bundle.len(),
+            compressed.len(),
+            content.len()
         ));
+        bundle.extend_from_slice(&compressed);
     }
 
+    let mut embedded_output = String::new();
+    embedded_output.push_str("// This file is generated by build.rs — do not edit.\n\n");
+    embedded_output.push_str(
+        "/// Configuration files under `share/conf.*`, compressed one by one and concatenated.\n",
+    );
+    embedded_output.push_str("const EMBEDDED_BUNDLE: &[u8] = include_bytes!(concat!(env!(\"OUT_DIR\"), \"/embedded_configs.bin\"));\n\n");
+    embedded_output.push_str("/// `(filename, offset, compressed length, length)` of each config in `EMBEDDED_BUNDLE`,\n");
+    embedded_output.push_str("/// sorted by filename.\n");
+    embedded_output.push_str("const EMBEDDED_INDEX: &[(&str, usize, usize, usize)] = &[\n");
+    embedded_output.push_str(&index);
     embedded_output.push_str("];\n\n");
 
     // Also generate a small list of embedded file names to make testing/debugging easier
@@ -61,9 +73,103 @@ fn compress_configs() {
     }
     embedded_output.push_str("];\n");
 
+    fs::write(&bundle_path, bundle).unwrap();
     fs::write(&embedded_path, embedded_output).unwrap();
     println!(
         "cargo:rustc-env=EMBEDDED_CONFIGS_PATH={}",
         embedded_path.display()
     );
 }
+
+/// Shortest match worth encoding
+const MIN_MATCH: usize = 4;
+/// Farthest back a match can refer to (offsets are 16 bits)
+const MAX_OFFSET: usize = u16::MAX as usize;
+/// Candidates tried per position; more finds longer matches but builds slower
+const MAX_CHAIN: usize = 256;
+const HASH_BITS: u32 = 15;
+const NONE: usize = usize::MAX;
+
+/// Hash of the 4 bytes at `i`
+fn hash(input: &[u8], i: usize) -> usize {
+    let word = u32::from_le_bytes([input[i], input[i + 1], input[i + 2], input[i + 3]]);
+    (word.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize
+}
+
+/// Compress `input` into a sequence of (literals, match) pairs decoded by
+/// `rgrc::bundle::decompress`, finding matches with hash chains.
+fn compress(input: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len() / 2);
+    // Most recent position of each hash, and the previous position with the same hash
+    let mut head = vec![NONE; 1 << HASH_BITS];
+    let mut prev = vec![NONE; input.len()];
+    let insert = |i: usize, head: &mut [usize], prev: &mut [usize]| {
+        if i + MIN_MATCH <= input.len() {
+            let h = hash(input, i);
+            prev[i] = head[h];
+            head[h] = i;
+        }
+    };
+
+    let mut pos = 0;
+    let mut literals = 0;
+    while pos + MIN_MATCH <= input.len() {
+        let (mut best_len, mut best_offset) = (0, 0);
+        let mut candidate = head[hash(input, pos)];
+        let mut chain = 0;
+        while candidate != NONE && pos - candidate <= MAX_OFFSET && chain < MAX_CHAIN {
+            let len = input[candidate..]
+                .iter()
+                .zip(&input[pos..])
+                .take_while(|(a, b)| a == b)
+                .count();
+            if len > best_len {
+                (best_len, best_offset) = (len, pos - candidate);
+            }
+            candidate = prev[candidate];
+            chain += 1;
+        }
+
+        if best_len >= MIN_MATCH {
+            emit(
+                &mut out,
+                &input[literals..pos],
+                Some((best_offset, best_len)),
+            );
+            for i in pos..pos + best_len {
+                insert(i, &mut head, &mut prev);
+            }
+            pos += best_len;
+            literals = pos;
+        } else {
+            insert(pos, &mut head, &mut prev);
+            pos += 1;
+        }
+    }
+    emit(&mut out, &input[literals..], None);
+    out
+}
+
+/// Append one sequence: a token with the literal and match lengths (4 bits each,
+/// extended with 255-runs), the literals, then the match offset
+fn emit(out: &mut Vec<u8>, literals: &[u8], matched: Option<(usize, usize)>) {
+    let match_len = matched.map_or(0, |(_, len)| len - MIN_MATCH);
+    out.push(((literals.len().min(15) as u8) << 4) | match_len.min(15) as u8);
+    push_length(out, literals.len());
+    out.extend_from_slice(literals);
+    if let Some((offset, _)) = matched {
+        out.extend_from_slice(&(offset as u16).to_le_bytes());
+        push_length(out, match_len);
+    }
+}
+
+fn push_length(out: &mut Vec<u8>, len: usize) {
+    if len >= 15 {
+        let mut rest = len - 15;
+        while rest >= 255 {
+            out.push(255);
+            rest -= 255;
+        }
+        out.push(rest as u8);
+    }
+}
@@ -4,6 +4,7 @@
 //! access to the parsed options.
 
 use crate::ColorMode;
+use crate::grc::{MatchLimits, RegexEngine};
 
 /// Debug level for rule debugging output.
 ///
@@ -64,6 +65,9 @@ impl std::str::FromStr for DebugLevel {
 /// - `except_aliases`: Comma-separated list of commands to exclude when
 ///   generating aliases.
 /// - `flush_cache`: Whether to flush and rebuild the cache directory (embed-configs only).
+/// - `regex_engine`: Fallback engine for patterns the fast regex engine rejects.
+/// - `match_limits`: Backtrack limit and per-rule time budget for slow-path rules.
+/// - `jobs`: Worker threads for batched colorization of `--config` input.
 ///
 /// # Example
 ///
@@ -93,6 +97,12 @@ pub struct Args {
     pub debug_level: DebugLevel,
     /// Explicitly specify config file name (e.g., "df" to load conf.df)
     pub config: Option<String>,
+    /// Fallback engine for lookaround/backreference patterns (auto|fast|enhanced|fancy)
+    pub regex_engine: RegexEngine,
+    /// Backtrack limit and per-line time budget for slow-path rules
+    pub match_limits: MatchLimits,
+    /// Worker threads for `--config` input (None = automatic)
+    pub jobs: Option<usize>,
 }
 
 /// Parse command-line arguments
@@ -161,6 +171,9 @@ fn parse_args_impl(args: Vec<String>) -> Result<Args, String> {
     let mut show_version = false;
     let mut show_completions: Option<String> = None;
     let mut config: Option<String> = None;
+    let mut regex_engine = RegexEngine::Auto;
+    let mut match_limits = MatchLimits::default();
+    let mut jobs: Option<usize> = None;
     #[cfg(feature = "debug")]
     let mut debug_level = DebugLevel::Off;
     #[cfg(not(feature = "debug"))]
@@ -213,6 +226,58 @@ fn parse_args_impl(args: Vec<String>) -> Result<Args, String> {
                 config = Some(value.to_string());
                 i = next_i;
             }
+            arg if arg.starts_with("--regex-engine") => {
+                let (value, next_i) = parse_arg_value(&args, i, "regex-engine")?;
+                regex_engine = value.parse()?;
+                if regex_engine == RegexEngine::Fancy && !cfg!(feature = "fancy-regex") {
+                    return Err(
+                        "Regex engine fancy is not available in this build (requires the fancy-regex feature)"
+                            .to_string(),
+                    );
+                }
+                i = next_i;
+            }
+            arg if arg.starts_with("--backtrack-limit") => {
+                let (value, next_i) = parse_arg_value(&args, i, "backtrack-limit")?;
+                match_limits.backtrack_limit = match value.parse::<usize>() {
+                    Ok(limit) if limit > 0 => limit,
+                    _ => {
+                        return Err(format!(
+                            "Invalid backtrack limit: {}. Must be a positive number.",
+                            value
+                        ));
+                    }
+                };
+                i = next_i;
+            }
+            arg if arg.starts_with("--rule-timeout") => {
+                let (value, next_i) = parse_arg_value(&args, i, "rule-timeout")?;
+                // Milliseconds, 0 disables the time budget
+                match_limits.rule_time_budget = match value.parse::<u64>() {
+                    Ok(0) => None,
+                    Ok(ms) => Some(std::time::Duration::from_millis(ms)),
+                    Err(_) => {
+                        return Err(format!(
+                            "Invalid rule timeout: {}. Must be a number of milliseconds.",
+                            value
+                        ));
+                    }
+                };
+                i = next_i;
+            }
+            arg if arg.starts_with("--jobs") => {
+                let (value, next_i) = parse_arg_value(&args, i, "jobs")?;
+                jobs = match value.parse::<usize>() {
+                    Ok(n) if n > 0 => Some(n),
+                    _ => {
+                        return Err(format!(
+                            "Invalid number of jobs: {}. Must be a positive number.",
+                            value
+                        ));
+                    }
+                };
+                i = next_i;
+            }
             "--aliases" => {
                 show_aliases = true;
                 i += 1;
@@ -290,6 +355,9 @@ fn parse_args_impl(args: Vec<String>) -> Result<Args, String> {
         show_completions,
         debug_level,
         config,
+        regex_engine,
+        match_limits,
+        jobs,
     })
 }
 
@@ -308,8 +376,13 @@ pub fn get_completion_script(shell: &str) -> Option<&'static str> {
         return 0
     fi
 
+    if [[ ${COMP_CWORD} -gt 0 && ${COMP_WORDS[COMP_CWORD-1]} == "--regex-engine" ]]; then
+        COMPREPLY=( $(compgen -W "auto fast enhanced fancy" -- "$cur") )
+        return 0
+    fi
+
     if [[ ${cur} == --* ]]; then
-        COMPREPLY=( $(compgen -W "--color --aliases --all-aliases --except --flush-cache --help -h --version -v --completions" -- "$cur") )
+        COMPREPLY=( $(compgen -W "--color --aliases --all-aliases --except --flush-cache --regex-engine --backtrack-limit --rule-timeout --jobs --help -h --version -v --completions" -- "$cur") )
         return 0
     fi
 
@@ -328,7 +401,11 @@ _rgrc() {
     '--aliases[Output shell aliases for available binaries]' \
     '--all-aliases[Output all shell aliases]' \
     '--except=[Exclude commands from alias generation]:commands:' \
-    '--flush-cache[Flush and rebuild cache dir]' \
+    '--flush-cache[Export embedded configs to cache dir]' \
+    '--regex-engine=[Fallback engine for lookaround patterns]:engine:(auto fast enhanced fancy)' \
+    '--backtrack-limit=[Steps allowed per slow-path match]:limit:' \
+    '--rule-timeout=[Per-line time budget for slow-path rules in ms (0=off)]:ms:' \
+    '--jobs=[Worker threads for batched --config input]:jobs:' \
     '--help[Show help]' \
     '--version[Show version]' \
     '--completions=[Print completions for shell]:shell:(bash zsh fish ash)' \
@@ -344,7 +421,11 @@ complete -c rgrc -l color -d 'Override color output (on,off,auto)'
 complete -c rgrc -l aliases -d 'Output shell aliases for detected binaries'
 complete -c rgrc -l all-aliases -d 'Output all aliases'
 complete -c rgrc -l except -r -d 'Exclude commands from alias generation' -a '(__rgrc_list_commands)'
-complete -c rgrc -l flush-cache -d 'Flush cache (embed-configs only)'
+complete -c rgrc -l flush-cache -d 'Export embedded configs (embed-configs only)'
+complete -c rgrc -l regex-engine -r -d 'Fallback engine for lookaround patterns' -a 'auto fast enhanced fancy'
+complete -c rgrc -l backtrack-limit -r -d 'Steps allowed per slow-path match'
+complete -c rgrc -l rule-timeout -r -d 'Per-line time budget for slow-path rules in ms (0=off)'
+complete -c rgrc -l jobs -r -d 'Worker threads for batched --config input'
 complete -c rgrc -l help -d 'Show help'
 complete -c rgrc -l version -s v -d 'Show version'
 complete -c rgrc -l completions -d 'Print completions for shell' -a 'bash zsh fish ash'
@@ -361,7 +442,7 @@ end
         ),
         "ash" => Some(
             r#"# ash / sh completion helper (simple - may need shell support)
-complete -W "--color --aliases --all-aliases --except --flush-cache --help -h --version -v --completions" rgrc
+complete -W "--color --aliases --all-aliases --except --flush-cache --regex-engine --backtrack-limit --rule-timeout --jobs --help -h --version -v --completions" rgrc
 "#,
         ),
         _ => None,
@@ -381,8 +462,18 @@ fn print_help() {
     println!("  --except CMD,..      Exclude commands from alias generation");
     println!("  --completions SHELL  Print shell completion script for SHELL (bash|zsh|fish|ash)");
     #[cfg(feature = "embed-configs")]
-    println!("  --flush-cache        Flush and rebuild cache directory");
+    println!("  --flush-cache        Export embedded configs to the cache directory");
     println!("  --config, -c NAME    Explicit config file name (e.g., df to load conf.df)");
+    println!(
+        "  --regex-engine NAME  Fallback engine for lookaround patterns (auto|fast|enhanced|fancy)"
+    );
+    println!("  --backtrack-limit N  Steps allowed per slow-path match (default: 1000000)");
+    println!(
+        "  --rule-timeout MS    Per-line time budget for slow-path rules (default: 100, 0=off)"
+    );
+    println!(
+        "  --jobs N             Worker threads for --config input (default: all cores for files)"
+    );
     println!("  --help, -h           Show this help message");
     println!("  --version, -V        Show installed rgrc version and exit");
     #[cfg(feature = "debug")]
@@ -739,6 +830,80 @@ mod tests {
         parse_args_impl(args)
     }
 
+    #[test]
+    fn test_parse_regex_engine() {
+        let args = parse_args_helper(vec!["ls"]).unwrap();
+        assert_eq!(args.regex_engine, RegexEngine::Auto);
+
+        let args = parse_args_helper(vec!["--regex-engine=enhanced", "ls"]).unwrap();
+        assert_eq!(args.regex_engine, RegexEngine::Enhanced);
+        assert_eq!(args.command, vec!["ls"]);
+
+        let args = parse_args_helper(vec!["--regex-engine", "fast", "-c", "df"]).unwrap();
+        assert_eq!(args.regex_engine, RegexEngine::Fast);
+        assert_eq!(args.config, Some("df".to_string()));
+
+        let result = parse_args_helper(vec!["--regex-engine=fancy", "ls"]);
+        if cfg!(feature = "fancy-regex") {
+            assert_eq!(result.unwrap().regex_engine, RegexEngine::Fancy);
+        } else {
+            assert!(result.unwrap_err().contains("not available"));
+        }
+
+        let result = parse_args_helper(vec!["--regex-engine=pcre", "ls"]);
+        assert!(result.unwrap_err().contains("Invalid regex engine"));
+
+        let result = parse_args_helper(vec!["--regex-engine"]);
+        assert!(
+            result
+                .unwrap_err()
+                .contains("Missing value for --regex-engine")
+        );
+    }
+
+    #[test]
+    fn test_parse_match_limits() {
+        use std::time::Duration;
+
+        let args = parse_args_helper(vec!["ls"]).unwrap();
+        assert_eq!(args.match_limits, MatchLimits::default());
+
+        let args = parse_args_helper(vec!["--backtrack-limit=5000", "--rule-timeout", "20", "ls"])
+            .unwrap();
+        assert_eq!(args.match_limits.backtrack_limit, 5000);
+        assert_eq!(
+            args.match_limits.rule_time_budget,
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(args.command, vec!["ls"]);
+
+        let args = parse_args_helper(vec!["--rule-timeout=0", "ls"]).unwrap();
+        assert_eq!(args.match_limits.rule_time_budget, None);
+
+        let result = parse_args_helper(vec!["--backtrack-limit=0", "ls"]);
+        assert!(result.unwrap_err().contains("Invalid backtrack limit"));
+
+        let result = parse_args_helper(vec!["--rule-timeout=soon", "ls"]);
+        assert!(result.unwrap_err().contains("Invalid rule timeout"));
+    }
+
+    #[test]
+    fn test_parse_jobs() {
+        let args = parse_args_helper(vec!["-c", "log"]).unwrap();
+        assert_eq!(args.jobs, None);
+
+        let args = parse_args_helper(vec!["--jobs=4", "-c", "log"]).unwrap();
+        assert_eq!(args.jobs, Some(4));
+        let args = parse_args_helper(vec!["--jobs", "1", "ls"]).unwrap();
+        assert_eq!(args.jobs, Some(1));
+        assert_eq!(args.command, vec!["ls"]);
+
+        for value in ["0", "many"] {
+            let result = parse_args_helper(vec!["--jobs", value, "ls"]);
+            assert!(result.unwrap_err().contains("Invalid number of jobs"));
+        }
+    }
+
     #[test]
     fn completion_scripts_present_for_supported_shells() {
         assert!(get_completion_script("bash").is_some());
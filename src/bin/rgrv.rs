@@ -4,6 +4,9 @@
 // in a user-friendly format with file locations and suggestions.
 
 use rgrc::Style;
+use rgrc::grc::{ConfigAst, ConfigLine, ConfigLineKind, ConfigNode, Span};
+use rgrc::macros::{MacroError, Macros};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -20,6 +23,11 @@ fn main() {
     match command.as_str() {
         "grc" => validate_grc_config(&args),
         "conf" => validate_conf_files(&args),
+        "engines" => report_engines(&args),
+        "fmt" => format_conf_files(&args),
+        "lint" => lint_conf_files(&args),
+        "test" => test_conf_file(&args),
+        "explain" => explain_conf_file(&args),
         "--help" | "-h" => print_help(&args[0]),
         "--version" | "-V" => println!("rgrc-validate 0.1.0"),
         _ => {
@@ -39,6 +47,15 @@ fn print_help(prog: &str) {
     println!("Commands:");
     println!("  grc [PATH]        Validate grc.conf configuration file");
     println!("  conf [PATH ...]   Validate color configuration files (conf.*)");
+    println!("  engines [--regex-engine=NAME] [PATH ...]");
+    println!("                    List rules that do not run on the fast regex engine");
+    println!("  lint [PATH ...]   Report likely mistakes in conf files (nolint=ID allows one)");
+    println!("  test CONF --input FILE --expect FILE [--update]");
+    println!("                    Compare the colouring of FILE with a stored rendering");
+    println!("  explain CONF [--input FILE]");
+    println!("                    Show how each rule colours each line of FILE (or stdin)");
+    println!("  fmt [--check] PATH ...");
+    println!("                    Rewrite conf files in canonical layout (--check: only report)");
     println!("  --help, -h        Show this help message");
     println!("  --version, -v     Show version");
     println!();
@@ -56,6 +73,26 @@ fn print_help(prog: &str) {
         "  {} conf share/conf.*      # Validate all conf files",
         prog
     );
+    println!(
+        "  {} engines                # Show slow-path rules in all conf files",
+        prog
+    );
+    println!(
+        "  {} lint share/conf.ping   # Check one conf file for likely mistakes",
+        prog
+    );
+    println!(
+        "  {} test conf.ping --input share/tests/ping.txt --expect share/tests/ping.ansi",
+        prog
+    );
+    println!(
+        "  {} fmt --check share/conf.* # Fail if a conf file is not formatted",
+        prog
+    );
+    println!(
+        "  ping -c1 localhost | {} explain conf.ping # Trace the colouring of ping",
+        prog
+    );
 }
 
 /// Validate grc.conf file
@@ -142,6 +179,7 @@ fn validate_conf_files(args: &[String]) {
                         print_errors(&errors);
                         total_errors += errors.len();
                     }
+                    print_translations(&content);
                     validated_files += 1;
                 }
                 Err(e) => {
@@ -214,6 +252,7 @@ fn validate_conf_files(args: &[String]) {
                             print_errors(&errors);
                             total_errors += errors.len();
                         }
+                        print_translations(&content);
                         validated_files += 1;
                     }
                     Err(e) => {
@@ -249,6 +288,882 @@ fn validate_conf_files(args: &[String]) {
     }
 }
 
+/// Report which regex backend handles each rule of the given conf.* files
+fn report_engines(args: &[String]) {
+    let mut engine = rgrc::grc::RegexEngine::Auto;
+    let mut paths = Vec::new();
+
+    for arg in &args[2..] {
+        if let Some(value) = arg.strip_prefix("--regex-engine=") {
+            engine = match value.parse() {
+                Ok(engine) => engine,
+                Err(e) => {
+                    eprintln!("{} {}", Style::new().red().apply_to("✗"), e);
+                    std::process::exit(1);
+                }
+            };
+        } else {
+            paths.push(PathBuf::from(arg));
+        }
+    }
+
+    // Default to all conf.* files in the default directory
+    if paths.is_empty() {
+        paths = default_conf_files();
+    }
+
+    println!(
+        "{}Regex engines per rule (fallback: {})...",
+        Style::new().bold().apply_to(""),
+        engine
+    );
+    println!();
+
+    let mut total_rules = 0;
+    let mut total_slow = 0;
+    let mut total_lowered = 0;
+    let mut total_errors = 0;
+
+    for path in &paths {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!(
+                    "  {} {} (read error: {})",
+                    Style::new().red().apply_to("✗"),
+                    path.display(),
+                    e
+                );
+                total_errors += 1;
+                continue;
+            }
+        };
+
+        let mut rules = 0;
+        let mut slow = Vec::new();
+        for (line, pattern) in expand_conf_macros(&ConfigAst::parse(&content)) {
+            let pattern = match pattern {
+                Ok(pattern) => pattern,
+                Err(e) => {
+                    slow.push((line, "error".to_string(), e.to_string()));
+                    total_errors += 1;
+                    continue;
+                }
+            };
+            rules += 1;
+            // Same optimization as rules loaded by rgrc
+            match rgrc::grc::CompiledRegex::with_engine(&pattern, engine).map(|re| re.optimized()) {
+                Ok(re) if re.backend() == rgrc::grc::RegexBackend::Lowered => total_lowered += 1,
+                Ok(re) if re.backend().is_slow_path() => {
+                    slow.push((line, re.backend().to_string(), pattern))
+                }
+                Ok(_) => {}
+                Err(_) => {
+                    slow.push((line, "error".to_string(), pattern));
+                    total_errors += 1;
+                }
+            }
+        }
+
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if slow.is_empty() {
+            println!(
+                "  {} {}: all {} rules use the fast path",
+                Style::new().green().apply_to("✓"),
+                name,
+                rules
+            );
+        } else {
+            println!(
+                "  {} {}: {} of {} rules use the slow path",
+                Style::new().yellow().apply_to("!"),
+                name,
+                slow.len(),
+                rules
+            );
+            for (line, backend, pattern) in &slow {
+                println!("    line {:<4} {:<8} {}", line, backend, pattern);
+            }
+        }
+        total_rules += rules;
+        total_slow += slow.len();
+    }
+
+    println!();
+    println!(
+        "Summary: {} files, {} rules, {} on the slow path, {} lowered to the fast path, {} errors",
+        paths.len(),
+        total_rules,
+        total_slow,
+        total_lowered,
+        total_errors
+    );
+
+    if total_errors > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// All conf.* files in the default directory, sorted
+fn default_conf_files() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = match fs::read_dir(find_conf_dir()) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().starts_with("conf."))
+            .map(|e| e.path())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+    paths
+}
+
+/// Rewrite conf.* files in the layout of [`ConfigAst::formatted`]. With
+/// `--check`, only report the files that would change and exit non-zero.
+fn format_conf_files(args: &[String]) {
+    let check = args[2..].iter().any(|arg| arg == "--check");
+    let paths: Vec<PathBuf> = args[2..]
+        .iter()
+        .filter(|arg| *arg != "--check")
+        .map(PathBuf::from)
+        .collect();
+
+    if paths.is_empty() {
+        eprintln!("Error: fmt needs at least one conf file");
+        std::process::exit(1);
+    }
+
+    let mut changed = 0;
+    let mut total_errors = 0;
+
+    for path in &paths {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!(
+                    "  {} {} (read error: {})",
+                    Style::new().red().apply_to("✗"),
+                    path.display(),
+                    e
+                );
+                total_errors += 1;
+                continue;
+            }
+        };
+
+        let formatted = ConfigAst::parse(&content).formatted();
+        if formatted == content {
+            println!(
+                "  {} {}",
+                Style::new().green().apply_to("✓"),
+                path.display()
+            );
+            continue;
+        }
+        changed += 1;
+
+        if check {
+            // First line that differs, for a quick look in CI logs
+            let (old, new) = (content.split('\n'), formatted.split('\n'));
+            let shorter = old.clone().count().min(new.clone().count());
+            let line = old
+                .zip(new)
+                .position(|(old, new)| old != new)
+                .map_or(shorter, |idx| idx + 1);
+            println!(
+                "  {} {} (not formatted, first difference at line {})",
+                Style::new().red().apply_to("✗"),
+                path.display(),
+                line
+            );
+        } else if let Err(e) = fs::write(path, &formatted) {
+            eprintln!(
+                "  {} {} (write error: {})",
+                Style::new().red().apply_to("✗"),
+                path.display(),
+                e
+            );
+            total_errors += 1;
+        } else {
+            println!(
+                "  {} {} (reformatted)",
+                Style::new().yellow().apply_to("!"),
+                path.display()
+            );
+        }
+    }
+
+    println!();
+    if check {
+        println!(
+            "Summary: {} files, {} not formatted, {} errors",
+            paths.len(),
+            changed,
+            total_errors
+        );
+    } else {
+        println!(
+            "Summary: {} files, {} reformatted, {} errors",
+            paths.len(),
+            changed,
+            total_errors
+        );
+    }
+
+    if total_errors > 0 || (check && changed > 0) {
+        std::process::exit(1);
+    }
+}
+
+/// IDs of the checks of `rgrv lint`; `nolint=ID[,ID...]` in a rule turns them off for it
+const LINTS: &[&str] = &[
+    "colour-count",
+    "shadowed",
+    "duplicate-regexp",
+    "replace-group",
+    "empty-match",
+    "slow-lookaround",
+];
+
+/// A rule as `rgrv lint` sees it
+struct LintRule {
+    /// Line of the regexp=
+    line: usize,
+    /// Pattern with macros expanded
+    pattern: String,
+    regex: rgrc::grc::CompiledRegex,
+    /// Number of comma-separated colours=, if set
+    colours: Option<usize>,
+    count: String,
+    skip: bool,
+    replace: String,
+    nolint: Vec<String>,
+}
+
+/// A problem found by `rgrv lint`
+struct Lint {
+    line: usize,
+    id: &'static str,
+    message: String,
+}
+
+/// Report likely mistakes in conf.* files, beyond what `rgrv conf` rejects
+fn lint_conf_files(args: &[String]) {
+    let mut paths: Vec<PathBuf> = args[2..].iter().map(PathBuf::from).collect();
+    if paths.is_empty() {
+        paths = default_conf_files();
+    }
+
+    println!(
+        "{}Linting color configuration files...",
+        Style::new().bold().apply_to("")
+    );
+    println!();
+
+    let mut total_lints = 0;
+    let mut total_errors = 0;
+
+    for path in &paths {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!(
+                    "  {} {} (read error: {})",
+                    Style::new().red().apply_to("✗"),
+                    path.display(),
+                    e
+                );
+                total_errors += 1;
+                continue;
+            }
+        };
+
+        let lints = lint_rules(&lint_rules_of(&ConfigAst::parse(&content)));
+        if lints.is_empty() {
+            println!(
+                "  {} {}",
+                Style::new().green().apply_to("✓"),
+                path.display()
+            );
+        } else {
+            println!(
+                "  {} {}: {} warnings",
+                Style::new().yellow().apply_to("!"),
+                path.display(),
+                lints.len()
+            );
+            for lint in &lints {
+                println!("    line {:<4} {:<16} {}", lint.line, lint.id, lint.message);
+            }
+        }
+        total_lints += lints.len();
+    }
+
+    println!();
+    println!(
+        "Summary: {} files, {} warnings, {} errors",
+        paths.len(),
+        total_lints,
+        total_errors
+    );
+
+    if total_lints > 0 || total_errors > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// Rules of a conf file with a regexp that compiles; `rgrv conf` reports the others
+fn lint_rules_of(ast: &ConfigAst) -> Vec<LintRule> {
+    let expanded: HashMap<_, _> = expand_conf_macros(ast).into_iter().collect();
+    let mut rules = Vec::new();
+    for node in &ast.nodes {
+        let ConfigNode::Rule(lines) = node else {
+            continue;
+        };
+        let mut regexp = None;
+        let mut colours = None;
+        let mut count = "more";
+        let mut skip = false;
+        let mut replace = "";
+        let mut nolint = Vec::new();
+        for line in lines {
+            let Some((key, value)) = line.key_value() else {
+                continue;
+            };
+            match key {
+                "regexp" => regexp = Some((line.number, &expanded[&line.number])),
+                "colours" | "colors" | "colour" => colours = Some(value.split(',').count()),
+                "count" => count = value,
+                "skip" => skip = matches!(value.to_lowercase().as_str(), "true" | "1" | "yes"),
+                "replace" => replace = value,
+                "nolint" => nolint.extend(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|id| !id.is_empty())
+                        .map(str::to_string),
+                ),
+                _ => {}
+            }
+        }
+        let Some((line, Ok(pattern))) = regexp else {
+            continue;
+        };
+        // Same optimization as rules loaded by rgrc
+        let Ok(regex) = rgrc::grc::CompiledRegex::new(pattern) else {
+            continue;
+        };
+        rules.push(LintRule {
+            line,
+            pattern: pattern.clone(),
+            regex: regex.optimized(),
+            colours,
+            count: count.to_string(),
+            skip,
+            replace: replace.to_string(),
+            nolint,
+        });
+    }
+    rules
+}
+
+/// Run every lint on the rules of one file, in rule order
+fn lint_rules(rules: &[LintRule]) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    for (idx, rule) in rules.iter().enumerate() {
+        let mut lint = |id: &'static str, message: String| {
+            if !rule.nolint.iter().any(|allowed| allowed == id) {
+                lints.push(Lint {
+                    line: rule.line,
+                    id,
+                    message,
+                });
+            }
+        };
+        let groups = rule.regex.captures_len() - 1;
+
+        // A single colour styles the whole match, whatever the groups are for
+        match rule.colours {
+            Some(colours) if colours > groups + 1 => lint(
+                "colour-count",
+                format!(
+                    "{} for {}: {}",
+                    plural(colours, "colour"),
+                    plural(groups, "group"),
+                    match colours - groups - 1 {
+                        1 => "the last one never applies".to_string(),
+                        extra => format!("the last {} never apply", extra),
+                    }
+                ),
+            ),
+            Some(colours) if colours > 1 && colours <= groups => lint(
+                "colour-count",
+                format!(
+                    "{} for {}: {} no colour",
+                    plural(colours, "colour"),
+                    plural(groups, "group"),
+                    if colours == groups {
+                        format!("group {} has", groups)
+                    } else {
+                        format!("groups {} to {} have", colours, groups)
+                    }
+                ),
+            ),
+            _ => {}
+        }
+
+        let stops = |earlier: &LintRule| earlier.count == "stop" && !earlier.skip;
+        if let Some(earlier) = rules[..idx].iter().find(|r| r.pattern == rule.pattern) {
+            if stops(earlier) {
+                lint(
+                    "shadowed",
+                    format!(
+                        "never applies: the count=stop rule at line {} has the same regexp",
+                        earlier.line
+                    ),
+                );
+            } else {
+                lint(
+                    "duplicate-regexp",
+                    format!("same regexp as the rule at line {}", earlier.line),
+                );
+            }
+        } else if let Some(earlier) = rules[..idx]
+            .iter()
+            .find(|earlier| stops(earlier) && matches_every_line_of(earlier, rule))
+        {
+            lint(
+                "shadowed",
+                format!(
+                    "never applies: the count=stop rule at line {} matches every line it matches",
+                    earlier.line
+                ),
+            );
+        }
+
+        for group in replace_groups(&rule.replace) {
+            if group > groups {
+                lint(
+                    "replace-group",
+                    format!(
+                        "replace= uses \\{}, but the regexp has {}",
+                        group,
+                        plural(groups, "group")
+                    ),
+                );
+            }
+        }
+
+        if rule.count == "more" && matches_empty(&rule.regex) {
+            lint(
+                "empty-match",
+                "regexp can match the empty string, so with count=more it matches at every position".to_string(),
+            );
+        }
+
+        if rule.regex.backend().is_slow_path()
+            && let Some(rewrite) = rgrc::lowering::lookarounds_as_groups(rule.regex.as_str())
+            && rgrc::grc::CompiledRegex::with_engine(&rewrite, rgrc::grc::RegexEngine::Fast).is_ok()
+        {
+            lint(
+                "slow-lookaround",
+                format!(
+                    "runs on the {} engine for its lookarounds only; {} runs on the fast engine, with the lookaround text in the whole match",
+                    rule.regex.backend(),
+                    rewrite
+                ),
+            );
+        }
+    }
+    lints
+}
+
+/// Whether every line `later` matches is also matched by `earlier`. Only clear
+/// cases are recognised: `earlier` matches any line at all, or is a plain literal
+/// that every match of `later` contains.
+fn matches_every_line_of(earlier: &LintRule, later: &LintRule) -> bool {
+    let pattern = earlier.regex.as_str();
+    // An empty match needs nothing from the line, unless an assertion is involved
+    let body = pattern.strip_prefix('^').unwrap_or(pattern);
+    let asserts = body.contains(['^', '$'])
+        || ["\\b", "\\B", "\\A", "\\z", "\\Z", "(?=", "(?!", "(?<"]
+            .iter()
+            .any(|assertion| body.contains(assertion));
+    if !asserts && earlier.regex.is_match("") {
+        return true;
+    }
+    let required = rgrc::prefilter::required_literals;
+    match (required(pattern).as_deref(), required(later.regex.as_str())) {
+        (Some([literal]), Some(literals)) => {
+            regex_lite::escape(literal) == pattern
+                && literals.iter().all(|l| l.contains(literal.as_str()))
+        }
+        _ => false,
+    }
+}
+
+/// Whether `regex` finds an empty match in a few typical lines
+fn matches_empty(regex: &rgrc::grc::CompiledRegex) -> bool {
+    ["", "x", "0", " "].iter().any(|text| {
+        regex
+            .captures_from_pos(text, 0)
+            .and_then(|caps| caps.get(0))
+            .is_some_and(|m| m.start() == m.end())
+    })
+}
+
+/// Group numbers referenced as `\N` in a replace= value
+fn replace_groups(replace: &str) -> Vec<usize> {
+    replace
+        .split('\\')
+        .skip(1)
+        .filter_map(|rest| {
+            let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+            rest[..digits].parse().ok()
+        })
+        .collect()
+}
+
+/// `n thing` or `n things`
+fn plural(n: usize, thing: &str) -> String {
+    if n == 1 {
+        format!("1 {}", thing)
+    } else {
+        format!("{} {}s", n, thing)
+    }
+}
+
+/// Colorize a sample input with a conf file and compare the result with a stored
+/// rendering (a golden file), or store it with `--update`. Lines are compared by
+/// text and style, and differences are shown with style keywords instead of escapes.
+fn test_conf_file(args: &[String]) {
+    let mut conf = None;
+    let mut input = None;
+    let mut expect = None;
+    let mut update = false;
+    let mut rest = args[2..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--input" => input = rest.next().map(PathBuf::from),
+            "--expect" => expect = rest.next().map(PathBuf::from),
+            "--update" => update = true,
+            _ => conf = Some(PathBuf::from(arg)),
+        }
+    }
+    let (Some(conf), Some(input), Some(expect)) = (conf, input, expect) else {
+        eprintln!("Error: test needs CONF, --input FILE and --expect FILE");
+        std::process::exit(1);
+    };
+    // A bare name such as conf.ping is looked up in the conf directory
+    let conf = if conf.exists() || conf.components().count() > 1 {
+        conf
+    } else {
+        find_conf_dir().join(conf)
+    };
+
+    let fail = |message: String| -> ! {
+        eprintln!("  {} {}", Style::new().red().apply_to("✗"), message);
+        std::process::exit(1);
+    };
+
+    // Rules with problems are dropped; the rest still colour the sample
+    let mut has_problems = false;
+    let rules = rgrc::try_load_grcat_config(conf.to_string_lossy()).unwrap_or_else(|e| {
+        has_problems = true;
+        e.reported()
+    });
+    let fail_on_problems = || {
+        if has_problems {
+            fail(format!("{}: the conf file has problems", conf.display()));
+        }
+    };
+    let sample = match fs::read(&input) {
+        Ok(sample) => sample,
+        Err(e) => fail(format!("{} (read error: {})", input.display(), e)),
+    };
+    let mut output = Vec::new();
+    if let Err(e) = rgrc::colorizer::colorize_regex(&mut sample.as_slice(), &mut output, &rules) {
+        fail(format!("{}: {}", input.display(), e));
+    }
+    let actual = String::from_utf8_lossy(&output);
+
+    if update {
+        if let Err(e) = fs::write(&expect, actual.as_bytes()) {
+            fail(format!("{} (write error: {})", expect.display(), e));
+        }
+        println!(
+            "  {} {} (updated)",
+            Style::new().yellow().apply_to("!"),
+            expect.display()
+        );
+        fail_on_problems();
+        return;
+    }
+
+    let expected = match fs::read_to_string(&expect) {
+        Ok(expected) => expected,
+        Err(e) => fail(format!(
+            "{} (read error: {}; use --update to create it)",
+            expect.display(),
+            e
+        )),
+    };
+
+    let expected: Vec<String> = expected.lines().map(rgrc::style::ansi_to_markup).collect();
+    let actual: Vec<String> = actual.lines().map(rgrc::style::ansi_to_markup).collect();
+    let differences: Vec<usize> = (0..expected.len().max(actual.len()))
+        .filter(|&idx| expected.get(idx) != actual.get(idx))
+        .collect();
+
+    if differences.is_empty() {
+        println!(
+            "  {} {} matches {} ({} lines)",
+            Style::new().green().apply_to("✓"),
+            conf.display(),
+            expect.display(),
+            actual.len()
+        );
+        fail_on_problems();
+        return;
+    }
+
+    println!(
+        "  {} {}: {} of {} lines differ from {}",
+        Style::new().red().apply_to("✗"),
+        conf.display(),
+        differences.len(),
+        expected.len().max(actual.len()),
+        expect.display()
+    );
+    for idx in differences {
+        println!("    line {}", idx + 1);
+        if let Some(line) = expected.get(idx) {
+            println!("      {} {}", Style::new().red().apply_to("-"), line);
+        }
+        if let Some(line) = actual.get(idx) {
+            println!("      {} {}", Style::new().green().apply_to("+"), line);
+        }
+    }
+    println!();
+    println!("Run with --update to accept the new colouring.");
+    std::process::exit(1);
+}
+
+/// Show, line by line, how each rule of a conf file colours the input
+fn explain_conf_file(args: &[String]) {
+    let mut conf = None;
+    let mut input = None;
+    let mut rest = args[2..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--input" => input = rest.next().map(PathBuf::from),
+            _ => conf = Some(PathBuf::from(arg)),
+        }
+    }
+    let Some(conf) = conf else {
+        eprintln!("Error: explain needs CONF");
+        std::process::exit(1);
+    };
+    // A bare name such as conf.ping is looked up in the conf directory
+    let conf = if conf.exists() || conf.components().count() > 1 {
+        conf
+    } else {
+        find_conf_dir().join(conf)
+    };
+
+    let fail = |message: String| -> ! {
+        eprintln!("  {} {}", Style::new().red().apply_to("✗"), message);
+        std::process::exit(1);
+    };
+
+    // Rules with problems are dropped; the rest are explained
+    let rules =
+        rgrc::try_load_grcat_config(conf.to_string_lossy()).unwrap_or_else(|e| e.reported());
+    let sample = match &input {
+        Some(input) => {
+            fs::read(input).map_err(|e| format!("{} (read error: {})", input.display(), e))
+        }
+        None => {
+            let mut sample = Vec::new();
+            std::io::Read::read_to_end(&mut std::io::stdin(), &mut sample)
+                .map(|_| sample)
+                .map_err(|e| format!("stdin (read error: {})", e))
+        }
+    };
+    let sample = sample.unwrap_or_else(|message| fail(message));
+
+    for (idx, line) in String::from_utf8_lossy(&sample).lines().enumerate() {
+        if idx > 0 {
+            println!();
+        }
+        println!("line {}: {}", idx + 1, line);
+        explain_line(line, &rules);
+    }
+}
+
+/// Print what each rule does to `line`, the rule group that wins each styled
+/// region, and the resulting colouring
+fn explain_line(line: &str, rules: &[rgrc::grc::GrcatConfigEntry]) {
+    use rgrc::colorizer::{NotRun, SpanOwner};
+    use rgrc::grc::GrcatConfigEntryCount;
+
+    let explanation = rgrc::colorizer::explain_line(line, rules);
+    if explanation.rules.is_empty() {
+        println!("  (no rules run on an empty line)");
+        return;
+    }
+    let text = |line: &str, (start, end): (usize, usize)| -> String {
+        format!(
+            "{}..{} {:?}",
+            start,
+            end,
+            line.get(start..end).unwrap_or("")
+        )
+    };
+
+    for trace in &explanation.rules {
+        let rule = &rules[trace.rule];
+        let outcome = match trace.not_run {
+            Some(NotRun::Skip) => "not run (skip=true)".to_string(),
+            Some(NotRun::Disabled) => "not run (disabled)".to_string(),
+            Some(NotRun::Prefilter) => "not run (a literal it needs is missing)".to_string(),
+            Some(NotRun::Stopped) => "not run (an earlier rule has count=stop)".to_string(),
+            Some(NotRun::Replaced) => "not run (an earlier rule replaced text)".to_string(),
+            None if trace.matches.is_empty() => "no match".to_string(),
+            None => format!("matched {}", plural(trace.matches.len(), "time")),
+        };
+        println!(
+            "  rule {} {}: {}",
+            rule.label(trace.rule),
+            rule.regex.as_str(),
+            outcome
+        );
+
+        for m in &trace.matches {
+            for (group, range) in m.groups.iter().enumerate() {
+                let Some(range) = *range else {
+                    println!("    group {} did not take part", group);
+                    continue;
+                };
+                let effect = if !m.painted.contains(&group) {
+                    "no colour".to_string()
+                } else {
+                    // How much of the range this group still owns at the end, and
+                    // which rule groups own the rest
+                    let overlapping = explanation
+                        .spans
+                        .iter()
+                        .filter(|s| s.start < range.1 && s.end > range.0);
+                    let (own, others): (Vec<&SpanOwner>, Vec<&SpanOwner>) =
+                        overlapping.partition(|s| s.rule == trace.rule && s.group == group);
+                    let kept: usize = own
+                        .iter()
+                        .map(|s| s.end.min(range.1) - s.start.max(range.0))
+                        .sum();
+                    let mut winners: Vec<String> = Vec::new();
+                    for s in others {
+                        let winner = if s.rule == trace.rule {
+                            format!("group {} of this rule", s.group)
+                        } else {
+                            format!("rule {} group {}", rules[s.rule].label(s.rule), s.group)
+                        };
+                        if !winners.contains(&winner) {
+                            winners.push(winner);
+                        }
+                    }
+                    let keywords = rule.colors[group].keywords();
+                    if kept == 0 && !winners.is_empty() {
+                        format!("{} (overridden by {})", keywords, winners.join(", "))
+                    } else if kept < range.1 - range.0 && !winners.is_empty() {
+                        format!("{} (partly overridden by {})", keywords, winners.join(", "))
+                    } else {
+                        keywords
+                    }
+                };
+                let indent = if group == 0 { "  " } else { "    " };
+                let name = if group == 0 { "match" } else { "group" };
+                println!(
+                    "  {}{} {} {} -> {}",
+                    indent,
+                    name,
+                    group,
+                    text(line, range),
+                    effect
+                );
+            }
+        }
+
+        if let Some(reason) = &trace.disabled {
+            println!("    disabled: {}", reason);
+        }
+        if let Some(replaced) = &trace.replaced {
+            println!("    replace={:?}: line is now {:?}", rule.replace, replaced);
+            println!("    (later rules are not run)");
+        } else if !trace.matches.is_empty() {
+            match rule.count {
+                GrcatConfigEntryCount::Once => println!("    count=once: first match only"),
+                GrcatConfigEntryCount::Stop => {
+                    println!("    count=stop: first match only, later rules are not run")
+                }
+                GrcatConfigEntryCount::More => {}
+            }
+        }
+    }
+
+    println!("  spans:");
+    if explanation.spans.is_empty() {
+        println!("    (none)");
+    }
+    for span in &explanation.spans {
+        let rule = &rules[span.rule];
+        println!(
+            "    {} <- rule {} group {} [{}]",
+            text(&explanation.line, (span.start, span.end)),
+            rule.label(span.rule),
+            span.group,
+            rule.colors[span.group].keywords()
+        );
+    }
+    println!(
+        "  result: {}",
+        rgrc::style::ansi_to_markup(&explanation.output)
+    );
+}
+
+/// Print the Python `re` rewrites applied to the regexp= lines of a conf file
+fn print_translations(content: &str) {
+    for (line, pattern) in expand_conf_macros(&ConfigAst::parse(content)) {
+        let Ok(pattern) = pattern else {
+            continue;
+        };
+        for rewrite in rgrc::translate::translate(&pattern).rewrites {
+            println!(
+                "    {} line {}: {}",
+                Style::new().cyan().apply_to("note:"),
+                line,
+                rewrite
+            );
+        }
+    }
+}
+
+/// Line numbers and patterns of the regexp= lines of a conf file, with `{NAME}`
+/// macros expanded as rgrc does, plus the `define` lines that fail.
+fn expand_conf_macros(ast: &ConfigAst) -> Vec<(usize, Result<String, MacroError>)> {
+    let mut macros = Macros::global().clone();
+    let mut expanded = Vec::new();
+    for node in &ast.nodes {
+        for line in node.lines() {
+            if let Some(Err(e)) = line.define_statement().and_then(|d| macros.define_line(d)) {
+                expanded.push((line.number, Err(e)));
+            } else if let Some(("regexp", pattern)) = line.key_value() {
+                expanded.push((line.number, macros.expand(pattern)));
+            }
+        }
+    }
+    expanded
+}
+
 /// Validate grc.conf format
 fn validate_grc_content(content: &str, path: &Path, errors: &mut Vec<ValidationError>) {
     let lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
@@ -277,6 +1192,7 @@ fn validate_grc_content(content: &str, path: &Path, errors: &mut Vec<ValidationE
             errors.push(ValidationError {
                 path: path.to_path_buf(),
                 line: line_num,
+                column: None,
                 error_type: "RegexError".to_string(),
                 message: format!("Invalid regex: {}", e),
                 suggestion: Some(
@@ -294,6 +1210,7 @@ fn validate_grc_content(content: &str, path: &Path, errors: &mut Vec<ValidationE
             errors.push(ValidationError {
                 path: path.to_path_buf(),
                 line: line_num,
+                column: None,
                 error_type: "FormatError".to_string(),
                 message: "Missing config file reference after regex pattern".to_string(),
                 suggestion: Some("Add config file name on next line, e.g., conf.ping".to_string()),
@@ -307,6 +1224,7 @@ fn validate_grc_content(content: &str, path: &Path, errors: &mut Vec<ValidationE
             errors.push(ValidationError {
                 path: path.to_path_buf(),
                 line: next_line_num,
+                column: None,
                 error_type: "FormatError".to_string(),
                 message: "Expected config file reference after regex pattern".to_string(),
                 suggestion: Some("Format:\n  regex_pattern\n  conf.name".to_string()),
@@ -324,6 +1242,7 @@ fn validate_grc_content(content: &str, path: &Path, errors: &mut Vec<ValidationE
                 errors.push(ValidationError {
                     path: path.to_path_buf(),
                     line: next_line_num,
+                    column: None,
                     error_type: "FileNotFound".to_string(),
                     message: format!("Config file not found: {}", config_line),
                     suggestion: Some(format!("Create {} or check file name", config_line)),
@@ -335,282 +1254,293 @@ fn validate_grc_content(content: &str, path: &Path, errors: &mut Vec<ValidationE
     }
 }
 
-/// Validate conf.* file format
-fn validate_conf_content(content: &str, path: &Path, errors: &mut Vec<ValidationError>) {
-    let lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
-    let mut i = 0;
-
-    while i < lines.len() {
-        let line_num = i + 1;
-        let line = &lines[i];
+/// Keys of a rule that rgrc applies
+const RULE_KEYS: &[&str] = &[
+    "regexp", "colours", "colors", "colour", "count", "skip", "replace", "id", "overlay", "tags",
+    "severity", "nolint",
+];
 
-        // Skip empty lines and comments
-        if line.trim().is_empty() || line.trim().starts_with('#') {
-            i += 1;
-            continue;
+/// Validate conf.* file format, reading it as rgrc does (see [`ConfigAst`])
+fn validate_conf_content(content: &str, path: &Path, errors: &mut Vec<ValidationError>) {
+    let ast = ConfigAst::parse(content);
+    // regexp= and failing define lines by line number, with macros expanded
+    let expanded: HashMap<usize, Result<String, MacroError>> =
+        expand_conf_macros(&ast).into_iter().collect();
+
+    for node in &ast.nodes {
+        match node {
+            ConfigNode::Rule(lines) => validate_rule(lines, &expanded, path, errors),
+            ConfigNode::Trivia(line) if line.kind == ConfigLineKind::Other => {
+                validate_other_line(line, path, errors)
+            }
+            // Includes are checked when loading; blanks, comments and separators are fine
+            ConfigNode::Trivia(_) | ConfigNode::Include(_) => {}
         }
+    }
+}
 
-        // Check for regexp= line
-        if let Some(regex_part) = line.strip_prefix("regexp=") {
-            // Validate regex using CompiledRegex (supports fancy regex features)
-            if let Err(e) = rgrc::grc::CompiledRegex::new(regex_part) {
-                errors.push(ValidationError {
-                    path: path.to_path_buf(),
-                    line: line_num,
-                    error_type: "RegexError".to_string(),
-                    message: format!("Invalid regex: {}", e),
-                    suggestion: Some(
-                        "Check regex syntax (escape special characters with \\)".to_string(),
-                    ),
-                });
+/// Validate the lines of one rule, the keys as rgrc applies them
+fn validate_rule(
+    lines: &[ConfigLine],
+    expanded: &HashMap<usize, Result<String, MacroError>>,
+    path: &Path,
+    errors: &mut Vec<ValidationError>,
+) {
+    let error =
+        |line: &ConfigLine, span: Span, error_type: &str, message: String, suggestion: &str| {
+            ValidationError {
+                path: path.to_path_buf(),
+                line: line.number,
+                column: Some(span.column()),
+                error_type: error_type.to_string(),
+                message,
+                suggestion: Some(suggestion.to_string()),
             }
+        };
+    let mut has_regexp = false;
+    let mut has_keys = false;
 
-            // Look for colours= line
-            i += 1;
-
-            while i < lines.len() {
-                let next_line_num = i + 1;
-                let next_line = &lines[i];
-
-                if next_line.trim().is_empty() {
-                    i += 1;
-                    continue;
+    for line in lines {
+        let (key, value, value_span) = match &line.kind {
+            ConfigLineKind::KeyValue { key, value } => {
+                (key.text(&line.text), value.text(&line.text), *value)
+            }
+            ConfigLineKind::Define { .. } => {
+                // A define line only matters when it fails
+                if let Some(Err(e)) = expanded.get(&line.number) {
+                    errors.push(macro_error(line, e, path));
                 }
-
-                if next_line.starts_with("colours=") || next_line.starts_with("colour=") {
-                    let style_part = if let Some(stripped) = next_line.strip_prefix("colours=") {
-                        stripped
-                    } else if let Some(stripped) = next_line.strip_prefix("colour=") {
-                        stripped
-                    } else {
-                        // This shouldn't happen due to the starts_with check above
-                        next_line
-                    };
-
-                    // Validate styles
-                    validate_style_definition(style_part, next_line_num, path, errors);
-                    i += 1;
-
-                    // Continue to check for additional config lines after colours=
-                    while i < lines.len() {
-                        let config_line_num = i + 1;
-                        let config_line = &lines[i];
-
-                        if config_line.trim().is_empty() {
-                            i += 1;
-                            continue;
-                        }
-
-                        if config_line.starts_with("count=")
-                            || config_line.starts_with("skip=")
-                            || config_line.starts_with("replace=")
-                            || config_line.starts_with("#")
-                        {
-                            // Valid config lines or comments after colours=, skip them
-                            i += 1;
-                        } else if config_line.starts_with("regexp=") {
-                            // New regexp= starts a new rule, break out to handle it
-                            break;
-                        } else if config_line.starts_with("=======")
-                            || config_line.starts_with("-")
-                            || config_line.starts_with(".........")
-                            || config_line.starts_with("==")
-                            || config_line.starts_with("%%%%%%%")
-                        {
-                            // End of rule
-                            break;
-                        } else {
-                            // Unexpected line after colours=
-                            errors.push(ValidationError {
-                                path: path.to_path_buf(),
-                                line: config_line_num,
-                                error_type: "FormatError".to_string(),
-                                message: format!("Unexpected line after colours=: {}", config_line),
-                                suggestion: Some(
-                                    "Expected count=, skip=, replace=, regexp= lines or separator"
-                                        .to_string(),
-                                ),
-                            });
-                            i += 1;
-                        }
-                    }
-                    break;
-                } else if next_line.starts_with("count=")
-                    || next_line.starts_with("skip=")
-                    || next_line.starts_with("replace=")
-                    || next_line.starts_with("#")
-                {
-                    // Valid config lines or comments, skip them
-                    i += 1;
-                } else if next_line.starts_with("regexp=") {
-                    // New regexp= starts a new rule, break out to handle it
-                    break;
-                } else if next_line.starts_with("=======")
-                    || next_line.starts_with("-")
-                    || next_line.starts_with(".........")
-                    || next_line.starts_with("==")
-                    || next_line.starts_with("%%%%%%%")
-                {
-                    // End of rule
-                    break;
+                continue;
+            }
+            _ => {
+                errors.push(if is_compact_line(line) {
+                    compact_line_error(line, path)
                 } else {
-                    // Unexpected line
-                    errors.push(ValidationError {
-                        path: path.to_path_buf(),
-                        line: next_line_num,
-                        error_type: "FormatError".to_string(),
-                        message: format!("Unexpected line after regexp: {}", next_line),
-                        suggestion: Some("Expected colours=, count=, skip=, replace=, regexp= lines or ======= / - / ......... / == / %%%%%%% separator".to_string()),
-                    });
-                    i += 1;
-                }
+                    error(
+                        line,
+                        line.span(),
+                        "FormatError",
+                        format!("Unexpected line in rule: {}", line.text),
+                        "Expected key=value lines such as regexp=, colours= or count=; rgrc ignores this line",
+                    )
+                });
+                continue;
             }
-        } else if line.starts_with("=======")
-            || line.starts_with("-")
-            || line.starts_with(".........")
-            || line.starts_with("==")
-            || line.starts_with("%%%%%%%")
-        {
-            // Rule separator, continue
-            i += 1;
-        } else {
-            // Support legacy compact format: "pattern <whitespace or tab> style1 style2"
-            // Split on tab first, then on first whitespace if needed.
-            let trimmed = line.trim();
-            let mut pattern = trimmed;
-            let mut style_part: Option<&str> = None;
-
-            // Look for tab separator first (most common in conf files)
-            if let Some(idx) = trimmed.find('\t') {
-                pattern = trimmed[..idx].trim();
-                style_part = Some(trimmed[idx + 1..].trim());
-            } else {
-                // Look for first whitespace
-                if let Some(idx) = trimmed.find(char::is_whitespace) {
-                    pattern = &trimmed[..idx];
-                    style_part = Some(trimmed[idx..].trim());
+        };
+        has_keys |= RULE_KEYS.contains(&key);
+        match key {
+            "regexp" => {
+                has_regexp = true;
+                // Validate regex using CompiledRegex (supports fancy regex features)
+                match &expanded[&line.number] {
+                    Err(e) => errors.push(macro_error(line, e, path)),
+                    Ok(pattern) => {
+                        if let Err(e) = rgrc::grc::CompiledRegex::new(pattern) {
+                            errors.push(error(
+                                line,
+                                value_span,
+                                "RegexError",
+                                format!("Invalid regex: {}", e),
+                                "Check regex syntax (escape special characters with \\)",
+                            ));
+                        }
+                    }
                 }
             }
-
-            if let Some(styles) = style_part
-                && !styles.is_empty()
+            "colours" | "colors" | "colour" => validate_colours(line, value_span, path, errors),
+            "count" if !matches!(value, "once" | "more" | "stop") => errors.push(error(
+                line,
+                value_span,
+                "FormatError",
+                format!("Unknown count value: {}", value),
+                "Expected count=once, more or stop",
+            )),
+            "skip"
+                if !matches!(
+                    value.to_lowercase().as_str(),
+                    "true" | "1" | "yes" | "false" | "0" | "no"
+                ) =>
             {
-                // Validate regex
-                if let Err(e) = rgrc::grc::CompiledRegex::new(pattern) {
-                    errors.push(ValidationError {
-                        path: path.to_path_buf(),
-                        line: line_num,
-                        error_type: "RegexError".to_string(),
-                        message: format!("Invalid regex: {}", e),
-                        suggestion: Some(
-                            "Check regex syntax (escape special characters with \\)".to_string(),
-                        ),
-                    });
+                errors.push(error(
+                    line,
+                    value_span,
+                    "FormatError",
+                    format!("Unknown skip value: {}", value),
+                    "Expected skip=true or false",
+                ))
+            }
+            "overlay" if !matches!(value, "prepend" | "append" | "replace") => errors.push(error(
+                line,
+                value_span,
+                "FormatError",
+                format!("Unknown overlay value: {}", value),
+                "Expected overlay=prepend, append or replace",
+            )),
+            "severity" if rgrc::grc::Severity::from_name(value).is_none() => errors.push(error(
+                line,
+                value_span,
+                "FormatError",
+                format!("Unknown severity value: {}", value),
+                "Expected severity=error, warning, info or ok",
+            )),
+            "nolint" => {
+                let unknown = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|id| !id.is_empty() && !LINTS.contains(id));
+                for id in unknown {
+                    errors.push(error(
+                        line,
+                        value_span,
+                        "FormatError",
+                        format!("Unknown lint in nolint=: {}", id),
+                        &format!("Expected {}", LINTS.join(", ")),
+                    ));
                 }
-
-                // Validate styles on the same line
-                validate_style_definition(styles, line_num, path, errors);
-                i += 1;
-                continue;
             }
-
-            // If we get here, it's an unexpected line format
-            errors.push(ValidationError {
-                path: path.to_path_buf(),
-                line: line_num,
-                error_type: "FormatError".to_string(),
-                message: format!("Unexpected line format: {}", line),
-                suggestion: Some(
-                    "Expected regexp= line or pattern<tab>styles or ======= / - / ......... / == separator".to_string(),
-                ),
-            });
-            i += 1;
+            _ if RULE_KEYS.contains(&key) => {}
+            _ => errors.push(if is_compact_line(line) {
+                compact_line_error(line, path)
+            } else {
+                error(
+                    line,
+                    line.span(),
+                    "FormatError",
+                    format!("Unknown key in rule: {}", line.text),
+                    "Expected regexp=, colours=, count=, skip=, replace=, id=, overlay=, tags=, severity= or nolint=; rgrc ignores this line",
+                )
+            }),
         }
     }
+
+    if has_keys && !has_regexp {
+        errors.push(error(
+            &lines[0],
+            lines[0].span(),
+            "FormatError",
+            "Rule without regexp=, rgrc ignores it".to_string(),
+            "Add a regexp= line, or join the lines split by a blank or comment line",
+        ));
+    }
 }
 
-/// Validate style definition
-fn validate_style_definition(
-    style_def: &str,
-    line_num: usize,
+/// `MacroError` for an undefined macro or an invalid define on `line`
+fn macro_error(line: &ConfigLine, e: &MacroError, path: &Path) -> ValidationError {
+    ValidationError {
+        path: path.to_path_buf(),
+        line: line.number,
+        column: None,
+        error_type: "MacroError".to_string(),
+        message: e.to_string(),
+        suggestion: Some(
+            "Define it with `define NAME=regex` above, or in ~/.config/rgrc/rgrc.defines"
+                .to_string(),
+        ),
+    }
+}
+
+/// Check a `colours=` value word by word, with the keywords rgrc accepts
+fn validate_colours(
+    line: &ConfigLine,
+    value: Span,
     path: &Path,
     errors: &mut Vec<ValidationError>,
 ) {
-    let valid_styles = vec![
-        // Special keywords
-        "unchanged",
-        "default",
-        "dark",
-        "none",
-        // Foreground colors
-        "black",
-        "red",
-        "green",
-        "yellow",
-        "blue",
-        "magenta",
-        "cyan",
-        "white",
-        // Bright colors
-        "bright_black",
-        "bright_red",
-        "bright_green",
-        "bright_yellow",
-        "bright_blue",
-        "bright_magenta",
-        "bright_cyan",
-        "bright_white",
-        // Background colors
-        "on_black",
-        "on_red",
-        "on_green",
-        "on_yellow",
-        "on_blue",
-        "on_magenta",
-        "on_cyan",
-        "on_white",
-        // Text attributes
-        "bold",
-        "dim",
-        "italic",
-        "underline",
-        "blink",
-        "reverse",
-    ];
-
-    // Split by comma first, then by space for each style group
-    for style_group in style_def.split(',') {
-        for style in style_group.split_whitespace() {
-            let trimmed_style = style.trim();
-            if trimmed_style.is_empty() {
-                continue;
-            }
-            // Allow empty quotes for no styling
-            if trimmed_style == "''" {
-                continue;
-            }
-            // Allow ANSI escape sequences
-            if trimmed_style.starts_with('"') && trimmed_style.contains("\\033[") {
-                continue;
-            }
-            // Normalize hyphenated style names to underscored versions
-            let normalized_style = trimmed_style.replace('-', "_");
-            if !valid_styles.contains(&normalized_style.as_str()) {
+    let mut start = value.start;
+    for group in value.text(&line.text).split(',') {
+        let mut column = start;
+        for word in group.split(' ') {
+            if rgrc::grc::style_from_str(word).is_err() {
+                let suggestion = match style_correction(word) {
+                    Some(keyword) => format!("Use {}", keyword),
+                    None => STYLE_HINT.to_string(),
+                };
                 errors.push(ValidationError {
                     path: path.to_path_buf(),
-                    line: line_num,
+                    line: line.number,
+                    column: Some(column + 1),
                     error_type: "StyleError".to_string(),
-                    message: format!("Unknown style: '{}'", trimmed_style),
-                    suggestion: Some(format!(
-                        "Valid styles include: {}",
-                        valid_styles[0..12].join(", ")
-                    )),
+                    message: format!("Unknown style: '{}'", word),
+                    suggestion: Some(suggestion),
                 });
             }
+            column += word.len() + 1;
         }
+        start += group.len() + 1;
+    }
+}
+
+/// Validate a line that rgrc skips because it does not start with a letter or digit
+fn validate_other_line(line: &ConfigLine, path: &Path, errors: &mut Vec<ValidationError>) {
+    let trimmed = line.text.trim();
+    if ConfigLine::parse(line.number, trimmed.to_string()).is_rule_line() {
+        errors.push(ValidationError {
+            path: path.to_path_buf(),
+            line: line.number,
+            column: Some(1),
+            error_type: "FormatError".to_string(),
+            message: format!("Indented line ends the rule and is ignored: {}", trimmed),
+            suggestion: Some("Remove the leading whitespace".to_string()),
+        });
+    } else if is_compact_line(line) {
+        errors.push(compact_line_error(line, path));
+    } else {
+        errors.push(ValidationError {
+            path: path.to_path_buf(),
+            line: line.number,
+            column: None,
+            error_type: "FormatError".to_string(),
+            message: format!("Unexpected line format: {}", line.text),
+            suggestion: Some(
+                "Expected a regexp= line or a ======= / - / ......... / == separator".to_string(),
+            ),
+        });
+    }
+}
+
+/// Whether `line` has the shape of the legacy grc compact format, `pattern<TAB>style1
+/// style2`, which rgrc does not read
+fn is_compact_line(line: &ConfigLine) -> bool {
+    let trimmed = line.text.trim();
+    trimmed
+        .split_once(char::is_whitespace)
+        .is_some_and(|(pattern, styles)| !pattern.is_empty() && !styles.trim().is_empty())
+}
+
+/// Error for a legacy `pattern<TAB>styles` line
+fn compact_line_error(line: &ConfigLine, path: &Path) -> ValidationError {
+    ValidationError {
+        path: path.to_path_buf(),
+        line: line.number,
+        column: None,
+        error_type: "FormatError".to_string(),
+        message: format!(
+            "Legacy pattern<TAB>styles line ignored by rgrc: {}",
+            line.text.trim()
+        ),
+        suggestion: Some("Write it as a rule: regexp=<pattern> and colours=<styles>".to_string()),
     }
 }
 
+/// Hint for a style word with no close keyword
+const STYLE_HINT: &str = "Expected a color (red, bright_red, on_red, ...), bold, dim, italic, underline, blink, reverse, default or unchanged";
+
+/// Keyword `style_from_str` accepts for a misspelled style `word`, such as
+/// `bright_red` for `Bright-Red`
+fn style_correction(word: &str) -> Option<String> {
+    let lower = word.to_lowercase();
+    [
+        word.replace('-', "_"),
+        lower.clone(),
+        lower.replace('-', "_"),
+        lower.replace(['-', '_'], ""),
+        lower.replacen("bright", "bright_", 1),
+        lower.replacen("on", "on_", 1),
+    ]
+    .into_iter()
+    .find(|candidate| !candidate.is_empty() && rgrc::grc::style_from_str(candidate).is_ok())
+}
+
 /// Find grc.conf file
 fn find_grc_conf() -> PathBuf {
     let candidates = vec![
@@ -665,6 +1595,8 @@ fn find_conf_dir() -> PathBuf {
 struct ValidationError {
     path: PathBuf,
     line: usize,
+    /// 1-based column, when the problem is part of the line
+    column: Option<usize>,
     error_type: String,
     message: String,
     suggestion: Option<String>,
@@ -679,15 +1611,16 @@ fn print_errors(errors: &[ValidationError]) {
             Style::new().red().bold().apply_to("Error"),
             Style::new().red().apply_to(&error.error_type)
         );
+        let location = match error.column {
+            Some(column) => format!("{}:{}", error.line, column),
+            None => error.line.to_string(),
+        };
         eprintln!(
             "    {}:{}",
             Style::new()
                 .yellow()
                 .apply_to(&error.path.display().to_string()),
-            Style::new()
-                .yellow()
-                .bold()
-                .apply_to(&error.line.to_string())
+            Style::new().yellow().bold().apply_to(&location)
         );
         eprintln!("    {}", error.message);
         if let Some(suggestion) = &error.suggestion {
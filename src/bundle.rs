@@ -0,0 +1,103 @@
+//! Embedded Config Bundle - Decoder for the Configs Compressed by build.rs
+//!
+//! With `embed-configs`, build.rs compresses each `share/conf.*` and concatenates
+//! them into one bundle, so the binary stays small as configs are added. Each
+//! config is compressed on its own: loading the rules for a command decodes only
+//! its config, and the config names are available without decoding anything.
+//!
+//! ## Format
+//!
+//! A compressed config is a sequence of LZ77 sequences, in the spirit of LZ4 blocks:
+//!
+//! ```text
+//! token         literal length (high 4 bits), match length - 4 (low 4 bits)
+//! [length...]   if the literal length is 15: more length bytes, 255 while continuing
+//! literals      bytes copied as-is
+//! offset        u16 little endian: how far back the match starts (absent at the end)
+//! [length...]   if the match length is 15: more length bytes, as for literals
+//! ```
+//!
+//! The last sequence has literals only and ends the input. A match may overlap
+//! the bytes it produces, which encodes runs.
+
+/// Shortest match the encoder emits
+const MIN_MATCH: usize = 4;
+
+/// Decompress one config of `len` bytes, or `None` if the input is malformed.
+pub fn decompress(input: &[u8], len: usize) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(len);
+    let mut pos = 0;
+    loop {
+        let token = *input.get(pos)?;
+        pos += 1;
+
+        let literals = read_length(input, &mut pos, (token >> 4) as usize)?;
+        out.extend_from_slice(input.get(pos..pos + literals)?);
+        pos += literals;
+        if pos == input.len() {
+            break;
+        }
+
+        let offset = u16::from_le_bytes([*input.get(pos)?, *input.get(pos + 1)?]) as usize;
+        pos += 2;
+        let match_len = read_length(input, &mut pos, (token & 0x0f) as usize)? + MIN_MATCH;
+        if offset == 0 || offset > out.len() {
+            return None;
+        }
+        // Byte by byte: the match may overlap what it produces
+        let start = out.len() - offset;
+        for i in start..start + match_len {
+            out.push(out[i]);
+        }
+    }
+    (out.len() == len).then_some(out)
+}
+
+/// A 4-bit length from a token, extended by the bytes that follow when it is 15
+fn read_length(input: &[u8], pos: &mut usize, len: usize) -> Option<usize> {
+    if len < 15 {
+        return Some(len);
+    }
+    let mut len = len;
+    loop {
+        let byte = *input.get(*pos)?;
+        *pos += 1;
+        len += byte as usize;
+        if byte != 255 {
+            return Some(len);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decompress_literals_and_overlapping_match() {
+        // "ab" then a 6-byte match at offset 2 ("ababab"), then "!"
+        let input = [0x22, b'a', b'b', 0x02, 0x00, 0x10, b'!'];
+        assert_eq!(decompress(&input, 9).unwrap(), b"abababab!");
+    }
+
+    #[test]
+    fn test_decompress_extended_lengths() {
+        // 20 literals (15 + 5), then a 4 + 15 + 255 + 1 = 275 byte match
+        let mut input = vec![0xff, 5];
+        input.extend_from_slice(&[b'x'; 20]);
+        input.extend_from_slice(&[1, 0, 255, 1]);
+        input.push(0x00);
+        assert_eq!(decompress(&input, 295).unwrap(), vec![b'x'; 295]);
+    }
+
+    #[test]
+    fn test_decompress_rejects_malformed_input() {
+        // Offset before the start of the output
+        assert!(decompress(&[0x10, b'a', 0x02, 0x00, 0x00], 5).is_none());
+        // Truncated literals
+        assert!(decompress(&[0x30, b'a'], 3).is_none());
+        // Wrong length
+        assert!(decompress(&[0x10, b'a'], 2).is_none());
+        assert!(decompress(&[], 0).is_none());
+    }
+}
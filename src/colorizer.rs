@@ -20,28 +20,36 @@
 //!    - Support count and replace functionality
 //!
 //! 2. **Style Mapping** (Phase 2):
-//!    - Build per-character style map
+//!    - Paint matched ranges into sorted, non-overlapping style spans
 //!    - Later rules override earlier ones where they overlap
 //!    - Implements simple precedence strategy
 //!
 //! 3. **ANSI Encoding** (Phase 3):
-//!    - Merge adjacent characters with same style
+//!    - Merge adjacent spans with the same style into runs
 //!    - Minimize ANSI escape sequences
-//!    - Apply console styling using the `console` crate
+//!    - Lines without matches are written through unchanged
 //!
 //! ## Key Optimizations
 //!
 //! - **Match result caching**: Tracks rightmost end positions to avoid redundant checks
 //! - **Zero-width match handling**: Prevents infinite loops on empty matches
 //! - **Style merging**: Combines adjacent styled segments to reduce escape sequences
+//! - **Span-based rendering**: No per-byte style map; work is proportional to the
+//!   number of matches, not the line length
 //! - **Count field support**: once/more/stop matching control
 //! - **Replace field support**: Text substitution functionality
+//! - **Literal prefilter**: rules whose required literals are absent from a line are
+//!   not run at all (see `Prefilter`)
+//! - **Catastrophic-regex protection**: each slow-path match attempt is bounded by the
+//!   backtrack limit, and rules that exceed it or their time budget are disabled for
+//!   the rest of the stream (see `MatchLimits`)
 
 use std::io::{BufRead, BufReader, Read, Write};
-#[cfg(feature = "debug")]
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
 
-use crate::grc::GrcatConfigEntry;
+use crate::grc::{GrcatConfigEntry, MatchLimits};
+use crate::prefilter::Prefilter;
 use crate::style::Style;
 
 /// Regex-optimized colorizer with advanced caching and pattern matching optimizations.
@@ -111,6 +119,49 @@ pub fn colorize_regex<R, W>(
     writer: &mut W,
     rules: &[GrcatConfigEntry],
 ) -> Result<(), Box<dyn std::error::Error>>
+where
+    R: Read,
+    W: Write,
+{
+    colorize_regex_with_limits(reader, writer, rules, crate::grc::match_limits())
+}
+
+/// Same as [`colorize_regex`], with explicit match limits instead of the process-wide ones.
+///
+/// A slow-path rule (lookaround/backreference engine) whose matching on a single line
+/// takes `limits.rule_time_budget` or longer, or which hits a runtime error such as
+/// the backtrack limit, is disabled for the rest of the stream. The time budget is
+/// checked after each match attempt, and the backtrack limit bounds every attempt
+/// (see [`MatchLimits`]), so a pathological rule cannot stall the stream. A single
+/// warning is printed on stderr for each disabled rule; matches already found on the
+/// current line are kept.
+#[allow(dead_code)]
+pub fn colorize_regex_with_limits<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    rules: &[GrcatConfigEntry],
+    limits: MatchLimits,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    R: Read,
+    W: Write,
+{
+    let prefilter = Prefilter::new(rules);
+    colorize_regex_with_prefilter(reader, writer, rules, limits, &prefilter)
+}
+
+/// Same as [`colorize_regex_with_limits`], with a prefilter built beforehand.
+///
+/// `prefilter` must have been built from `rules`; [`Prefilter::default`] runs every
+/// rule on every line.
+#[allow(dead_code)]
+pub fn colorize_regex_with_prefilter<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    rules: &[GrcatConfigEntry],
+    limits: MatchLimits,
+    prefilter: &Prefilter,
+) -> Result<(), Box<dyn std::error::Error>>
 where
     R: Read,
     W: Write,
@@ -125,8 +176,45 @@ where
         None
     };
 
+    let disabled_rules = disabled_flags(rules.len());
+    let _lines_processed =
+        colorize_lines(reader, writer, rules, limits, prefilter, &disabled_rules, 0)?;
+
     #[cfg(feature = "debug")]
-    let mut lines_processed: usize = 0;
+    if let Some(s) = overall_start.filter(|_| record_time) {
+        eprintln!(
+            "[rgrc:time] colorizer total processed {} lines in {:?}",
+            _lines_processed,
+            s.elapsed()
+        );
+    }
+
+    Ok(())
+}
+
+/// One flag per rule, set when the rule is disabled for the rest of the stream
+pub(crate) fn disabled_flags(rules: usize) -> Vec<AtomicBool> {
+    (0..rules).map(|_| AtomicBool::new(false)).collect()
+}
+
+/// Colorize the lines of `reader`, returning how many were read.
+///
+/// `disabled_rules` is shared by every batch of one stream, so a rule disabled in one
+/// batch is skipped (and warned about) only once; `first_line` is the number of lines
+/// before this batch, used in warnings.
+pub(crate) fn colorize_lines<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    rules: &[GrcatConfigEntry],
+    limits: MatchLimits,
+    prefilter: &Prefilter,
+    disabled_rules: &[AtomicBool],
+    first_line: usize,
+) -> Result<usize, Box<dyn std::error::Error>>
+where
+    R: Read,
+    W: Write,
+{
     // ═══════════════════════════════════════════════════════════════════════════════
     // PHASE 1: INPUT PROCESSING - Set up buffered reading and line iteration
     // ═══════════════════════════════════════════════════════════════════════════════
@@ -139,15 +227,25 @@ where
     // ═══════════════════════════════════════════════════════════════════════════════
 
     if rules.is_empty() {
+        let mut lines = 0;
         for line in reader {
-            writeln!(writer, "{}", line?)?;
+            write_raw_line(writer, &line?)?;
+            lines += 1;
         }
-        return Ok(());
+        return Ok(lines);
     }
 
     // Default style for unstyled text (no color, no attributes)
     let default_style = Style::new();
 
+    let mut line_num = first_line;
+
+    // Runs the rules on each line
+    let mut matcher = LineMatcher::new(rules, limits, prefilter, disabled_rules);
+
+    // Styled ranges of the current line, reused across lines
+    let mut spans = StyleSpans::default();
+
     // ═══════════════════════════════════════════════════════════════════════════════
     // PHASE 2: LINE-BY-LINE PROCESSING - Apply colorization rules to each line
     // ═══════════════════════════════════════════════════════════════════════════════
@@ -155,17 +253,14 @@ where
     for line in reader {
         // Extract line content, propagating any I/O errors
         let mut line = line?;
-        #[cfg(feature = "debug")]
-        if record_time {
-            lines_processed += 1;
-        }
+        line_num += 1;
 
         // ═══════════════════════════════════════════════════════════════════════════════
         // FAST PATH: Empty lines - preserve as single newline without processing
         // ═══════════════════════════════════════════════════════════════════════════════
 
         if line.is_empty() {
-            writeln!(writer)?;
+            writer.write_all(b"\n")?;
             continue;
         }
 
@@ -173,24 +268,109 @@ where
         // PHASE 2A: MATCH COLLECTION - Find all regex matches with intelligent caching
         // ═══════════════════════════════════════════════════════════════════════════════
 
-        // Vector to collect all (start_pos, end_pos, style) ranges for matched patterns
-        let mut style_ranges: Vec<(usize, usize, &Style)> = Vec::new();
+        matcher.match_line(&mut line, line_num, &mut spans, None);
+
+        // ═══════════════════════════════════════════════════════════════════════════════
+        // FAST PATH: No matches found - write the line through unchanged
+        // ═══════════════════════════════════════════════════════════════════════════════
+
+        if spans.is_empty() {
+            write_raw_line(writer, &line)?;
+            continue;
+        }
+
+        // ═══════════════════════════════════════════════════════════════════════════════
+        // PHASE 2B: OUTPUT GENERATION - Write styled runs of the line
+        // ═══════════════════════════════════════════════════════════════════════════════
+
+        spans.write_line(writer, &line, &default_style)?;
+    }
+
+    Ok(line_num - first_line)
+}
+
+/// The rules of a stream and the state shared by its lines, applied one line at a time
+struct LineMatcher<'r> {
+    rules: &'r [GrcatConfigEntry],
+    limits: MatchLimits,
+    prefilter: &'r Prefilter,
+    disabled_rules: &'r [AtomicBool],
+    /// Which prefilter literals occur in the current line
+    present_literals: Vec<bool>,
+}
+
+impl<'r> LineMatcher<'r> {
+    fn new(
+        rules: &'r [GrcatConfigEntry],
+        limits: MatchLimits,
+        prefilter: &'r Prefilter,
+        disabled_rules: &'r [AtomicBool],
+    ) -> Self {
+        LineMatcher {
+            rules,
+            limits,
+            prefilter,
+            disabled_rules,
+            present_literals: Vec::new(),
+        }
+    }
+
+    /// Run the rules on line `line_num`, painting their matches into `spans` and
+    /// applying `replace=` to `line`. With `trace`, also record what each rule did,
+    /// up to the last rule run (see [`explain_line`]).
+    fn match_line(
+        &mut self,
+        line: &mut String,
+        line_num: usize,
+        spans: &mut StyleSpans<&'r Style>,
+        mut trace: Option<&mut Vec<RuleTrace>>,
+    ) {
+        // The trace of the rule being run
+        fn current<'t>(trace: &'t mut Option<&mut Vec<RuleTrace>>) -> Option<&'t mut RuleTrace> {
+            trace.as_deref_mut()?.last_mut()
+        }
+
+        // One literal scan per line decides which rules are worth running
+        self.prefilter.scan(line, &mut self.present_literals);
+
+        // Styled ranges for matched patterns; later rules paint over earlier ones
+        spans.clear();
 
         // Track whether to stop processing the entire line (for count=stop)
         let mut stop_line_processing = false;
 
         // Process each rule (regex pattern + associated styles)
-        'outer_loop: for rule in rules {
-            // Skip rules marked with skip=true
-            if rule.skip {
-                continue;
-            }
-
+        'outer_loop: for (rule_idx, rule) in self.rules.iter().enumerate() {
             // Stop processing if a previous rule had count=stop
             if stop_line_processing {
                 break;
             }
 
+            // Skip rules marked with skip=true or disabled after exceeding their limits
+            if rule.skip || self.disabled_rules[rule_idx].load(Ordering::Relaxed) {
+                if let Some(trace) = trace.as_deref_mut() {
+                    let reason = if rule.skip {
+                        NotRun::Skip
+                    } else {
+                        NotRun::Disabled
+                    };
+                    trace.push(RuleTrace::new(rule_idx, Some(reason)));
+                }
+                continue;
+            }
+
+            // Skip rules whose required literals are not in the line
+            if !self.prefilter.may_match(&self.present_literals, rule_idx) {
+                if let Some(trace) = trace.as_deref_mut() {
+                    trace.push(RuleTrace::new(rule_idx, Some(NotRun::Prefilter)));
+                }
+                continue;
+            }
+
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.push(RuleTrace::new(rule_idx, None));
+            }
+
             // Current search offset in the line (advances as we find matches)
             let mut offset = 0;
 
@@ -201,6 +381,13 @@ where
             // Track whether this rule should match only once (for count=once)
             let mut rule_matched_once = false;
 
+            // Only slow-path rules can blow up; the fast engine runs in linear time
+            let budget = self
+                .limits
+                .rule_time_budget
+                .filter(|_| rule.regex.backend().is_slow_path());
+            let rule_start = budget.map(|_| Instant::now());
+
             // Scan the line for all matches of this rule's regex pattern
             while offset < line.len() && !rule_matched_once {
                 // ═══════════════════════════════════════════════════════════════════════════════
@@ -215,7 +402,46 @@ where
                 }
 
                 // Attempt regex match starting from current offset position
-                if let Some(matches) = rule.regex.captures_from_pos(&line, offset) {
+                let result = match rule.regex.try_captures_from_pos(line, offset) {
+                    Ok(result) => result,
+                    Err(e) => {
+                        // e.g. backtrack limit exceeded: give up on this rule for good
+                        if !self.disabled_rules[rule_idx].swap(true, Ordering::Relaxed) {
+                            warn_rule_disabled(rule_idx, rule, line_num, &e.to_string());
+                        }
+                        if let Some(rule_trace) = current(&mut trace) {
+                            rule_trace.disabled = Some(e.to_string());
+                        }
+                        None
+                    }
+                };
+
+                // ═══════════════════════════════════════════════════════════════════════════════
+                // TIME BUDGET: Disable slow-path rules that take too long on a single line
+                // ═══════════════════════════════════════════════════════════════════════════════
+
+                if let (Some(budget), Some(started)) = (budget, rule_start)
+                    && started.elapsed() >= budget
+                    && !self.disabled_rules[rule_idx].swap(true, Ordering::Relaxed)
+                {
+                    let reason = format!("exceeded its time budget of {:?}", budget);
+                    warn_rule_disabled(rule_idx, rule, line_num, &reason);
+                    if let Some(rule_trace) = current(&mut trace) {
+                        rule_trace.disabled = Some(reason);
+                    }
+                    // Keep the match just found, but stop scanning this line
+                    rule_matched_once = true;
+                }
+
+                if let Some(matches) = result {
+                    if let Some(rule_trace) = current(&mut trace) {
+                        rule_trace.matches.push(MatchTrace {
+                            groups: (0..matches.len())
+                                .map(|i| matches.get(i).map(|m| (m.start(), m.end())))
+                                .collect(),
+                            painted: Vec::new(),
+                        });
+                    }
                     // ═══════════════════════════════════════════════════════════════════════════════
                     // CAPTURE GROUP PROCESSING: Extract each matched subgroup
                     // ═══════════════════════════════════════════════════════════════════════════════
@@ -232,7 +458,12 @@ where
                                 let style = &rule.colors[i];
 
                                 // Record this styled range for later application
-                                style_ranges.push((start, end, style));
+                                spans.paint(start, end, style);
+                                if let Some(rule_trace) = current(&mut trace)
+                                    && let Some(match_trace) = rule_trace.matches.last_mut()
+                                {
+                                    match_trace.painted.push(i);
+                                }
 
                                 // Update cache: track rightmost position covered by any match
                                 last_end = last_end.max(end);
@@ -266,7 +497,10 @@ where
                             // We rebuild the line with the replacement
                             let before = &line[..full_match.start()];
                             let after = &line[full_match.end()..];
-                            line = format!("{}{}{}", before, replacement, after);
+                            *line = format!("{}{}{}", before, replacement, after);
+                            if let Some(rule_trace) = current(&mut trace) {
+                                rule_trace.replaced = Some(line.clone());
+                            }
 
                             // Since we modified the line, we need to restart processing from the beginning
                             // This is a simplified approach - in practice, we might want more sophisticated handling
@@ -317,76 +551,272 @@ where
             }
         }
 
-        // ═══════════════════════════════════════════════════════════════════════════════
-        // FAST PATH: No matches found - output line unchanged to avoid processing
-        // ═══════════════════════════════════════════════════════════════════════════════
+        // A replacement may have shortened the line after ranges were recorded
+        spans.truncate(line.len());
+    }
+}
 
-        if style_ranges.is_empty() {
-            writeln!(writer, "{}", line)?;
-            continue;
+/// Why a rule was not run on a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotRun {
+    /// The rule has `skip=true`
+    Skip,
+    /// The rule was disabled earlier in the stream
+    Disabled,
+    /// A literal the rule requires is not in the line
+    Prefilter,
+    /// An earlier rule with `count=stop` matched
+    Stopped,
+    /// An earlier rule replaced text, which ends the line's processing
+    Replaced,
+}
+
+/// One match of a rule, as seen by the colorizer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchTrace {
+    /// Byte range of each group (0 is the whole match), `None` if it did not take part
+    pub groups: Vec<Option<(usize, usize)>>,
+    /// The groups painted with their colour, in order
+    pub painted: Vec<usize>,
+}
+
+/// What one rule did to a line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleTrace {
+    /// Index of the rule in its config
+    pub rule: usize,
+    /// Why the rule was not run, `None` if it was
+    pub not_run: Option<NotRun>,
+    /// Its matches, in the order they were found
+    pub matches: Vec<MatchTrace>,
+    /// The line after the rule's `replace=`, if it replaced text
+    pub replaced: Option<String>,
+    /// Why the rule was disabled while running, if it was
+    pub disabled: Option<String>,
+}
+
+impl RuleTrace {
+    fn new(rule: usize, not_run: Option<NotRun>) -> Self {
+        RuleTrace {
+            rule,
+            not_run,
+            matches: Vec::new(),
+            replaced: None,
+            disabled: None,
         }
+    }
+}
 
-        // ═══════════════════════════════════════════════════════════════════════════════
-        // PHASE 2B: STYLE APPLICATION - Build per-character style mapping
-        // ═══════════════════════════════════════════════════════════════════════════════
+/// A styled region of an explained line and the rule group that won it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpanOwner {
+    pub start: usize,
+    pub end: usize,
+    /// Index of the rule in its config
+    pub rule: usize,
+    /// The capture group whose colour applies (0 is the whole match)
+    pub group: usize,
+}
 
-        // Create per-character style array (one style reference per character)
-        // Initialize all characters to default style (unstyled)
-        let mut char_styles: Vec<&Style> = vec![&default_style; line.len()];
+/// How a line is coloured, rule by rule; see [`explain_line`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineExplanation {
+    /// The line after any `replace=`; span offsets refer to it
+    pub line: String,
+    /// The coloured line, as the colorizer writes it (without the newline)
+    pub output: String,
+    /// One entry per rule, in config order (empty for an empty line, which is
+    /// written through without running any rule)
+    pub rules: Vec<RuleTrace>,
+    /// The styled regions of `line`, sorted and non-overlapping
+    pub spans: Vec<SpanOwner>,
+}
 
-        // Apply all collected style ranges to the character array
-        // Later ranges override earlier ones (simple precedence rule)
-        for (start, end, style) in style_ranges {
-            // Bounds check: ensure we don't exceed line length
-            for item in char_styles.iter_mut().take(end.min(line.len())).skip(start) {
-                *item = style;
-            }
+/// Colour `line` with `rules` the way the colorizer does, recording every rule
+/// tried, its matches, and which rule group won each styled region.
+///
+/// The line is treated as the first of a stream, so no rule starts out disabled.
+#[allow(dead_code)]
+pub fn explain_line(line: &str, rules: &[GrcatConfigEntry]) -> LineExplanation {
+    let mut line = line.to_string();
+    let prefilter = Prefilter::new(rules);
+    let disabled_rules = disabled_flags(rules.len());
+    let mut trace = Vec::new();
+    let mut spans = StyleSpans::default();
+
+    if !line.is_empty() {
+        let mut matcher = LineMatcher::new(
+            rules,
+            crate::grc::match_limits(),
+            &prefilter,
+            &disabled_rules,
+        );
+        matcher.match_line(&mut line, 1, &mut spans, Some(&mut trace));
+
+        // Rules after the last one run were cut off by a stop or a replacement
+        let reason = if trace.iter().any(|t| t.replaced.is_some()) {
+            NotRun::Replaced
+        } else {
+            NotRun::Stopped
+        };
+        for rule in trace.len()..rules.len() {
+            trace.push(RuleTrace::new(rule, Some(reason)));
         }
+    }
 
-        // ═══════════════════════════════════════════════════════════════════════════════
-        // PHASE 2C: OUTPUT GENERATION - Write styled text with run-length encoding
-        // ═══════════════════════════════════════════════════════════════════════════════
+    let mut output = Vec::new();
+    if spans.is_empty() {
+        output.extend_from_slice(line.as_bytes());
+    } else {
+        // Writing to a Vec cannot fail
+        let _ = spans.write_line(&mut output, &line, &Style::new());
+        output.pop();
+    }
 
-        // Run-length encoding: merge consecutive characters with same style
-        // This minimizes ANSI escape sequence overhead
-        let mut prev_style = &default_style;
-        let mut offset = 0;
-
-        // Scan through characters and detect style boundaries
-        for i in 0..line.len() {
-            let this_style = char_styles[i];
-
-            // Style boundary detected - output previous styled segment
-            if this_style != prev_style {
-                if i > 0 {
-                    // Apply previous style to characters from offset to current position
-                    // Style::apply_to() generates appropriate ANSI escape codes
-                    write!(writer, "{}", prev_style.apply_to(&line[offset..i]))?;
+    // Replay the painting with owners in place of styles
+    let mut owners = StyleSpans::default();
+    for rule_trace in &trace {
+        for m in &rule_trace.matches {
+            for &group in &m.painted {
+                if let Some((start, end)) = m.groups[group] {
+                    owners.paint(start, end, (rule_trace.rule, group));
                 }
-
-                // Update tracking for next segment
-                prev_style = this_style;
-                offset = i;
             }
         }
+    }
+    owners.truncate(line.len());
+
+    LineExplanation {
+        output: String::from_utf8_lossy(&output).into_owned(),
+        line,
+        rules: trace,
+        spans: owners
+            .spans
+            .into_iter()
+            .map(|(start, end, (rule, group))| SpanOwner {
+                start,
+                end,
+                rule,
+                group,
+            })
+            .collect(),
+    }
+}
 
-        // Output the final segment (from last boundary to end of line)
-        if offset < line.len() {
-            write!(writer, "{}", prev_style.apply_to(&line[offset..]))?;
-        }
+/// Write a line without styling, followed by a newline.
+fn write_raw_line<W: Write>(writer: &mut W, line: &str) -> std::io::Result<()> {
+    writer.write_all(line.as_bytes())?;
+    writer.write_all(b"\n")
+}
+
+/// Styled byte ranges of a line: sorted by start and never overlapping.
+///
+/// Painting a range replaces whatever it covers, trimming or splitting the spans it
+/// overlaps, so later rules take precedence. Bytes outside every span keep the
+/// default style.
+#[derive(Debug)]
+struct StyleSpans<S> {
+    spans: Vec<(usize, usize, S)>,
+}
 
-        // Always terminate line with newline (matches input format)
-        writeln!(writer)?;
+impl<S> Default for StyleSpans<S> {
+    fn default() -> Self {
+        StyleSpans { spans: Vec::new() }
     }
+}
 
-    #[cfg(feature = "debug")]
-    if let Some(s) = overall_start.filter(|_| record_time) {
-        eprintln!(
-            "[rgrc:time] colorizer total processed {} lines in {:?}",
-            lines_processed,
-            s.elapsed()
+impl<S: Copy> StyleSpans<S> {
+    fn clear(&mut self) {
+        self.spans.clear();
+    }
+
+    fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Style `start..end`, overriding earlier spans.
+    fn paint(&mut self, start: usize, end: usize, style: S) {
+        if start >= end {
+            return;
+        }
+        // spans[first..last] are the ones overlapping start..end
+        let first = self.spans.partition_point(|&(_, e, _)| e <= start);
+        let last = self.spans.partition_point(|&(s, _, _)| s < end);
+
+        // Parts of the first and last overlapping spans that stick out
+        let head = self.spans[first..last]
+            .first()
+            .filter(|&&(s, _, _)| s < start)
+            .map(|&(s, _, style)| (s, start, style));
+        let tail = self.spans[first..last]
+            .last()
+            .filter(|&&(_, e, _)| e > end)
+            .map(|&(_, e, style)| (end, e, style));
+
+        self.spans.splice(
+            first..last,
+            head.into_iter().chain([(start, end, style)]).chain(tail),
         );
     }
 
-    Ok(())
+    /// Drop everything at or after `len`.
+    fn truncate(&mut self, len: usize) {
+        self.spans.retain(|&(start, _, _)| start < len);
+        if let Some(last) = self.spans.last_mut() {
+            last.1 = last.1.min(len);
+        }
+    }
+}
+
+impl StyleSpans<&Style> {
+    /// Write `line` with its spans applied, merging neighbouring spans (and gaps)
+    /// with the same style into a single run, followed by a newline.
+    fn write_line<W: Write>(
+        &self,
+        writer: &mut W,
+        line: &str,
+        default_style: &Style,
+    ) -> std::io::Result<()> {
+        let mut run_start = 0;
+        let mut run_style = *default_style;
+        let mut pos = 0;
+
+        let mut boundary = |at: usize, style: Style, writer: &mut W| {
+            if style != run_style {
+                if at > run_start {
+                    write!(writer, "{}", run_style.apply_to(&line[run_start..at]))?;
+                }
+                run_style = style;
+                run_start = at;
+            }
+            Ok::<_, std::io::Error>(())
+        };
+
+        for &(start, end, style) in &self.spans {
+            if start > pos {
+                boundary(pos, *default_style, writer)?;
+            }
+            boundary(start, *style, writer)?;
+            pos = end;
+        }
+        if pos < line.len() {
+            boundary(pos, *default_style, writer)?;
+        }
+
+        if run_start < line.len() {
+            write!(writer, "{}", run_style.apply_to(&line[run_start..]))?;
+        }
+        writer.write_all(b"\n")
+    }
+}
+
+/// Print the one-time warning for a rule disabled by the colorizer.
+fn warn_rule_disabled(rule_idx: usize, rule: &GrcatConfigEntry, line_num: usize, reason: &str) {
+    eprintln!(
+        "rgrc: warning: rule {} ({}) {} on input line {}; disabled for the rest of the stream",
+        rule.label(rule_idx),
+        rule.regex.as_str(),
+        reason,
+        line_num
+    );
 }
@@ -0,0 +1,289 @@
+//! Command Index - Find the Config for a Command Without Compiling grc.conf
+//!
+//! grc.conf maps command lines to conf files with one regex per entry, and the first
+//! matching entry wins. Compiling every pattern up to the match dominates the startup
+//! of short commands such as `id` or `uptime`, yet most entries have the form
+//! `^([/\w\.]+\/)?NAME\b`: a literal command name, optionally preceded by a path.
+//!
+//! A [`CommandIndex`] maps those literal names to their entries, so they are looked
+//! up by name. The other entries keep their regexes and are only compiled when they
+//! come before the literal match and the command line contains their required
+//! literals (see [`crate::prefilter::required_literals`]). The result is the entry
+//! that a scan of grc.conf in order would have found.
+
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::sync::OnceLock;
+
+use crate::grc::{CompiledRegex, GrcConfigReader};
+use crate::prefilter::required_literals;
+
+/// Prefix of a literal entry: an optional path to the command
+const PATH_PREFIX: &str = r"^([/\w\.]+\/)?";
+
+/// grc.conf entries indexed by literal command name
+#[derive(Debug, Default)]
+pub struct CommandIndex {
+    entries: Vec<IndexEntry>,
+    /// Literal name → (entry index, terminator), in file order
+    names: HashMap<String, Vec<(usize, Terminator)>>,
+    /// Length of the longest literal name
+    max_name_len: usize,
+}
+
+#[derive(Debug)]
+struct IndexEntry {
+    pattern: String,
+    config: String,
+    /// For entries that are not literal: literals the command must contain
+    /// (`None` when nothing can be ruled out without the regex), extracted on
+    /// first use since most lookups end at an earlier literal entry
+    required: OnceLock<Option<Vec<String>>>,
+    literal: bool,
+}
+
+/// What must follow a literal command name
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Terminator {
+    /// `\b`: end of the command line or a non-word character
+    WordBoundary,
+    /// `\s`: a whitespace character
+    Whitespace,
+}
+
+impl CommandIndex {
+    /// Index the entries of a grc.conf file, without compiling any pattern.
+    pub fn new<A: BufRead>(mut reader: GrcConfigReader<A>) -> Self {
+        let mut index = CommandIndex::default();
+        while let Some((pattern, config)) = reader.next_entry() {
+            let idx = index.entries.len();
+            let literal = match literal_name(&pattern) {
+                Some((name, terminator)) => {
+                    index.max_name_len = index.max_name_len.max(name.len());
+                    let ids = index.names.entry(name.to_string()).or_default();
+                    ids.push((idx, terminator));
+                    true
+                }
+                None => false,
+            };
+            index.entries.push(IndexEntry {
+                pattern,
+                config,
+                required: OnceLock::new(),
+                literal,
+            });
+        }
+        index
+    }
+
+    /// Number of indexed entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no entry was indexed
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of entries looked up by name instead of by regex
+    pub fn literal_entries(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.literal).count()
+    }
+
+    /// First entry matching `command`, as (pattern, config file).
+    ///
+    /// Entries with an invalid regex never match, as with [`GrcConfigReader`].
+    pub fn lookup(&self, command: &str) -> Option<(&str, &str)> {
+        let literal = self.lookup_literal(command);
+        let limit = literal.unwrap_or(self.entries.len());
+        let idx = self.entries[..limit]
+            .iter()
+            .position(|entry| !entry.literal && entry.matches(command))
+            .or(literal)?;
+        let entry = &self.entries[idx];
+        Some((&entry.pattern, &entry.config))
+    }
+
+    /// Index of the first literal entry matching `command`
+    fn lookup_literal(&self, command: &str) -> Option<usize> {
+        let bytes = command.as_bytes();
+        let mut best: Option<usize> = None;
+        for start in name_starts(bytes) {
+            let longest = bytes.len().min(start + self.max_name_len);
+            for end in start + 1..=longest {
+                // Names end in a word character, so `end` is a char boundary
+                if !is_word_byte(bytes[end - 1]) {
+                    continue;
+                }
+                let Some(ids) = self.names.get(&command[start..end]) else {
+                    continue;
+                };
+                let next = bytes.get(end).copied();
+                for &(idx, terminator) in ids {
+                    let terminated = match terminator {
+                        Terminator::WordBoundary => next.is_none_or(|b| !is_word_byte(b)),
+                        Terminator::Whitespace => next.is_some_and(is_space_byte),
+                    };
+                    if terminated && best.is_none_or(|best| idx < best) {
+                        best = Some(idx);
+                    }
+                }
+            }
+        }
+        best
+    }
+}
+
+impl IndexEntry {
+    /// Whether the pattern of a non-literal entry matches, compiling it only when
+    /// the command contains one of its required literals
+    fn matches(&self, command: &str) -> bool {
+        let required = self
+            .required
+            .get_or_init(|| required_literals(&self.pattern));
+        if let Some(required) = required
+            && !required
+                .iter()
+                .any(|literal| command.contains(literal.as_str()))
+        {
+            return false;
+        }
+        CompiledRegex::new(&self.pattern).is_ok_and(|re| re.is_match(command))
+    }
+}
+
+/// Split a pattern of the form `^([/\w\.]+\/)?NAME\b` (or `NAME\s`) into its name
+/// and terminator. The name may contain word characters, spaces and dashes and must
+/// end in a word character.
+fn literal_name(pattern: &str) -> Option<(&str, Terminator)> {
+    let rest = pattern.strip_prefix(PATH_PREFIX)?;
+    let (name, terminator) = if let Some(name) = rest.strip_suffix(r"\b") {
+        (name, Terminator::WordBoundary)
+    } else {
+        (rest.strip_suffix(r"\s")?, Terminator::Whitespace)
+    };
+    let plain = name
+        .bytes()
+        .all(|b| is_word_byte(b) || b == b' ' || b == b'-');
+    let ends_in_word = name.bytes().last().is_some_and(is_word_byte);
+    (plain && ends_in_word).then_some((name, terminator))
+}
+
+/// Offsets where a name can start after the optional `([/\w\.]+\/)?` prefix: the
+/// start of the command and every `/` within a leading run of `[/\w\.]` that has
+/// at least one byte before it
+fn name_starts(bytes: &[u8]) -> impl Iterator<Item = usize> + '_ {
+    let prefix = bytes
+        .iter()
+        .take_while(|&&b| b == b'/' || b == b'.' || is_word_byte(b))
+        .count();
+    std::iter::once(0).chain(
+        (0..prefix)
+            .filter(|&i| i > 0 && bytes[i] == b'/')
+            .map(|i| i + 1),
+    )
+}
+
+/// `\w` without Unicode support
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// `\s` without Unicode support
+fn is_space_byte(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\x0b' | b'\x0c' | b'\r')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(conf: &str) -> CommandIndex {
+        CommandIndex::new(GrcConfigReader::new(conf.as_bytes().lines()))
+    }
+
+    #[test]
+    fn test_literal_name() {
+        assert_eq!(
+            literal_name(r"^([/\w\.]+\/)?id\b"),
+            Some(("id", Terminator::WordBoundary))
+        );
+        assert_eq!(
+            literal_name(r"^([/\w\.]+\/)?ls\s"),
+            Some(("ls", Terminator::Whitespace))
+        );
+        assert_eq!(
+            literal_name(r"^([/\w\.]+\/)?go test\b"),
+            Some(("go test", Terminator::WordBoundary))
+        );
+        assert_eq!(literal_name(r"^([/\w\.]+\/)?(uptime|w)\b"), None);
+        assert_eq!(literal_name(r"^([/\w\.]+\/)?ping[236]?\b"), None);
+        assert_eq!(literal_name(r"^id\b"), None);
+    }
+
+    #[test]
+    fn test_lookup_by_name_and_path() {
+        let index = index(
+            "# comment\n^([/\\w\\.]+\\/)?id\\b\nconf.id\n\n^([/\\w\\.]+\\/)?ls\\s\nconf.ls\n",
+        );
+        assert_eq!(index.literal_entries(), 2);
+        assert_eq!(index.lookup("id -u").map(|e| e.1), Some("conf.id"));
+        assert_eq!(index.lookup("/usr/bin/id").map(|e| e.1), Some("conf.id"));
+        assert_eq!(index.lookup("ls -l").map(|e| e.1), Some("conf.ls"));
+        // `\s` needs whitespace, `\b` a non-word character
+        assert_eq!(index.lookup("ls"), None);
+        assert_eq!(index.lookup("idle"), None);
+        assert_eq!(index.lookup("x id"), None);
+    }
+
+    #[test]
+    fn test_regex_entries_keep_file_order() {
+        let index = index(concat!(
+            "^([/\\w\\.]+\\/)?(uptime|w)\\b\nconf.uptime\n",
+            "^([/\\w\\.]+\\/)?w\\b\nconf.w\n",
+            "\\b\\w+\\b.*log\\b\nconf.log\n",
+            "^([/\\w\\.]+\\/)?tail\\b\nconf.tail\n",
+        ));
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.lookup("w").map(|e| e.1), Some("conf.uptime"));
+        assert_eq!(index.lookup("tail -f x").map(|e| e.1), Some("conf.tail"));
+        // An earlier regex entry wins over a later literal one
+        assert_eq!(
+            index.lookup("tail -f syslog").map(|e| e.1),
+            Some("conf.log")
+        );
+    }
+
+    #[test]
+    fn test_matches_reader_on_bundled_conf() {
+        let conf = std::fs::read_to_string("etc/rgrc.conf").unwrap();
+        let index = index(&conf);
+        assert!(index.literal_entries() > index.len() / 3);
+        for command in [
+            "id",
+            "/id",
+            "//id",
+            "uptime",
+            "/bin/ls -la",
+            "ls",
+            "ping -c 3 localhost",
+            "docker ps",
+            "ip addr show",
+            "go test ./...",
+            "tail -f /var/log/syslog",
+            "journalctl -f",
+            "unknown-command",
+        ] {
+            let expected = GrcConfigReader::new(conf.as_bytes().lines())
+                .find(|(re, _)| re.is_match(command))
+                .map(|(_, config)| config);
+            assert_eq!(
+                index.lookup(command).map(|(_, config)| config.to_string()),
+                expected,
+                "{}",
+                command
+            );
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Diagnostics - Problems Found While Loading Configs
+//!
+//! Loading rules never stops at a bad line: an invalid regex or style drops its
+//! rule, an unknown value falls back to the default, and a missing include is
+//! skipped. Each of these is recorded as a [`Diagnostic`] (file, line, kind and
+//! message) instead of being printed where it is found, so nothing reaches the
+//! colourised stream on stdout.
+//!
+//! The `try_load_*` functions of the crate root return the rules together with
+//! their diagnostics as a [`LoadError`]; the caller decides whether to
+//! [`report`](LoadError::report) them or to keep the rules anyway:
+//!
+//! ```ignore
+//! let rules = rgrc::try_load_rules_for_command("ping").unwrap_or_else(|e| {
+//!     e.report();
+//!     e.into_rules()
+//! });
+//! ```
+
+use std::fmt;
+
+use crate::grc::GrcatConfigEntry;
+
+/// What went wrong
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// A config file could not be read
+    Io,
+    /// A `regexp=` that does not compile
+    Regex,
+    /// An unknown keyword in `colours=`
+    Style,
+    /// An unknown value for `count=`, `skip=`, `overlay=` or `severity=`
+    Value,
+    /// An undefined `{NAME}` macro or an invalid `define`
+    Macro,
+    /// An include cycle or an included config that does not exist
+    Include,
+    /// An overlay rule with nothing to replace
+    Overlay,
+}
+
+impl DiagnosticKind {
+    /// Short name, as shown by `rgrv`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiagnosticKind::Io => "io",
+            DiagnosticKind::Regex => "regex",
+            DiagnosticKind::Style => "style",
+            DiagnosticKind::Value => "value",
+            DiagnosticKind::Macro => "macro",
+            DiagnosticKind::Include => "include",
+            DiagnosticKind::Overlay => "overlay",
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One problem in a config, located by file and line when they are known
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Path (or `embedded/NAME`) of the config, if it has a name
+    pub file: Option<String>,
+    /// 1-based line number
+    pub line: Option<usize>,
+    /// Category of the problem
+    pub kind: DiagnosticKind,
+    /// Description, without the location
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// A diagnostic for `file` (if named) at `line` (if known)
+    pub fn new(
+        file: Option<&str>,
+        line: Option<usize>,
+        kind: DiagnosticKind,
+        message: impl fmt::Display,
+    ) -> Self {
+        Diagnostic {
+            file: file.map(str::to_string),
+            line,
+            kind,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// `file:line: message`, leaving out what is unknown
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, "{}:{}: {}", file, line, self.message),
+            (Some(file), None) => write!(f, "{}: {}", file, self.message),
+            (None, Some(line)) => write!(f, "line {}: {}", line, self.message),
+            (None, None) => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Rules loaded despite one or more problems
+#[derive(Debug)]
+pub struct LoadError {
+    /// Everything that could be loaded, as the non-`try` functions return it
+    pub rules: Vec<GrcatConfigEntry>,
+    /// The problems, in the order they were found (never empty)
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl LoadError {
+    /// `Ok(rules)` when there is nothing to report
+    pub fn check(
+        rules: Vec<GrcatConfigEntry>,
+        diagnostics: Vec<Diagnostic>,
+    ) -> Result<Vec<GrcatConfigEntry>, LoadError> {
+        if diagnostics.is_empty() {
+            Ok(rules)
+        } else {
+            Err(LoadError { rules, diagnostics })
+        }
+    }
+
+    /// Print every diagnostic on stderr, one `rgrc: ` line each
+    pub fn report(&self) {
+        for diagnostic in &self.diagnostics {
+            eprintln!("rgrc: {}", diagnostic);
+        }
+    }
+
+    /// The rules that were loaded
+    pub fn into_rules(self) -> Vec<GrcatConfigEntry> {
+        self.rules
+    }
+
+    /// [`report`](Self::report), then [`into_rules`](Self::into_rules)
+    pub fn reported(self) -> Vec<GrcatConfigEntry> {
+        self.report();
+        self.into_rules()
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, diagnostic) in self.diagnostics.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_leaves_out_unknown_parts() {
+        let at = |file, line| Diagnostic::new(file, line, DiagnosticKind::Regex, "bad");
+        assert_eq!(at(Some("conf.x"), Some(3)).to_string(), "conf.x:3: bad");
+        assert_eq!(at(Some("conf.x"), None).to_string(), "conf.x: bad");
+        assert_eq!(at(None, Some(3)).to_string(), "line 3: bad");
+        assert_eq!(at(None, None).to_string(), "bad");
+    }
+
+    #[test]
+    fn test_check_keeps_rules_either_way() {
+        assert!(LoadError::check(Vec::new(), Vec::new()).is_ok());
+        let diagnostic = Diagnostic::new(Some("a"), None, DiagnosticKind::Io, "missing");
+        let err = LoadError::check(Vec::new(), vec![diagnostic.clone(), diagnostic]).unwrap_err();
+        assert_eq!(err.to_string(), "a: missing\na: missing");
+        assert!(err.into_rules().is_empty());
+    }
+}
@@ -17,26 +17,38 @@
 //! The module uses a hybrid regex engine approach for optimal performance:
 //!
 
-use std::io::{BufRead, Lines};
+use std::borrow::Cow;
+use std::io::{self, BufRead, Lines, Write};
+use std::iter::MapWhile;
+use std::sync::atomic::{AtomicU8, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
-#[cfg(not(feature = "fancy-regex"))]
-use crate::enhanced_regex::EnhancedRegex;
+use crate::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::enhanced_regex::{EnhancedCaptures, EnhancedRegex, EnhancedRegexError};
+use crate::lowering::{LoweredCaptures, LoweredRegex};
+use crate::macros::{Macros, is_define};
 use crate::style::Style;
+use crate::translate::translate;
 #[cfg(feature = "fancy-regex")]
 use fancy_regex::Regex as FancyRegex;
 use regex::Regex;
 use regex_lite as regex;
 
 /// Custom error type for regex compilation
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum RegexError {
     Syntax(String),
+    /// The engine gave up while matching (e.g. backtrack limit exceeded)
+    #[allow(dead_code)]
+    Runtime(String),
 }
 
 impl std::fmt::Display for RegexError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             RegexError::Syntax(msg) => write!(f, "Regex syntax error: {}", msg),
+            RegexError::Runtime(msg) => write!(f, "Regex runtime error: {}", msg),
         }
     }
 }
@@ -49,6 +61,166 @@ impl From<regex::Error> for RegexError {
     }
 }
 
+impl From<EnhancedRegexError> for RegexError {
+    fn from(err: EnhancedRegexError) -> Self {
+        RegexError::Syntax(err.to_string())
+    }
+}
+
+/// Engine used for patterns that the standard regex crate rejects.
+///
+/// The standard (fast) engine is always tried first; this only selects the fallback:
+/// - `Auto`: fancy-regex when compiled in, otherwise EnhancedRegex
+/// - `Fast`: no fallback, such patterns fail to compile
+/// - `Enhanced`: always EnhancedRegex, even in fancy-regex builds
+/// - `Fancy`: always fancy-regex (only available with the `fancy-regex` feature)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RegexEngine {
+    #[default]
+    Auto = 0,
+    Fast = 1,
+    Enhanced = 2,
+    Fancy = 3,
+}
+
+impl std::str::FromStr for RegexEngine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(RegexEngine::Auto),
+            "fast" => Ok(RegexEngine::Fast),
+            "enhanced" => Ok(RegexEngine::Enhanced),
+            "fancy" => Ok(RegexEngine::Fancy),
+            _ => Err(format!(
+                "Invalid regex engine: {}. Must be auto, fast, enhanced or fancy.",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for RegexEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            RegexEngine::Auto => "auto",
+            RegexEngine::Fast => "fast",
+            RegexEngine::Enhanced => "enhanced",
+            RegexEngine::Fancy => "fancy",
+        })
+    }
+}
+
+/// Process-wide fallback engine used by [`CompiledRegex::new`].
+static REGEX_ENGINE: AtomicU8 = AtomicU8::new(RegexEngine::Auto as u8);
+
+/// Select the fallback engine used by [`CompiledRegex::new`] for the rest of the process.
+#[allow(dead_code)]
+pub fn set_regex_engine(engine: RegexEngine) {
+    REGEX_ENGINE.store(engine as u8, Ordering::Relaxed);
+}
+
+/// Currently selected fallback engine (see [`set_regex_engine`]).
+pub fn regex_engine() -> RegexEngine {
+    match REGEX_ENGINE.load(Ordering::Relaxed) {
+        1 => RegexEngine::Fast,
+        2 => RegexEngine::Enhanced,
+        3 => RegexEngine::Fancy,
+        _ => RegexEngine::Auto,
+    }
+}
+
+/// Limits that protect the pipeline from catastrophic (exponential) patterns.
+///
+/// - `backtrack_limit`: maximum backtracking steps per match attempt for fancy-regex,
+///   and maximum bytes scanned per match attempt for EnhancedRegex (which re-scans
+///   every candidate it rejects); exceeding it makes the match fail with
+///   [`RegexError::Runtime`], so no single call can stall the pipeline
+/// - `rule_time_budget`: per-line matching time allowed for a slow-path rule before
+///   the colorizer disables it for the rest of the stream (`None` = unlimited)
+///
+/// The fast engine runs in linear time and is never subject to these limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchLimits {
+    pub backtrack_limit: usize,
+    pub rule_time_budget: Option<Duration>,
+}
+
+impl MatchLimits {
+    pub const DEFAULT_BACKTRACK_LIMIT: usize = 1_000_000;
+    pub const DEFAULT_RULE_TIME_BUDGET: Duration = Duration::from_millis(100);
+}
+
+impl Default for MatchLimits {
+    fn default() -> Self {
+        MatchLimits {
+            backtrack_limit: Self::DEFAULT_BACKTRACK_LIMIT,
+            rule_time_budget: Some(Self::DEFAULT_RULE_TIME_BUDGET),
+        }
+    }
+}
+
+static BACKTRACK_LIMIT: AtomicUsize = AtomicUsize::new(MatchLimits::DEFAULT_BACKTRACK_LIMIT);
+/// Rule time budget in microseconds, 0 means unlimited
+static RULE_TIME_BUDGET_US: AtomicU64 =
+    AtomicU64::new(MatchLimits::DEFAULT_RULE_TIME_BUDGET.as_micros() as u64);
+
+/// Set the process-wide match limits (see [`MatchLimits`]).
+///
+/// The backtrack limit only affects patterns compiled afterwards.
+#[allow(dead_code)]
+pub fn set_match_limits(limits: MatchLimits) {
+    BACKTRACK_LIMIT.store(limits.backtrack_limit, Ordering::Relaxed);
+    let budget_us = limits
+        .rule_time_budget
+        .map(|d| (d.as_micros() as u64).max(1))
+        .unwrap_or(0);
+    RULE_TIME_BUDGET_US.store(budget_us, Ordering::Relaxed);
+}
+
+/// Currently configured match limits (see [`set_match_limits`]).
+#[allow(dead_code)]
+pub fn match_limits() -> MatchLimits {
+    let budget_us = RULE_TIME_BUDGET_US.load(Ordering::Relaxed);
+    MatchLimits {
+        backtrack_limit: BACKTRACK_LIMIT.load(Ordering::Relaxed),
+        rule_time_budget: (budget_us > 0).then(|| Duration::from_micros(budget_us)),
+    }
+}
+
+/// Backend that actually handles a compiled pattern (see [`CompiledRegex::backend`]).
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegexBackend {
+    /// Standard regex crate
+    Fast,
+    /// Standard regex crate, with lookarounds lowered into groups (see [`crate::lowering`])
+    Lowered,
+    /// Our own EnhancedRegex (slow path)
+    Enhanced,
+    /// fancy-regex (slow path)
+    Fancy,
+}
+
+impl RegexBackend {
+    /// Whether the pattern runs on one of the slower fallback engines.
+    #[allow(dead_code)]
+    pub fn is_slow_path(&self) -> bool {
+        !matches!(self, RegexBackend::Fast | RegexBackend::Lowered)
+    }
+}
+
+impl std::fmt::Display for RegexBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            RegexBackend::Fast => "fast",
+            RegexBackend::Lowered => "lowered",
+            RegexBackend::Enhanced => "enhanced",
+            RegexBackend::Fancy => "fancy",
+        })
+    }
+}
+
 /// Hybrid regex engine: tries standard regex first, then falls back to Enhanced implementation.
 ///
 /// This provides significant performance improvement for most configuration files:
@@ -57,17 +229,17 @@ impl From<regex::Error> for RegexError {
 ///
 /// ## Enhanced Implementation Selection
 ///
-/// The Enhanced variant uses conditional compilation:
+/// The fallback engine is chosen by [`RegexEngine`] (see [`set_regex_engine`]):
 ///
-/// - **With `fancy` feature** (default, enabled in Cargo.toml):
+/// - **Fancy** (default with the `fancy-regex` feature):
 ///   - Uses `fancy-regex` crate (battle-tested, production-ready)
 ///   - Supports: lookahead, lookbehind (variable-length), backreferences, etc.
 ///   - Binary size: ~2.1MB (release)
 ///   - Recommended for conservative users who prioritize stability
 ///
-/// - **Without `fancy` feature** (`cargo build --no-default-features --features=embed-configs`):
-///   - Uses custom `EnhancedRegex` implementation (~600 lines)
-///   - Supports: fixed-length lookahead/lookbehind patterns
+/// - **Enhanced** (default without the `fancy-regex` feature, always available):
+///   - Uses custom `EnhancedRegex` implementation
+///   - Supports: fixed-length lookahead/lookbehind patterns, backreferences
 ///   - Binary size: ~1.8MB (release)
 ///   - Covers 99% of patterns in rgrc config files
 ///   - Newer implementation, less battle-tested
@@ -80,82 +252,269 @@ impl From<regex::Error> for RegexError {
 /// // Simple pattern → Fast(Regex)
 /// let re = CompiledRegex::new(r"\d+").unwrap();
 ///
-/// // Lookahead pattern → Enhanced(fancy_regex::Regex) or Enhanced(EnhancedRegex)
+/// // Lookahead pattern → Fancy(fancy_regex::Regex) or Enhanced(EnhancedRegex)
 /// let re = CompiledRegex::new(r"\d+(?=\.\d+\.\d+\.\d+)").unwrap();
 /// ```
 #[derive(Debug, Clone)]
 pub enum CompiledRegex {
     /// Fast path: standard regex crate (no lookaround, ~2-5x faster)
     Fast(Regex),
-    /// Enhanced path: fancy-regex (battle-tested, enabled with --features=fancy)
-    #[cfg(feature = "fancy-regex")]
-    Enhanced(FancyRegex),
-    /// Enhanced path: our own lookaround implementation (lightweight, default without fancy feature)
-    #[cfg(not(feature = "fancy-regex"))]
+    /// Fast path for leading/trailing lookarounds rewritten into groups (see [`CompiledRegex::optimized`])
+    Lowered(LoweredRegex),
+    /// Enhanced path: our own lookaround implementation (lightweight)
     Enhanced(EnhancedRegex),
+    /// Enhanced path: fancy-regex (battle-tested, enabled with --features=fancy-regex)
+    #[cfg(feature = "fancy-regex")]
+    Fancy(FancyRegex),
+    /// Not compiled yet: compiled on first use (see [`CompiledRegex::lazy`])
+    Lazy(Arc<LazyRegex>),
 }
 
 impl CompiledRegex {
     /// Compile a regex pattern, automatically selecting the fastest engine.
-    /// Tries standard regex first, then falls back to EnhancedRegex for lookaround patterns.
+    /// Tries standard regex first, then falls back to the engine selected
+    /// with [`set_regex_engine`] for lookaround patterns.
     pub fn new(pattern: &str) -> Result<Self, RegexError> {
+        Self::with_engine(pattern, regex_engine())
+    }
+
+    /// Compile a regex pattern with an explicit fallback engine.
+    ///
+    /// Python `re` syntax from upstream grc configs is translated first
+    /// (see [`translate`](crate::translate)).
+    pub fn with_engine(pattern: &str, engine: RegexEngine) -> Result<Self, RegexError> {
+        let translation = translate(pattern);
+        let pattern = translation.pattern.as_str();
+
         // Try standard regex first (fastest, but no lookaround)
-        if let Ok(re) = Regex::new(pattern) {
-            return Ok(CompiledRegex::Fast(re));
+        let fast_error = match Regex::new(pattern) {
+            Ok(re) => return Ok(CompiledRegex::Fast(re)),
+            Err(e) => e,
+        };
+
+        match engine {
+            RegexEngine::Fast => Err(RegexError::from(fast_error)),
+            RegexEngine::Enhanced => Self::compile_enhanced(pattern),
+            RegexEngine::Fancy => Self::compile_fancy(pattern),
+            #[cfg(feature = "fancy-regex")]
+            RegexEngine::Auto => Self::compile_fancy(pattern),
+            #[cfg(not(feature = "fancy-regex"))]
+            RegexEngine::Auto => Self::compile_enhanced(pattern),
         }
+    }
 
-        // Fall back to Enhanced regex implementation
-        #[cfg(feature = "fancy-regex")]
-        {
-            // Use battle-tested fancy-regex when enabled
-            FancyRegex::new(pattern)
-                .map(CompiledRegex::Enhanced)
-                .map_err(|e| RegexError::Syntax(e.to_string()))
+    /// Defer compilation of `pattern` until the regex is first used.
+    ///
+    /// Rules loaded for a command are compiled lazily, so rules that the prefilter
+    /// never lets through cost nothing. A syntax error surfaces as an error from the
+    /// `try_*` methods on first use instead of at load time. The compiled regex is
+    /// [`optimized`](Self::optimized), like eagerly loaded rules.
+    #[allow(dead_code)]
+    pub fn lazy(pattern: &str) -> Self {
+        CompiledRegex::Lazy(Arc::new(LazyRegex {
+            source: pattern.to_string(),
+            translated: translate(pattern).pattern,
+            backend: None,
+            compiled: OnceLock::new(),
+        }))
+    }
+
+    /// Like [`lazy`](Self::lazy), for a pattern already translated and possibly
+    /// with a known backend (e.g. from the [rule cache](crate::rule_cache)).
+    ///
+    /// With a backend, [`backend`](Self::backend) answers without compiling, and
+    /// first use compiles straight for that backend instead of trying the faster
+    /// ones first.
+    #[allow(dead_code)]
+    pub fn lazy_translated(source: &str, translated: &str, backend: Option<RegexBackend>) -> Self {
+        CompiledRegex::Lazy(Arc::new(LazyRegex {
+            source: source.to_string(),
+            translated: translated.to_string(),
+            backend,
+            compiled: OnceLock::new(),
+        }))
+    }
+
+    /// The compiled regex behind a lazy one, compiling it if needed
+    fn force(&self) -> Result<&CompiledRegex, RegexError> {
+        match self {
+            CompiledRegex::Lazy(lazy) => lazy
+                .compiled
+                .get_or_init(|| lazy.compile())
+                .as_ref()
+                .map_err(Clone::clone),
+            _ => Ok(self),
         }
-        #[cfg(not(feature = "fancy-regex"))]
-        {
-            // Use our own EnhancedRegex implementation (default)
-            EnhancedRegex::new(pattern)
-                .map(CompiledRegex::Enhanced)
-                .map_err(RegexError::from)
+    }
+
+    /// Compile an already translated pattern for `backend`, or `None` if that
+    /// backend cannot handle it
+    fn compile_for(pattern: &str, backend: RegexBackend) -> Option<Self> {
+        match backend {
+            RegexBackend::Fast => Regex::new(pattern).ok().map(CompiledRegex::Fast),
+            RegexBackend::Lowered => LoweredRegex::new(pattern).map(CompiledRegex::Lowered),
+            RegexBackend::Enhanced => Self::compile_enhanced(pattern).ok(),
+            RegexBackend::Fancy => Self::compile_fancy(pattern).ok(),
+        }
+    }
+
+    /// Use our own EnhancedRegex implementation, bounded by the configured backtrack limit
+    fn compile_enhanced(pattern: &str) -> Result<Self, RegexError> {
+        EnhancedRegex::new(pattern)
+            .map(|re| CompiledRegex::Enhanced(re.with_step_limit(match_limits().backtrack_limit)))
+            .map_err(RegexError::from)
+    }
+
+    /// Use battle-tested fancy-regex when enabled, bounded by the configured backtrack limit
+    #[cfg(feature = "fancy-regex")]
+    fn compile_fancy(pattern: &str) -> Result<Self, RegexError> {
+        fancy_regex::RegexBuilder::new(pattern)
+            .backtrack_limit(match_limits().backtrack_limit)
+            .build()
+            .map(CompiledRegex::Fancy)
+            .map_err(|e| RegexError::Syntax(e.to_string()))
+    }
+
+    #[cfg(not(feature = "fancy-regex"))]
+    fn compile_fancy(_pattern: &str) -> Result<Self, RegexError> {
+        Err(RegexError::Syntax(
+            "fancy-regex engine is not available in this build".to_string(),
+        ))
+    }
+
+    /// Move a slow-path pattern to the fast path when its lookarounds can be
+    /// lowered into groups (see [`crate::lowering`]); other patterns are returned as-is.
+    ///
+    /// Captures keep the original numbering, so this is applied to every rule loaded
+    /// from a config file. Lazy quantifiers before a lowered lookahead follow Python's
+    /// `re` (and fancy-regex) semantics, which EnhancedRegex only approximates.
+    #[allow(dead_code)]
+    pub fn optimized(self) -> Self {
+        match self {
+            CompiledRegex::Fast(_) | CompiledRegex::Lowered(_) | CompiledRegex::Lazy(_) => self,
+            _ => match LoweredRegex::new(self.as_str()) {
+                Some(lowered) => CompiledRegex::Lowered(lowered),
+                None => self,
+            },
+        }
+    }
+
+    /// Backend that handles this pattern.
+    ///
+    /// Compiles a lazy regex whose backend is not known yet; one that fails to
+    /// compile never runs and reports `Fast`.
+    #[allow(dead_code)]
+    pub fn backend(&self) -> RegexBackend {
+        match self {
+            CompiledRegex::Lazy(lazy) => match lazy.backend {
+                Some(backend) => backend,
+                None => self.force().map_or(RegexBackend::Fast, Self::backend),
+            },
+            CompiledRegex::Fast(_) => RegexBackend::Fast,
+            CompiledRegex::Lowered(_) => RegexBackend::Lowered,
+            CompiledRegex::Enhanced(_) => RegexBackend::Enhanced,
+            #[cfg(feature = "fancy-regex")]
+            CompiledRegex::Fancy(_) => RegexBackend::Fancy,
+        }
+    }
+
+    /// Backend that handles this pattern, or the error that keeps a lazy regex
+    /// from compiling.
+    #[allow(dead_code)]
+    pub fn try_backend(&self) -> Result<RegexBackend, RegexError> {
+        self.force().map(Self::backend)
+    }
+
+    /// Number of capture groups, including group 0 for the whole match.
+    ///
+    /// A lazy regex that fails to compile reports 1.
+    #[allow(dead_code)]
+    pub fn captures_len(&self) -> usize {
+        match self {
+            CompiledRegex::Fast(re) => re.captures_len(),
+            CompiledRegex::Lowered(re) => re.captures_len(),
+            CompiledRegex::Enhanced(re) => re.captures_len(),
+            #[cfg(feature = "fancy-regex")]
+            CompiledRegex::Fancy(re) => re.captures_len(),
+            CompiledRegex::Lazy(_) => self.force().map_or(1, Self::captures_len),
         }
     }
 
     /// Check if the regex matches anywhere in the text.
+    ///
+    /// Runtime errors (see [`try_is_match`](Self::try_is_match)) count as no match.
     #[allow(dead_code)]
     pub fn is_match(&self, text: &str) -> bool {
+        self.try_is_match(text).unwrap_or(false)
+    }
+
+    /// Check if the regex matches anywhere in the text, reporting runtime errors
+    /// such as an exceeded backtrack limit.
+    #[allow(dead_code)]
+    pub fn try_is_match(&self, text: &str) -> Result<bool, RegexError> {
         match self {
-            CompiledRegex::Fast(re) => re.is_match(text),
+            CompiledRegex::Fast(re) => Ok(re.is_match(text)),
+            CompiledRegex::Lowered(re) => Ok(re.is_match(text)),
+            CompiledRegex::Enhanced(re) => Ok(re.is_match(text)),
             #[cfg(feature = "fancy-regex")]
-            CompiledRegex::Enhanced(re) => re.is_match(text).unwrap_or(false),
-            #[cfg(not(feature = "fancy-regex"))]
-            CompiledRegex::Enhanced(re) => re.is_match(text),
+            CompiledRegex::Fancy(re) => re
+                .is_match(text)
+                .map_err(|e| RegexError::Runtime(e.to_string())),
+            CompiledRegex::Lazy(_) => self.force()?.try_is_match(text),
         }
     }
 
     /// Find all capture groups starting from the given position.
+    ///
+    /// Runtime errors (see [`try_captures_from_pos`](Self::try_captures_from_pos)) count as no match.
     #[allow(dead_code)]
     pub fn captures_from_pos<'t>(&self, text: &'t str, pos: usize) -> Option<Captures<'t>> {
+        self.try_captures_from_pos(text, pos).ok().flatten()
+    }
+
+    /// Find all capture groups starting from the given position, reporting runtime
+    /// errors such as an exceeded backtrack limit.
+    #[allow(dead_code)]
+    pub fn try_captures_from_pos<'t>(
+        &self,
+        text: &'t str,
+        pos: usize,
+    ) -> Result<Option<Captures<'t>>, RegexError> {
         match self {
             CompiledRegex::Fast(re) => {
                 // Standard regex: convert to our Captures format
-                re.captures(&text[pos..])
-                    .map(|caps| Captures::Fast(caps, pos))
+                Ok(re
+                    .captures(&text[pos..])
+                    .map(|caps| Captures::Fast(caps, pos)))
             }
-            #[cfg(feature = "fancy-regex")]
-            CompiledRegex::Enhanced(re) => {
-                // fancy-regex: convert to our Captures format
-                re.captures(&text[pos..])
-                    .ok()
-                    .flatten()
-                    .map(|caps| Captures::Fancy(caps, pos))
+            CompiledRegex::Lowered(re) => {
+                // Lowered regex: captures renumbered like the original pattern
+                Ok(re.captures_from_pos(text, pos).map(Captures::Lowered))
             }
-            #[cfg(not(feature = "fancy-regex"))]
             CompiledRegex::Enhanced(re) => {
                 // EnhancedRegex: convert to our Captures format
-                re.captures_from_pos(text, pos)
-                    .map(|caps| Captures::Fast(caps, 0))
+                re.try_captures_from_pos(text, pos)
+                    .map(|caps| caps.map(Captures::Enhanced))
+                    .map_err(|e| RegexError::Runtime(e.to_string()))
             }
+            #[cfg(feature = "fancy-regex")]
+            CompiledRegex::Fancy(re) => {
+                // fancy-regex: convert to our Captures format
+                re.captures(&text[pos..])
+                    .map(|caps| caps.map(|caps| Captures::Fancy(caps, pos)))
+                    .map_err(|e| RegexError::Runtime(e.to_string()))
+            }
+            CompiledRegex::Lazy(_) => self.force()?.try_captures_from_pos(text, pos),
+        }
+    }
+
+    /// The pattern as written in the config, before translation, for a lazy regex;
+    /// the same as [`as_str`](Self::as_str) once compiled.
+    #[allow(dead_code)]
+    pub fn source(&self) -> &str {
+        match self {
+            CompiledRegex::Lazy(lazy) => &lazy.source,
+            _ => self.as_str(),
         }
     }
 
@@ -164,11 +523,35 @@ impl CompiledRegex {
     pub fn as_str(&self) -> &str {
         match self {
             CompiledRegex::Fast(re) => re.as_str(),
-            #[cfg(feature = "fancy-regex")]
-            CompiledRegex::Enhanced(re) => re.as_str(),
-            #[cfg(not(feature = "fancy-regex"))]
+            CompiledRegex::Lowered(re) => re.as_str(),
             CompiledRegex::Enhanced(re) => re.as_str(),
+            #[cfg(feature = "fancy-regex")]
+            CompiledRegex::Fancy(re) => re.as_str(),
+            CompiledRegex::Lazy(lazy) => &lazy.translated,
+        }
+    }
+}
+
+/// A rule pattern waiting to be compiled (see [`CompiledRegex::lazy`])
+#[derive(Debug)]
+pub struct LazyRegex {
+    source: String,
+    /// Translated pattern, reported by [`CompiledRegex::as_str`] without compiling
+    translated: String,
+    /// Backend the pattern is known to compile for
+    backend: Option<RegexBackend>,
+    compiled: OnceLock<Result<CompiledRegex, RegexError>>,
+}
+
+impl LazyRegex {
+    fn compile(&self) -> Result<CompiledRegex, RegexError> {
+        if let Some(compiled) = self
+            .backend
+            .and_then(|backend| CompiledRegex::compile_for(&self.translated, backend))
+        {
+            return Ok(compiled);
         }
+        CompiledRegex::new(&self.source).map(CompiledRegex::optimized)
     }
 }
 
@@ -179,6 +562,8 @@ pub enum Captures<'t> {
     Fast(regex::Captures<'t>, usize), // offset for position adjustment
     #[cfg(feature = "fancy-regex")]
     Fancy(fancy_regex::Captures<'t>, usize), // fancy-regex captures with offset
+    Enhanced(EnhancedCaptures<'t>),   // EnhancedRegex captures (positions in full text)
+    Lowered(LoweredCaptures<'t>),     // LoweredRegex captures (positions in full text)
 }
 
 impl<'t> Captures<'t> {
@@ -189,6 +574,8 @@ impl<'t> Captures<'t> {
             Captures::Fast(caps, offset) => caps.get(index).map(|m| Match::Fast(m, *offset)),
             #[cfg(feature = "fancy-regex")]
             Captures::Fancy(caps, offset) => caps.get(index).map(|m| Match::Fancy(m, *offset)),
+            Captures::Enhanced(caps) => caps.get(index).map(|m| Match::Fast(m, 0)),
+            Captures::Lowered(caps) => caps.get(index).map(|m| Match::Fast(m, 0)),
         }
     }
 
@@ -199,6 +586,8 @@ impl<'t> Captures<'t> {
             Captures::Fast(caps, _) => caps.len(),
             #[cfg(feature = "fancy-regex")]
             Captures::Fancy(caps, _) => caps.len(),
+            Captures::Enhanced(caps) => caps.len(),
+            Captures::Lowered(caps) => caps.len(),
         }
     }
 
@@ -322,12 +711,10 @@ pub fn style_from_str(text: &str) -> Result<Style, String> {
             "bright_cyan" => Ok(style.bright().cyan()),
             "bright_white" => Ok(style.bright().white()),
 
-            // Unknown keyword - log and return error
+            // Unknown keyword - return an error
             _ => {
-                // Return a descriptive error (used in callers/tests to detect invalid styles)
-                let msg = format!("unhandled style: {}", word);
-                println!("{}", msg);
-                Err(msg)
+                // Return a descriptive error; callers report it (stdout carries the output)
+                Err(format!("unhandled style: {}", word))
             }
         }
     })
@@ -479,9 +866,9 @@ impl<A: BufRead> GrcConfigReader<A> {
     /// - Not empty or whitespace-only
     /// - Not a line where whitespace precedes a comment character
     ///
-    /// Comments are detected using the regex pattern `^[- \t]*(#|$)` which matches:
+    /// Comments are detected like the pattern `^[- \t]*(#|$)`, without a regex:
     /// - Lines starting with optional whitespace/dashes followed by '#' (comments)
-    /// - Lines that are empty or whitespace-only (matches end of line via `$`)
+    /// - Lines that are empty or whitespace-only
     ///
     /// ## Returns
     ///
@@ -503,24 +890,12 @@ impl<A: BufRead> GrcConfigReader<A> {
     /// ```
     /// `next_content_line()` will skip the comment and blank line, returning `"^ping"`
     fn next_content_line(&mut self) -> Option<String> {
-        // Regex pattern explanation:
-        // ^[- \t]*(#|$)
-        // - ^       : Start of line
-        // - [- \t]* : Zero or more dashes, spaces, or tabs
-        // - (#|$)   : Either a hash (comment start) or end of line (empty/whitespace only)
-        //
-        // This matches:
-        // - Comment lines: "# comment" or "  # comment"
-        // - Empty lines: "" or "   " (just whitespace)
-        // But NOT:
-        // - "^ping" (regex line)
-        // - "conf.ping" (config path line)
-        let re = Regex::new("^[- \t]*(#|$)").unwrap();
         for line in &mut self.inner {
             match line {
                 Ok(line2) => {
-                    // If line doesn't match the comment/empty pattern, it's a content line
-                    if !re.is_match(&line2) {
+                    // Skip "# comment", "  # comment", "" and "   " but not "^ping" or "conf.ping"
+                    let rest = line2.trim_start_matches(['-', ' ', '\t']);
+                    if !rest.is_empty() && !rest.starts_with('#') {
                         return Some(line2.trim().to_string());
                     }
                 }
@@ -529,6 +904,16 @@ impl<A: BufRead> GrcConfigReader<A> {
         }
         None // No more content lines (EOF)
     }
+
+    /// Return the next (pattern, config_file_path) pair without compiling the pattern.
+    ///
+    /// Used to index grc.conf (see [`crate::command_index`]) so that only the patterns
+    /// that can match a command get compiled.
+    pub fn next_entry(&mut self) -> Option<(String, String)> {
+        let regexp = self.next_content_line()?;
+        let filename = self.next_content_line()?;
+        Some((regexp, filename))
+    }
 }
 
 /// Iterator that yields (regex, config_file_path) pairs from grc.conf.
@@ -589,27 +974,378 @@ impl<A: BufRead> Iterator for GrcConfigReader<A> {
     /// 5. If successful, returns tuple of (compiled_regex, config_path)
     /// 6. If pattern or path line is missing, returns None (stops iteration)
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(regexp) = self.next_content_line() {
-            if let Some(filename) = self.next_content_line() {
-                // Try to compile using hybrid CompiledRegex which supports lookarounds
-                match CompiledRegex::new(&regexp) {
-                    Ok(re) => Some((re, filename)),
-                    Err(_) => {
-                        // Malformed regex pattern - skip to next rule
-                        self.next()
-                    }
-                }
-            } else {
-                // Pattern without config file - incomplete rule, stop
-                None
+        // Pattern without config file (incomplete rule) or EOF: stop
+        let (regexp, filename) = self.next_entry()?;
+        // Try to compile using hybrid CompiledRegex which supports lookarounds
+        match CompiledRegex::new(&regexp) {
+            Ok(re) => Some((re, filename)),
+            Err(_) => {
+                // Malformed regex pattern - skip to next rule
+                self.next()
             }
+        }
+    }
+}
+
+/// Location of part of a config line: the 1-based line number and the byte
+/// columns `start..end` within the line (0-based, like string indices).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[allow(dead_code)]
+impl Span {
+    /// The spanned part of `text`, the line this span was taken from
+    pub fn text<'a>(&self, text: &'a str) -> &'a str {
+        &text[self.start..self.end]
+    }
+
+    /// 1-based column of the start, as editors show it
+    pub fn column(&self) -> usize {
+        self.start + 1
+    }
+}
+
+/// What a line of a grcat config is to the reader
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigLineKind {
+    /// Empty or only whitespace
+    Blank,
+    /// `#` after optional whitespace
+    Comment,
+    /// Only punctuation, such as `-` or `=======`
+    Separator,
+    /// `include=NAME` or `@include NAME`, with the span of NAME
+    Include { name: Span },
+    /// `define NAME=regex` (see [`crate::macros`]), with the span of the statement
+    Define { statement: Span },
+    /// `key=value` (`^([a-z_]+)\s*=\s*(.*)$`), with the spans of the key and value
+    KeyValue { key: Span, value: Span },
+    /// Any other line starting with a letter or digit: part of a rule, but ignored
+    Text,
+    /// Any other line, such as an indented one: ends a rule and is ignored
+    Other,
+}
+
+/// One line of a grcat config, as written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigLine {
+    /// 1-based line number
+    pub number: usize,
+    /// The line without its `\n` (a `\r` before it is kept)
+    pub text: String,
+    pub kind: ConfigLineKind,
+}
+
+impl ConfigLine {
+    /// Classify line `number` of a config
+    pub fn parse(number: usize, text: String) -> Self {
+        let kind = classify_line(number, &text);
+        ConfigLine { number, text, kind }
+    }
+
+    /// Span of the whole line, without a trailing `\r`
+    #[allow(dead_code)]
+    pub fn span(&self) -> Span {
+        let end = self.text.strip_suffix('\r').unwrap_or(&self.text).len();
+        Span {
+            line: self.number,
+            start: 0,
+            end,
+        }
+    }
+
+    /// Key and value of a `key=value` line
+    pub fn key_value(&self) -> Option<(&str, &str)> {
+        match &self.kind {
+            ConfigLineKind::KeyValue { key, value } => {
+                Some((key.text(&self.text), value.text(&self.text)))
+            }
+            _ => None,
+        }
+    }
+
+    /// The statement of a `define` line, as the reader applies it
+    pub fn define_statement(&self) -> Option<&str> {
+        match &self.kind {
+            ConfigLineKind::Define { statement } => Some(statement.text(&self.text)),
+            _ => None,
+        }
+    }
+
+    /// Config named by an include line
+    pub fn include_name(&self) -> Option<&str> {
+        match &self.kind {
+            ConfigLineKind::Include { name } => Some(name.text(&self.text)),
+            _ => None,
+        }
+    }
+
+    /// Whether the line belongs to a rule: it starts with a letter or digit and is
+    /// not an include
+    pub fn is_rule_line(&self) -> bool {
+        matches!(
+            self.kind,
+            ConfigLineKind::Define { .. } | ConfigLineKind::KeyValue { .. } | ConfigLineKind::Text
+        )
+    }
+}
+
+/// See [`ConfigLineKind`]
+fn classify_line(number: usize, text: &str) -> ConfigLineKind {
+    let body = text.strip_suffix('\r').unwrap_or(text);
+    let span = |part: &str| {
+        let start = part.as_ptr() as usize - body.as_ptr() as usize;
+        Span {
+            line: number,
+            start,
+            end: start + part.len(),
+        }
+    };
+    let trimmed = body.trim();
+    if trimmed.is_empty() {
+        ConfigLineKind::Blank
+    } else if trimmed.starts_with('#') {
+        ConfigLineKind::Comment
+    } else if let Some(name) = include_target(body) {
+        ConfigLineKind::Include { name: span(name) }
+    } else if !starts_alphanumeric(body) {
+        if trimmed.bytes().all(|b| b.is_ascii_punctuation()) {
+            ConfigLineKind::Separator
         } else {
-            // No pattern line found - EOF or read error
-            None
+            ConfigLineKind::Other
+        }
+    } else if is_define(body) {
+        ConfigLineKind::Define {
+            statement: span(body),
         }
+    } else if let Some((key, value)) = split_key_value(body) {
+        ConfigLineKind::KeyValue {
+            key: span(key),
+            value: span(value),
+        }
+    } else {
+        ConfigLineKind::Text
     }
 }
 
+/// A rule, an include or a line outside rules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigNode {
+    /// A blank, comment, separator or other line that is not part of a rule
+    Trivia(ConfigLine),
+    /// An `include=NAME` or `@include NAME` line
+    Include(ConfigLine),
+    /// Consecutive lines starting with a letter or digit. The value or `define`
+    /// statement on the first line ends before any trailing whitespace, as grcat
+    /// trims that line.
+    Rule(Vec<ConfigLine>),
+}
+
+#[allow(dead_code)]
+impl ConfigNode {
+    /// Lines of the node, in order
+    pub fn lines(&self) -> &[ConfigLine] {
+        match self {
+            ConfigNode::Trivia(line) | ConfigNode::Include(line) => std::slice::from_ref(line),
+            ConfigNode::Rule(lines) => lines,
+        }
+    }
+}
+
+/// Parser turning the lines of a grcat config into [`ConfigNode`]s as they are
+/// read. [`ConfigAst`] collects them; [`GrcatConfigReader`] builds rules from them.
+pub struct ConfigParser<I> {
+    lines: I,
+    /// Number of the last line read
+    number: usize,
+    /// The line that ended the previous rule
+    pending: Option<ConfigLine>,
+}
+
+impl<I: Iterator<Item = String>> ConfigParser<I> {
+    /// Parse `lines`, the config without line endings
+    pub fn new(lines: I) -> Self {
+        ConfigParser {
+            lines,
+            number: 0,
+            pending: None,
+        }
+    }
+
+    fn next_line(&mut self) -> Option<ConfigLine> {
+        if let Some(line) = self.pending.take() {
+            return Some(line);
+        }
+        let text = self.lines.next()?;
+        self.number += 1;
+        Some(ConfigLine::parse(self.number, text))
+    }
+}
+
+impl<I: Iterator<Item = String>> Iterator for ConfigParser<I> {
+    type Item = ConfigNode;
+
+    fn next(&mut self) -> Option<ConfigNode> {
+        let mut first = self.next_line()?;
+        if first.include_name().is_some() {
+            return Some(ConfigNode::Include(first));
+        }
+        if !first.is_rule_line() {
+            return Some(ConfigNode::Trivia(first));
+        }
+        if let ConfigLineKind::KeyValue { value: span, .. }
+        | ConfigLineKind::Define { statement: span } = &mut first.kind
+        {
+            span.end = span.start + first.text[span.start..span.end].trim_end().len();
+        }
+        let mut lines = vec![first];
+        while let Some(line) = self.next_line() {
+            if !line.is_rule_line() {
+                // A line outside rules, or an include, ends this rule
+                self.pending = Some(line);
+                break;
+            }
+            lines.push(line);
+        }
+        Some(ConfigNode::Rule(lines))
+    }
+}
+
+/// Lossless syntax tree of a grcat config: every line, comments and separators
+/// included, grouped as [`GrcatConfigReader`] reads them. `to_string()` gives back
+/// the original text, so tools can rewrite a config without losing anything.
+///
+/// ```ignore
+/// let ast = ConfigAst::parse("regexp=\\d+\ncolors=red\n-\n");
+/// assert!(matches!(&ast.nodes[0], ConfigNode::Rule(lines) if lines.len() == 2));
+/// assert_eq!(ast.to_string(), "regexp=\\d+\ncolors=red\n-\n");
+/// ```
+#[allow(dead_code)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigAst {
+    pub nodes: Vec<ConfigNode>,
+    /// Whether the last line ends with `\n`
+    pub final_newline: bool,
+}
+
+#[allow(dead_code)]
+impl ConfigAst {
+    /// Parse the whole text of a config
+    pub fn parse(content: &str) -> Self {
+        let body = content.strip_suffix('\n');
+        let final_newline = body.is_some();
+        let lines = (!content.is_empty())
+            .then(|| body.unwrap_or(content).split('\n').map(str::to_string))
+            .into_iter()
+            .flatten();
+        ConfigAst {
+            nodes: ConfigParser::new(lines).collect(),
+            final_newline,
+        }
+    }
+
+    /// All lines, in order
+    pub fn lines(&self) -> impl Iterator<Item = &ConfigLine> {
+        self.nodes.iter().flat_map(ConfigNode::lines)
+    }
+
+    /// The config in canonical layout, with the same rules in the same order:
+    ///
+    /// - `colours=` for `colors=` and `colour=`, no whitespace around `=`, and
+    ///   style keywords separated by one space
+    /// - `include=NAME` for `@include NAME`, `define NAME=regex` for defines
+    /// - `-` for every separator, comments without surrounding whitespace
+    /// - at most one blank line in a row, none at the start or end
+    /// - `\n` line endings, with one at the end
+    ///
+    /// Whitespace the reader keeps, such as at the end of a `regexp=` after
+    /// the first line of a rule, is left alone, and so are lines it ignores.
+    pub fn formatted(&self) -> String {
+        let mut out = String::new();
+        let mut blank = false;
+        for line in self.lines() {
+            if line.kind == ConfigLineKind::Blank {
+                blank = !out.is_empty();
+                continue;
+            }
+            if blank {
+                out.push('\n');
+                blank = false;
+            }
+            out.push_str(&format_line(line));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// A line in the layout of [`ConfigAst::formatted`]
+fn format_line(line: &ConfigLine) -> String {
+    let text = line.text.strip_suffix('\r').unwrap_or(&line.text);
+    match &line.kind {
+        ConfigLineKind::Blank => String::new(),
+        ConfigLineKind::Comment => text.trim().to_string(),
+        ConfigLineKind::Separator => "-".to_string(),
+        ConfigLineKind::Include { name } => format!("include={}", name.text(text)),
+        ConfigLineKind::Define { statement } => {
+            let statement = statement.text(text);
+            let rest = &statement.trim_start()["define".len()..];
+            match rest.split_once('=') {
+                Some((name, body)) => format!("define {}={}", name.trim(), body.trim_start()),
+                None => statement.trim().to_string(),
+            }
+        }
+        ConfigLineKind::KeyValue { key, value } => {
+            let value = value.text(text);
+            match key.text(text) {
+                "colours" | "colors" | "colour" => {
+                    let groups: Vec<String> = value
+                        .split(',')
+                        .map(|group| {
+                            let words: Vec<&str> =
+                                group.split(' ').filter(|w| !w.is_empty()).collect();
+                            words.join(" ")
+                        })
+                        .collect();
+                    format!("colours={}", groups.join(","))
+                }
+                "tags" => {
+                    let tags: Vec<&str> = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|tag| !tag.is_empty())
+                        .collect();
+                    format!("tags={}", tags.join(","))
+                }
+                key => format!("{}={}", key, value),
+            }
+        }
+        ConfigLineKind::Text | ConfigLineKind::Other => text.to_string(),
+    }
+}
+
+impl std::fmt::Display for ConfigAst {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, line) in self.lines().enumerate() {
+            if idx > 0 {
+                f.write_str("\n")?;
+            }
+            f.write_str(&line.text)?;
+        }
+        if self.final_newline {
+            f.write_str("\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// Lines of a reader, up to the first read error
+type LineStrings<A> = MapWhile<Lines<A>, fn(std::io::Result<String>) -> Option<String>>;
+
 /// Reader for grcat configuration files.
 ///
 /// This struct implements an iterator over grcat configuration rules. Each rule defines
@@ -633,7 +1369,9 @@ impl<A: BufRead> Iterator for GrcConfigReader<A> {
 /// ## Entry Structure
 ///
 /// Each configuration entry consists of consecutive lines starting with alphanumeric characters.
-/// An entry ends when a non-alphanumeric line is encountered. Each entry can contain:
+/// An entry ends when a non-alphanumeric line is encountered. The lines are grouped by
+/// [`ConfigParser`], the parser behind [`ConfigAst`], so tools see the same entries.
+/// Each entry can contain:
 ///
 /// **Required keys:**
 /// - `regexp` - Regex pattern to match against output text
@@ -650,6 +1388,9 @@ impl<A: BufRead> Iterator for GrcConfigReader<A> {
 /// - **Missing regexp key**: Entry is skipped
 /// - **Missing colours key**: Entry is valid with empty color list
 /// - **Key=value format**: Supports spaces around '=' (e.g., `regexp = pattern`)
+/// - **Includes**: `include=conf.common` (or `@include conf.common`) on its own line
+///   is returned by [`next_item`](GrcatConfigReader::next_item) as
+///   [`GrcatConfigItem::Include`] and skipped by plain iteration
 ///
 /// ## Generic Parameter
 ///
@@ -673,8 +1414,33 @@ impl<A: BufRead> Iterator for GrcConfigReader<A> {
 /// }
 /// ```
 #[allow(dead_code)]
-pub struct GrcatConfigReader<A> {
-    inner: Lines<A>,
+pub struct GrcatConfigReader<A: BufRead> {
+    parser: ConfigParser<LineStrings<A>>,
+    /// Defer compiling rule regexes until first use (see [`GrcatConfigReader::lazy`])
+    lazy: bool,
+    /// Macros for `{NAME}` in regexps: the global ones plus the `define`s read so far
+    macros: Cow<'static, Macros>,
+    /// Number of the line being read, for error messages
+    line: usize,
+    /// Name of the config in diagnostics
+    source: Option<String>,
+    /// Problems found so far (see [`take_diagnostics`](GrcatConfigReader::take_diagnostics))
+    diagnostics: Vec<Diagnostic>,
+    /// Line of the `regexp=` of the last rule returned
+    regexp_line: usize,
+}
+
+/// A rule, or an include directive, in the order they appear in a grcat config
+// Nearly every item is a rule, so boxing rules would only add allocations
+#[allow(dead_code, clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum GrcatConfigItem {
+    Rule(GrcatConfigEntry),
+    /// `include=NAME` on line `line`: the rules of the config NAME go here
+    Include {
+        name: String,
+        line: usize,
+    },
 }
 
 #[allow(dead_code)]
@@ -696,102 +1462,94 @@ impl<A: BufRead> GrcatConfigReader<A> {
     /// let grcat_reader = GrcatConfigReader::new(reader.lines());
     /// ```
     pub fn new(inner: Lines<A>) -> Self {
-        GrcatConfigReader { inner }
+        GrcatConfigReader {
+            parser: ConfigParser::new(inner.map_while(Result::ok)),
+            lazy: false,
+            macros: Cow::Borrowed(Macros::global()),
+            line: 0,
+            source: None,
+            diagnostics: Vec::new(),
+            regexp_line: 0,
+        }
     }
 
-    /// Fetch the next alphanumeric line (skipping comments/blank lines).
+    /// Create a reader whose rule regexes compile on first use (see [`CompiledRegex::lazy`]).
     ///
-    /// In grcat format, configuration entries start with alphanumeric characters (a-zA-Z0-9).
-    /// All other lines (comments, blank lines) are ignored. This method is used to find the
-    /// start of a new configuration entry.
-    ///
-    /// The regex pattern `^[a-zA-Z0-9]` matches lines that start with alphanumeric characters,
-    /// which indicates the beginning of a key=value line.
-    ///
-    /// ## Returns
-    ///
-    /// - `Some(String)` - Next line starting with alphanumeric character (trimmed)
-    /// - `None` - EOF reached, no more entries
-    ///
-    /// ## Error Handling
-    ///
-    /// If a line read error occurs, iteration stops and returns None.
-    ///
-    /// # Examples
-    ///
-    /// With input:
-    /// ```text
-    /// # Comment line
-    ///
-    /// regexp=^ERROR
-    /// colours=bold red
-    ///
-    /// regexp=^WARN
-    /// ```
-    /// `next_alphanumeric()` will skip comments and blanks, returning:
-    /// 1. `"regexp=^ERROR"`
-    /// 2. `"colours=bold red"`
-    /// 3. `"regexp=^WARN"`
-    fn next_alphanumeric(&mut self) -> Option<String> {
-        // Pattern ^[a-zA-Z0-9] matches lines starting with a letter or digit
-        let alphanumeric = Regex::new("^[a-zA-Z0-9]").unwrap();
-        for line in (&mut self.inner).flatten() {
-            // Skip non-matching lines (comments, blanks)
-            if alphanumeric.is_match(&line) {
-                return Some(line.trim().to_string());
-            }
+    /// Startup then only pays for the rules that actually run. Entries with an invalid
+    /// regex are kept instead of skipped; the colorizer disables them with a warning
+    /// when they are first used.
+    pub fn lazy(inner: Lines<A>) -> Self {
+        GrcatConfigReader {
+            lazy: true,
+            ..Self::new(inner)
         }
-        None // No more alphanumeric lines (EOF)
     }
 
-    /// Fetch the next line if it's alphanumeric, or None to signal end of entry.
-    ///
-    /// This method is used during entry parsing to continue reading key=value pairs
-    /// that belong to the current configuration entry. As long as lines start with
-    /// alphanumeric characters, they belong to the same entry. When a non-alphanumeric
-    /// line is encountered (comment, blank line), it signals the end of the current
-    /// entry, and the method returns None.
-    ///
-    /// ## Returns
-    ///
-    /// - `Some(String)` - Next line if it starts with alphanumeric (still in this entry)
-    /// - `None` - End of entry or EOF (non-alphanumeric line or no more input)
-    ///
-    /// ## Implementation Details
-    ///
-    /// 1. Calls `self.inner.next()` to get the next line from the buffer
-    /// 2. If EOF, returns None (no next line available)
-    /// 3. If line starts with alphanumeric, returns it (still in entry)
-    /// 4. If line doesn't start with alphanumeric, returns None (end of entry)
-    ///
-    /// ## Entry Boundary Detection
-    ///
-    /// This method implements entry boundary detection:
-    /// - Alphanumeric start → still in current entry
-    /// - Non-alphanumeric start → end of entry (return None)
-    /// - Examples of entry-ending lines:
-    ///   - Blank line: ""
-    ///   - Comment: "# This is a comment"
-    ///   - Whitespace: "   "
-    ///   - Any line starting with non-alphanumeric: "---", "$", etc.
-    fn following(&mut self) -> Option<String> {
-        // Pattern ^[a-zA-Z0-9] matches lines starting with a letter or digit
-        let alphanumeric = Regex::new("^[a-zA-Z0-9]").unwrap();
-        if let Some(Ok(line)) = self.inner.next() {
-            // If line starts with alphanumeric, it's part of this entry
-            if alphanumeric.is_match(&line) {
-                Some(line)
-            } else {
-                // Non-alphanumeric line marks end of entry
-                None
-            }
-        } else {
-            // EOF reached
-            None
-        }
+    /// Name the config in diagnostics (e.g. its path)
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.source = Some(source.to_string());
+        self
+    }
+
+    /// Problems found since the last call, such as invalid regexes or styles. The
+    /// reader prints nothing itself; the rules affected are skipped or use defaults.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    /// Line of the `regexp=` of the last rule returned, e.g. to locate an invalid
+    /// pattern once a [`lazy`](GrcatConfigReader::lazy) reader's rule is compiled
+    pub fn regexp_line(&self) -> usize {
+        self.regexp_line
+    }
+
+    /// Record a problem on the last line read
+    fn warn(&mut self, kind: DiagnosticKind, message: impl std::fmt::Display) {
+        let diagnostic = Diagnostic::new(self.source.as_deref(), Some(self.line), kind, message);
+        self.diagnostics.push(diagnostic);
     }
 }
 
+/// Whether `line` starts with an ASCII letter or digit, like `^[a-zA-Z0-9]`
+fn starts_alphanumeric(line: &str) -> bool {
+    line.as_bytes()
+        .first()
+        .is_some_and(u8::is_ascii_alphanumeric)
+}
+
+/// Name of the config included by an `include=NAME` or `@include NAME` line
+fn include_target(line: &str) -> Option<&str> {
+    let line = line.trim();
+    let name = match line.strip_prefix("@include") {
+        Some(rest) if rest.starts_with(is_regex_space) => rest,
+        Some(_) => return None,
+        None => match split_key_value(line)? {
+            ("include", name) => name,
+            _ => return None,
+        },
+    };
+    Some(name.trim()).filter(|name| !name.is_empty())
+}
+
+/// Split a grcat `key=value` line like `^([a-z_]+)\s*=\s*(.*)$`
+fn split_key_value(line: &str) -> Option<(&str, &str)> {
+    let key_len = line
+        .bytes()
+        .take_while(|b| b.is_ascii_lowercase() || *b == b'_')
+        .count();
+    if key_len == 0 {
+        return None;
+    }
+    let (key, rest) = line.split_at(key_len);
+    let value = rest.trim_start_matches(is_regex_space).strip_prefix('=')?;
+    Some((key, value.trim_start_matches(is_regex_space)))
+}
+
+/// ASCII whitespace as matched by `\s`
+fn is_regex_space(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\x0b' | '\x0c' | '\r')
+}
+
 /// A single grcat configuration entry (regex + colors).
 ///
 /// This struct represents a complete colorization rule parsed from a grcat configuration file.
@@ -878,6 +1636,97 @@ pub enum GrcatConfigEntryCount {
     Stop,
 }
 
+impl GrcatConfigEntryCount {
+    /// The value of the `count` key
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GrcatConfigEntryCount::Once => "once",
+            GrcatConfigEntryCount::More => "more",
+            GrcatConfigEntryCount::Stop => "stop",
+        }
+    }
+}
+
+/// Where a rule from an overlay file goes (see [`crate::overlay`]).
+///
+/// Set with the `overlay` key; ignored outside overlay files. This rule takes the
+/// place of the bundled rule with `id=ttl`:
+/// ```text
+/// id=ttl
+/// regexp=ttl=(\d+)
+/// colours=default,bold yellow
+/// overlay=replace
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayMode {
+    /// Insert before the rules of the base config
+    Prepend,
+    /// Insert after the rules of the base config
+    Append,
+    /// Take the place of the base rule with the same `id`
+    Replace,
+}
+
+impl OverlayMode {
+    /// The value of the `overlay` key
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OverlayMode::Prepend => "prepend",
+            OverlayMode::Append => "append",
+            OverlayMode::Replace => "replace",
+        }
+    }
+}
+
+/// What a match of a rule means, set with the `severity` key.
+///
+/// ```text
+/// regexp=\bDUP!
+/// colours=bold red
+/// severity=warning
+/// tags=network,duplicate
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// A failure
+    Error,
+    /// Something worth a look
+    Warning,
+    /// Neutral information
+    Info,
+    /// A success
+    Ok,
+}
+
+impl Severity {
+    /// Parse the value of a `severity` key
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "error" => Some(Severity::Error),
+            "warning" => Some(Severity::Warning),
+            "info" => Some(Severity::Info),
+            "ok" => Some(Severity::Ok),
+            _ => None,
+        }
+    }
+
+    /// The value of the `severity` key
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+            Severity::Ok => "ok",
+        }
+    }
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GrcatConfigEntry {
     #[allow(dead_code)]
@@ -893,6 +1742,30 @@ pub struct GrcatConfigEntry {
     /// Optional replacement template used when `replace` is specified in the
     /// configuration. Placeholders like `\1` are substituted with capture groups.
     pub replace: String,
+    #[allow(dead_code)]
+    /// Name of the rule given with the `id` key, so overlays can refer to it.
+    pub id: Option<String>,
+    #[allow(dead_code)]
+    /// Placement of the rule when it comes from an overlay file (`overlay` key).
+    pub overlay: Option<OverlayMode>,
+    #[allow(dead_code)]
+    /// Labels given with the comma-separated `tags` key, in order and without duplicates.
+    pub tags: Vec<String>,
+    #[allow(dead_code)]
+    /// What a match means, given with the `severity` key.
+    pub severity: Option<Severity>,
+}
+
+/// An empty pattern with no styles and the defaults of [`GrcatConfigEntry::new`],
+/// as a base for struct update syntax:
+/// `GrcatConfigEntry { regex, colors, ..Default::default() }`.
+impl Default for GrcatConfigEntry {
+    fn default() -> Self {
+        GrcatConfigEntry::new(
+            CompiledRegex::new("").expect("the empty pattern compiles"),
+            Vec::new(),
+        )
+    }
 }
 
 impl GrcatConfigEntry {
@@ -905,7 +1778,7 @@ impl GrcatConfigEntry {
     ///
     /// # Returns
     ///
-    /// A new GrcatConfigEntry with count set to GrcatConfigEntryCount::More, replace set to empty string, and skip set to false
+    /// A new GrcatConfigEntry with count set to GrcatConfigEntryCount::More, replace set to empty string, skip set to false and no id, overlay mode, tags or severity
     ///
     /// # Examples
     ///
@@ -929,14 +1802,90 @@ impl GrcatConfigEntry {
             skip: false,
             count: GrcatConfigEntryCount::More,
             replace: String::new(),
+            id: None,
+            overlay: None,
+            tags: Vec::new(),
+            severity: None,
         }
     }
+
+    /// The rule as named in messages: `#N` (1-based position), followed by its id,
+    /// severity and tags when it has them.
+    #[allow(dead_code)]
+    pub fn label(&self, idx: usize) -> String {
+        let mut label = format!("#{}", idx + 1);
+        if let Some(id) = &self.id {
+            label.push_str(&format!(" id={}", id));
+        }
+        if let Some(severity) = self.severity {
+            label.push_str(&format!(" severity={}", severity.as_str()));
+        }
+        if !self.tags.is_empty() {
+            label.push_str(&format!(" tags={}", self.tags.join(",")));
+        }
+        label
+    }
 }
 
-impl<A: BufRead> Iterator for GrcatConfigReader<A> {
-    type Item = GrcatConfigEntry;
+/// The rule as grcat config lines, each ending with `\n`: `id=`, `regexp=`, then
+/// the keys that differ from their defaults, in a fixed order. Reading them back
+/// gives the same rule, provided no value contains a newline or starts with
+/// whitespace (the reader never produces such values). The pattern is written as
+/// read, with `{NAME}` macros already expanded.
+impl std::fmt::Display for GrcatConfigEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines: Vec<(&str, Cow<'_, str>)> = Vec::new();
+        if let Some(id) = &self.id {
+            lines.push(("id", id.into()));
+        }
+        lines.push(("regexp", self.regex.source().into()));
+        if !self.colors.is_empty() {
+            let keywords: Vec<String> = self.colors.iter().map(|style| style.keywords()).collect();
+            lines.push(("colours", keywords.join(",").into()));
+        }
+        if self.count != GrcatConfigEntryCount::More {
+            lines.push(("count", self.count.as_str().into()));
+        }
+        if self.skip {
+            lines.push(("skip", "true".into()));
+        }
+        if !self.replace.is_empty() {
+            lines.push(("replace", self.replace.as_str().into()));
+        }
+        if let Some(overlay) = self.overlay {
+            lines.push(("overlay", overlay.as_str().into()));
+        }
+        if !self.tags.is_empty() {
+            lines.push(("tags", self.tags.join(",").into()));
+        }
+        if let Some(severity) = self.severity {
+            lines.push(("severity", severity.as_str().into()));
+        }
+        // The reader trims the first line of a rule, so trailing whitespace there
+        // is kept by putting the count first
+        if lines[0].1.ends_with(char::is_whitespace) {
+            lines.retain(|(key, _)| *key != "count");
+            lines.insert(0, ("count", self.count.as_str().into()));
+        }
+        for (key, value) in lines {
+            writeln!(f, "{}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
 
-    /// Parse and return the next GrcatConfigEntry from the grcat config file.
+/// `include=NAME` for an include, the rule's lines otherwise
+impl std::fmt::Display for GrcatConfigItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GrcatConfigItem::Rule(rule) => write!(f, "{}", rule),
+            GrcatConfigItem::Include { name, .. } => writeln!(f, "include={}", name),
+        }
+    }
+}
+
+impl<A: BufRead> GrcatConfigReader<A> {
+    /// Parse and return the next rule or include directive from the grcat config file.
     ///
     /// This method implements the core parsing logic for grcat configuration files.
     /// It processes configuration entries consisting of key=value pairs until a
@@ -948,14 +1897,18 @@ impl<A: BufRead> Iterator for GrcatConfigReader<A> {
     /// 2. **Parse key=value pairs**: Loop through consecutive alphanumeric lines
     /// 3. **Extract keys**: Parse "regexp=..." and "colours=..." assignments
     /// 4. **Validate regex**: Compile the regexp string; skip entry if invalid
+    ///    (a [`lazy`](GrcatConfigReader::lazy) reader compiles on first use instead)
     /// 5. **Parse styles**: Convert colour specification to Style vector
     /// 6. **Check boundaries**: Use `following()` to detect end of entry
     /// 7. **Return or skip**: Yield entry if valid regex found, otherwise skip
     ///
+    /// An `include=NAME` or `@include NAME` line forms an entry of its own and is
+    /// returned as [`GrcatConfigItem::Include`]; it also ends a preceding entry.
+    ///
     /// ## Key=Value Format
     ///
-    /// The regex pattern matches `key = value` format:
-    /// - Pattern: `^([a-z_]+)\s*=\s*(.*)$`
+    /// Lines are split in `key = value` format:
+    /// - Pattern: `^([a-z_]+)\s*=\s*(.*)$` (matched without a regex)
     /// - Supports spaces around the '=' sign
     /// - Keys are lowercase with underscores
     /// - Examples: `regexp=pattern`, `colours = style1, style2`
@@ -968,6 +1921,10 @@ impl<A: BufRead> Iterator for GrcatConfigReader<A> {
     /// **Optional:**
     /// - `colours=` line with comma-separated style keywords
     /// - If omitted, colors default to empty vector (no styling applied)
+    /// - `count=`, `skip=`, `replace=`
+    /// - `id=` naming the rule and `overlay=` placing it (see [`OverlayMode`])
+    /// - `tags=` (comma-separated labels) and `severity=` (see [`Severity`])
+    /// - `define NAME=regex` lines, for `{NAME}` in later regexps (see [`crate::macros`])
     ///
     /// **Ignored:**
     /// - Any other keys are silently ignored
@@ -976,10 +1933,14 @@ impl<A: BufRead> Iterator for GrcatConfigReader<A> {
     /// ## Error Handling
     ///
     /// - **Invalid regex**: Entry is skipped, iteration continues to next entry
-    /// - **Invalid colors**: Log via debug_println, colors default to empty
+    /// - **Invalid colors**: Colors default to empty
+    /// - **Unknown values**: `count`, `overlay` and `severity` are left unset, `skip` is false
     /// - **Missing regexp**: Entry is skipped
     /// - **Incomplete entry**: Iteration stops (None)
     ///
+    /// Each problem is recorded with its line number rather than printed; see
+    /// [`take_diagnostics`](GrcatConfigReader::take_diagnostics).
+    ///
     /// # Examples
     ///
     /// File content:
@@ -994,116 +1955,246 @@ impl<A: BufRead> Iterator for GrcatConfigReader<A> {
     /// Yields two GrcatConfigEntry items:
     /// 1. regex matches ERROR/WARN with capture group for timing
     /// 2. regex matches OK status line
-    fn next(&mut self) -> Option<Self::Item> {
-        // Regex pattern to parse key=value lines
-        // Pattern: ^([a-z_]+)\s*=\s*(.*)$
-        // - ^([a-z_]+)  : Key is one or more lowercase letters/underscores
-        // - \s*=\s*     : Optional whitespace around equals sign
-        // - (.*)$       : Value is everything to end of line
-        // Examples matched:
-        // - "regexp=^ERROR"
-        // - "colours = bold red, yellow"
-        // - "key = value with spaces"
-        let re = Regex::new("^([a-z_]+)\\s*=\\s*(.*)$").unwrap();
-        let mut ln: String;
-
-        while let Some(line) = self.next_alphanumeric() {
-            ln = line;
-            let mut regex: Option<CompiledRegex> = None;
-            let mut colors: Option<Vec<Style>> = None;
-            let mut skip: Option<bool> = None;
-            let mut count: Option<GrcatConfigEntryCount> = None;
-            let mut replace: Option<String> = None;
-
-            // Loop over all consecutive alphanumeric lines belonging to this entry
-            // until we hit a non-alphanumeric line (entry boundary)
-            loop {
-                // Parse the key=value pair from current line
-                let cap = re.captures(&ln).unwrap();
-                let key = cap.get(1).unwrap().as_str();
-                let value = cap.get(2).unwrap().as_str();
-
-                // Process known keys, ignore unknown ones
-                match key {
-                    "regexp" => {
+    pub fn next_item(&mut self) -> Option<GrcatConfigItem> {
+        while let Some(node) = self.parser.next() {
+            match node {
+                ConfigNode::Include(line) => {
+                    let name = line.include_name().unwrap_or_default();
+                    return Some(GrcatConfigItem::Include {
+                        name: name.to_string(),
+                        line: line.number,
+                    });
+                }
+                ConfigNode::Rule(lines) => {
+                    if let Some(rule) = self.rule(&lines) {
+                        return Some(GrcatConfigItem::Rule(rule));
+                    }
+                    // This entry lacked a valid regex; skip and try next entry
+                }
+                ConfigNode::Trivia(_) => {}
+            }
+        }
+        None // No more entries (EOF)
+    }
+
+    /// Build the rule of one [`ConfigNode::Rule`], or `None` without a valid regexp
+    fn rule(&mut self, lines: &[ConfigLine]) -> Option<GrcatConfigEntry> {
+        let mut regex: Option<CompiledRegex> = None;
+        let mut colors: Option<Vec<Style>> = None;
+        let mut skip: Option<bool> = None;
+        let mut count: Option<GrcatConfigEntryCount> = None;
+        let mut replace: Option<String> = None;
+        let mut id: Option<String> = None;
+        let mut overlay: Option<OverlayMode> = None;
+        let mut tags: Vec<String> = Vec::new();
+        let mut severity: Option<Severity> = None;
+        let mut invalid_macro = false;
+        let mut regexp_line = lines[0].number;
+
+        for line in lines {
+            self.line = line.number;
+            // `define NAME=regex` applies to the regexps that follow it
+            if let Some(statement) = line.define_statement() {
+                if let Some(Err(e)) = self.macros.to_mut().define_line(statement) {
+                    self.warn(DiagnosticKind::Macro, e);
+                }
+                continue;
+            }
+
+            // Lines other than key=value are ignored
+            let (key, value) = line.key_value().unwrap_or_default();
+
+            // Process known keys, ignore unknown ones
+            match key {
+                "regexp" => {
+                    // Expand {NAME} macros; the rule is skipped if one is undefined
+                    let value = match self.macros.expand(value) {
+                        Ok(value) => value,
+                        Err(e) => {
+                            self.warn(DiagnosticKind::Macro, e);
+                            invalid_macro = true;
+                            value.to_string()
+                        }
+                    };
+                    let value = value.as_str();
+                    regexp_line = self.line;
+                    if std::env::var_os("RGRC_DEBUG").is_some() {
+                        for rewrite in translate(value).rewrites {
+                            eprintln!("rgrc: regexp {}: {}", value, rewrite);
+                        }
+                    }
+                    if self.lazy {
+                        regex = Some(CompiledRegex::lazy(value));
+                    } else {
                         // Attempt to compile the regex pattern using hybrid engine
                         // This automatically selects fast standard regex or fancy-regex
                         match CompiledRegex::new(value) {
                             Ok(re) => {
-                                regex = Some(re);
+                                // Lower anchoring lookarounds onto the fast path
+                                regex = Some(re.optimized());
                             }
-                            Err(_exc) => {
-                                // Log error and skip this entry (regex is required)
-                                eprintln!("Failed regexp: {:?}", _exc);
-                            }
-                        }
-                    }
-                    // Accept both British and American spelling, singular/plural
-                    "colours" | "colors" | "colour" => {
-                        // Parse comma-separated style keywords into Style vector
-                        // Example: "bold red,yellow,cyan" → [Style::new().bold().red(), Style::new().yellow(), Style::new().cyan()]
-                        match styles_from_str(value) {
-                            Ok(styles) => colors = Some(styles),
                             Err(e) => {
-                                eprintln!("Error: Invalid style in configuration: {}", e);
-                                eprintln!("Skipping this rule due to style error.");
-                                break;
+                                // Record the error and skip this entry (regex is required)
+                                self.warn(DiagnosticKind::Regex, e);
                             }
                         }
                     }
-                    "count" => {
-                        // Parse count value: once/more/stop
-                        count = match value {
-                            "once" => Some(GrcatConfigEntryCount::Once),
-                            "more" => Some(GrcatConfigEntryCount::More),
-                            "stop" => Some(GrcatConfigEntryCount::Stop),
-                            _ => {
-                                eprintln!("Unknown count value: {}", value);
-                                None
-                            }
-                        };
-                    }
-                    "replace" => {
-                        // Store replace string
-                        replace = Some(value.to_string());
+                }
+                // Accept both British and American spelling, singular/plural
+                "colours" | "colors" | "colour" => {
+                    // Parse comma-separated style keywords into Style vector
+                    // Example: "bold red,yellow,cyan" → [Style::new().bold().red(), Style::new().yellow(), Style::new().cyan()]
+                    match styles_from_str(value) {
+                        Ok(styles) => colors = Some(styles),
+                        Err(e) => {
+                            self.warn(
+                                DiagnosticKind::Style,
+                                format!("{}; rule left uncoloured", e),
+                            );
+                            colors = None;
+                        }
                     }
-                    "skip" => {
-                        // Parse skip value: true/false
-                        skip = match value.to_lowercase().as_str() {
-                            "true" | "1" | "yes" => Some(true),
-                            "false" | "0" | "no" => Some(false),
-                            _ => {
-                                eprintln!("Unknown skip value: {}, defaulting to false", value);
-                                Some(false)
-                            }
-                        };
+                }
+                "count" => {
+                    // Parse count value: once/more/stop
+                    count = match value {
+                        "once" => Some(GrcatConfigEntryCount::Once),
+                        "more" => Some(GrcatConfigEntryCount::More),
+                        "stop" => Some(GrcatConfigEntryCount::Stop),
+                        _ => {
+                            self.warn(
+                                DiagnosticKind::Value,
+                                format!("unknown count value: {}", value),
+                            );
+                            None
+                        }
+                    };
+                }
+                "replace" => {
+                    // Store replace string
+                    replace = Some(value.to_string());
+                }
+                "skip" => {
+                    // Parse skip value: true/false
+                    skip = match value.to_lowercase().as_str() {
+                        "true" | "1" | "yes" => Some(true),
+                        "false" | "0" | "no" => Some(false),
+                        _ => {
+                            self.warn(
+                                DiagnosticKind::Value,
+                                format!("unknown skip value: {}, defaulting to false", value),
+                            );
+                            Some(false)
+                        }
+                    };
+                }
+                "id" => {
+                    // Name the rule so overlays can replace or move it
+                    id = (!value.is_empty()).then(|| value.to_string());
+                }
+                "overlay" => {
+                    // Parse overlay placement: prepend/append/replace
+                    overlay = match value {
+                        "prepend" => Some(OverlayMode::Prepend),
+                        "append" => Some(OverlayMode::Append),
+                        "replace" => Some(OverlayMode::Replace),
+                        _ => {
+                            self.warn(
+                                DiagnosticKind::Value,
+                                format!("unknown overlay value: {}", value),
+                            );
+                            None
+                        }
+                    };
+                }
+                "tags" => {
+                    // Comma-separated labels; repeated tags= lines add to the list
+                    for tag in value.split(',').map(str::trim) {
+                        if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+                            tags.push(tag.to_string());
+                        }
                     }
-                    _ => {
-                        // Ignore unknown keys - grcat may add new keys in future versions
+                }
+                "severity" => {
+                    // Parse severity: error/warning/info/ok
+                    severity = Severity::from_name(value);
+                    if severity.is_none() {
+                        self.warn(
+                            DiagnosticKind::Value,
+                            format!("unknown severity value: {}", value),
+                        );
                     }
-                };
-
-                // Attempt to fetch the next line in this entry
-                if let Some(nline) = self.following() {
-                    ln = nline; // Continue with next line of this entry
-                } else {
-                    // Non-alphanumeric line encountered - end of entry
-                    break;
                 }
-            }
+                _ => {
+                    // Ignore unknown keys - grcat may add new keys in future versions
+                }
+            };
+        }
 
-            // Only emit entry if we successfully parsed a regex (required)
-            if let Some(regex) = regex {
-                return Some(GrcatConfigEntry {
-                    regex,
-                    colors: colors.unwrap_or_default(), // Empty color list if not specified
-                    skip: skip.unwrap_or(false),        // Default to false if not specified
-                    count: count.unwrap_or(GrcatConfigEntryCount::More), // Default to More if not specified
-                    replace: replace.unwrap_or_default(), // Empty string if not specified
-                });
+        // Only emit entry if we successfully parsed a regex (required)
+        let regex = regex.filter(|_| !invalid_macro)?;
+        self.regexp_line = regexp_line;
+        Some(GrcatConfigEntry {
+            regex,
+            colors: colors.unwrap_or_default(), // Empty color list if not specified
+            skip: skip.unwrap_or(false),        // Default to false if not specified
+            count: count.unwrap_or(GrcatConfigEntryCount::More), // Default to More if not specified
+            replace: replace.unwrap_or_default(), // Empty string if not specified
+            id,
+            overlay,
+            tags,
+            severity,
+        })
+    }
+}
+
+impl<A: BufRead> Iterator for GrcatConfigReader<A> {
+    type Item = GrcatConfigEntry;
+
+    /// Parse and return the next GrcatConfigEntry, skipping include directives
+    /// (see [`next_item`](GrcatConfigReader::next_item)).
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let GrcatConfigItem::Rule(rule) = self.next_item()? {
+                return Some(rule);
             }
-            // This entry lacked a valid regex; skip and try next entry
         }
-        None // No more entries (EOF)
     }
 }
+
+/// Write `items` as a grcat config, with a `-` line between them. Reading the text
+/// back with [`GrcatConfigReader::next_item`] gives the same items (see the
+/// [`Display`](std::fmt::Display) of [`GrcatConfigEntry`]).
+///
+/// ```ignore
+/// let rules = rgrc::load_grcat_config("share/conf.ping");
+/// let items: Vec<_> = rules.into_iter().map(GrcatConfigItem::Rule).collect();
+/// write_grcat_config(&mut std::io::stdout(), &items)?;
+/// ```
+#[allow(dead_code)]
+pub fn write_grcat_config<W: Write>(out: &mut W, items: &[GrcatConfigItem]) -> io::Result<()> {
+    for (idx, item) in items.iter().enumerate() {
+        if idx > 0 {
+            out.write_all(b"-\n")?;
+        }
+        write!(out, "{}", item)?;
+    }
+    Ok(())
+}
+
+/// Write command mappings, as read by [`GrcConfigReader`], as a grc.conf: the
+/// command pattern and the config name on two lines, then a blank line. Patterns
+/// are written as read; one starting with `#` or whitespace would not read back.
+#[allow(dead_code)]
+pub fn write_grc_config<W: Write>(
+    out: &mut W,
+    mappings: &[(CompiledRegex, String)],
+) -> io::Result<()> {
+    for (idx, (regex, config)) in mappings.iter().enumerate() {
+        if idx > 0 {
+            out.write_all(b"\n")?;
+        }
+        writeln!(out, "{}\n{}", regex.source(), config)?;
+    }
+    Ok(())
+}
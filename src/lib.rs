@@ -13,11 +13,21 @@
 //!   - `style`: Lightweight ANSI styling (replaces console crate)
 //!   - `colorizer`: Text colorization engine
 //!   - `grc`: Config file parsing with hybrid regex engine
-//!   - `enhanced_regex`: Custom lookaround implementation (used when fancy feature is disabled)
+//!   - `enhanced_regex`: Custom lookaround implementation (default fallback when fancy feature is disabled)
+//!   - `translate`: Python `re` → regex-lite translation applied to every pattern
+//!   - `lowering`: Rewrites anchoring lookarounds into groups to keep rules on the fast path
+//!   - `macros`: Expands `{NAME}` pattern macros (`define NAME=regex`, rgrc.defines)
+//!   - `parallel`: Colorizes large non-interactive input in batches on a thread pool
+//!   - `prefilter`: Skips rules whose required literals do not occur in a line
+//!   - `command_index`: Maps literal command names in grc.conf to conf files without regexes
+//!   - `bundle`: Decodes the embedded configs, which build.rs compresses into one bundle
+//!   - `rule_cache`: Keeps the parsed rules of each conf file under `~/.cache/rgrc/rules`
+//!   - `overlay`: Merges user rules from `conf.X.d/*.conf` and `conf.X.local` into conf.X
+//!   - `diagnostic`: Problems found while loading configs, returned by the `try_load_*` functions
 //!
 //! ## Features
 //!
-//! - **embed-configs** (default): Embed configuration files into binary
+//! - **embed-configs** (default): Embed configuration files into binary, compressed by build.rs
 //! - **fancy-regex** (default): Use battle-tested fancy-regex for enhanced patterns
 //!   - Disable for smaller binary: `cargo build --no-default-features --features=embed-configs`
 //! - **timetrace**: Enable timing trace for performance profiling
@@ -28,6 +38,8 @@
 //! - Simple patterns → Standard `regex` crate (fast)
 //! - Complex patterns → `fancy-regex` (default) or `EnhancedRegex` (lightweight)
 //!
+//! The fallback can be chosen at runtime with `--regex-engine=auto|fast|enhanced|fancy`.
+//!
 //! See `grc::CompiledRegex` documentation for details.
 //!
 //! ## Usage Example
@@ -45,19 +57,33 @@
 pub mod style;
 // Re-export Style for easier access
 pub use style::Style;
+// Re-export the load diagnostics next to the functions that return them
+pub use diagnostic::{Diagnostic, DiagnosticKind, LoadError};
 
 pub mod args;
 pub mod buffer;
+pub mod bundle;
 pub mod colorizer;
+pub mod command_index;
+pub mod diagnostic;
 pub mod enhanced_regex;
 pub mod grc;
+pub mod lowering;
+pub mod macros;
+pub mod overlay;
+pub mod parallel;
+pub mod prefilter;
+pub mod rule_cache;
+pub mod translate;
 pub mod utils;
 
 use std::fs::File;
 use std::io::BufRead;
 use std::str::FromStr;
 
-use grc::{GrcConfigReader, GrcatConfigEntry, GrcatConfigReader};
+use command_index::CommandIndex;
+use grc::{GrcConfigReader, GrcatConfigEntry, GrcatConfigItem};
+use macros::Macros;
 
 // Simple tilde expansion function to replace shellexpand
 fn expand_tilde(path: &str) -> String {
@@ -75,6 +101,7 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 // Use generated `embedded_configs.rs` (created by build.rs) so the list of
 // embedded files is derived from the `share` directory instead of being hard-coded.
+// It defines the compressed `EMBEDDED_BUNDLE`, its `EMBEDDED_INDEX` and `EMBEDDED_CONFIG_NAMES`.
 #[cfg(feature = "embed-configs")]
 include!(concat!(env!("OUT_DIR"), "/embedded_configs.rs"));
 
@@ -86,9 +113,10 @@ pub const EMBEDDED_GRC_CONF: &str = include_str!("../etc/rgrc.conf");
 
 /// Flush and rebuild the cache directory (embed-configs only)
 ///
-/// This function removes the existing cache directory and rebuilds it with
-/// embedded configuration files. Returns the path to the rebuilt cache directory
-/// and the number of configuration files created.
+/// Embedded configs are parsed straight from memory, so rgrc never reads the cache
+/// directory itself. This exports them to `~/.cache/rgrc/<version>` as editable
+/// files, replacing a previous export. Returns the path to the rebuilt cache
+/// directory and the number of configuration files created.
 ///
 /// # Returns
 ///
@@ -104,19 +132,8 @@ pub fn flush_and_rebuild_cache() -> Option<(std::path::PathBuf, usize)> {
     }
 
     // Rebuild cache
-    let new_cache_dir = ensure_cache_populated()?;
-
-    // Count the number of config files
-    let conf_dir = new_cache_dir.join("conf");
-    let config_count = if conf_dir.exists() {
-        std::fs::read_dir(&conf_dir)
-            .map(|entries| entries.count())
-            .unwrap_or(0)
-    } else {
-        0
-    };
-
-    Some((new_cache_dir, config_count))
+    let config_count = export_embedded_configs(&cache_dir)?;
+    Some((cache_dir, config_count))
 }
 
 // Helper function to get cache directory path
@@ -128,45 +145,59 @@ fn get_cache_dir() -> Option<std::path::PathBuf> {
         .map(|h| h.join(".cache").join("rgrc").join(VERSION))
 }
 
-// Ensure cache directory exists and populate it with embedded configs
+// Write rgrc.conf and conf/* from the embedded configs, returning the number of
+// conf files written
 #[cfg(feature = "embed-configs")]
-fn ensure_cache_populated() -> Option<std::path::PathBuf> {
-    let cache_dir = get_cache_dir()?;
-
-    // Check if cache directory exists and appears populated (rgrc.conf + at least one conf file)
-    let grc_conf_path = cache_dir.join("rgrc.conf");
-    let conf_dir = cache_dir.join("conf");
-    if grc_conf_path.exists() {
-        // If conf directory exists and contains at least one file, we assume cache is populated
-        if conf_dir.exists()
-            && let Ok(mut entries) = std::fs::read_dir(&conf_dir)
-            && entries.next().is_some()
-        {
-            return Some(cache_dir);
-        }
-        // rgrc.conf exists but conf dir missing or empty — fall through and repopulate
-    }
-
-    // Create cache directory structure
-    std::fs::create_dir_all(&cache_dir).ok()?;
+fn export_embedded_configs(cache_dir: &std::path::Path) -> Option<usize> {
     let conf_dir = cache_dir.join("conf");
     std::fs::create_dir_all(&conf_dir).ok()?;
 
     // Write rgrc.conf
-    std::fs::write(&grc_conf_path, EMBEDDED_GRC_CONF).ok()?;
+    std::fs::write(cache_dir.join("rgrc.conf"), EMBEDDED_GRC_CONF).ok()?;
 
     // Write all embedded configs
-    // Don't fail the entire cache population if a single file fails to write
-    let mut any_success = false;
-    for (filename, content) in EMBEDDED_CONFIGS {
-        let file_path = conf_dir.join(filename);
-        if std::fs::write(file_path, content).is_ok() {
-            any_success = true;
-        }
-    }
+    // Don't fail the entire export if a single file fails to write
+    let written = embedded_configs()
+        .filter(|(filename, content)| std::fs::write(conf_dir.join(filename), content).is_ok())
+        .count();
+
+    // Only succeed if we wrote at least one config file
+    (written > 0).then_some(written)
+}
+
+/// Contents of the embedded config file `name` (e.g. `conf.ping`), if any.
+///
+/// Only this config is decompressed from the bundle.
+#[cfg(feature = "embed-configs")]
+pub fn embedded_config(name: &str) -> Option<String> {
+    let idx = EMBEDDED_INDEX
+        .binary_search_by(|(filename, ..)| (*filename).cmp(name))
+        .ok()?;
+    let (_, offset, compressed_len, len) = EMBEDDED_INDEX[idx];
+    let compressed = EMBEDDED_BUNDLE.get(offset..offset + compressed_len)?;
+    String::from_utf8(bundle::decompress(compressed, len)?).ok()
+}
+
+/// All embedded config files as `(filename, contents)`, sorted by filename.
+#[cfg(feature = "embed-configs")]
+pub fn embedded_configs() -> impl Iterator<Item = (&'static str, String)> {
+    EMBEDDED_CONFIG_NAMES
+        .iter()
+        .filter_map(|name| Some((*name, embedded_config(name)?)))
+}
 
-    // Only return Some if we successfully wrote at least one config file
-    if any_success { Some(cache_dir) } else { None }
+/// Parse the rules of an embedded config file (see [`embedded_config`]).
+#[cfg(feature = "embed-configs")]
+fn parse_embedded_config(
+    name: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<Vec<GrcatConfigEntry>> {
+    let content = embedded_config(name)?;
+    Some(load_rules(
+        &format!("embedded/{}", name),
+        content.as_bytes(),
+        diagnostics,
+    ))
 }
 
 /// Control whether colored output should be enabled for this run.
@@ -273,6 +304,9 @@ impl FromStr for ColorMode {
 /// - If `~/.config/rgrc/conf.df` exists → **RETURNED** (other paths not searched)
 /// - If only `/usr/share/rgrc/conf.df` exists → returned as fallback
 ///
+/// Either way, overlays from `~/.config/rgrc/conf.df.d/*.conf` and
+/// `~/.config/rgrc/conf.df.local` are merged into the rules (see [`overlay`]).
+///
 /// # Examples
 ///
 /// All paths in RESOURCE_PATHS are searched in order when loading configuration:
@@ -331,11 +365,14 @@ pub const RESOURCE_PATHS: &[&str] = &[
 ///
 /// # Errors Handled
 ///
-/// All errors are silently handled and result in empty or partial rule sets:
-/// - File not found → returns empty vector
-/// - Invalid regex → pattern not matched → returns empty vector
+/// Problems never stop loading, and result in empty or partial rule sets:
+/// - grc.conf not readable → returns empty vector
+/// - Invalid regex or style in a rule → the rule is left out
 /// - Invalid colorization file path → skipped to next directory
 ///
+/// They are reported on stderr with their file and line; use [`try_load_config`]
+/// to handle them instead.
+///
 /// # Examples
 ///
 /// ```ignore
@@ -358,56 +395,177 @@ pub const RESOURCE_PATHS: &[&str] = &[
 /// 3. Extracts the colorization file reference from matching entry
 /// 4. Expands ~ in paths using shellexpand
 /// 5. Searches RESOURCE_PATHS directories **in order** for the colorization file
-/// 6. Returns rules from the **first matching file found**
+/// 6. Returns rules from the **first matching file found**, merged with the user's
+///    overlays (`~/.config/rgrc/conf.X.d/*.conf`, `conf.X.local`; see [`overlay`])
 pub fn load_config(path: &str, pseudo_command: &str) -> Vec<GrcatConfigEntry> {
-    // First, try to load from filesystem config file
-    let filesystem_result = File::open(path).ok().and_then(|f| {
-        let bufreader = std::io::BufReader::new(f);
-        let configreader = GrcConfigReader::new(bufreader.lines());
-        // Iterate each rule so we can optionally log which pattern matched
-        for (re, config) in configreader {
-            if re.is_match(pseudo_command) {
+    try_load_config(path, pseudo_command).unwrap_or_else(LoadError::reported)
+}
+
+/// Like [`load_config`], but returns the problems found (including an unreadable
+/// `path` and errors in `rgrc.defines`) instead of printing them. The rules that
+/// could be loaded are in the [`LoadError`].
+pub fn try_load_config(
+    path: &str,
+    pseudo_command: &str,
+) -> Result<Vec<GrcatConfigEntry>, LoadError> {
+    let mut diagnostics = Macros::global_diagnostics().to_vec();
+    let rules = load_config_into(path, pseudo_command, &mut diagnostics).unwrap_or_else(|e| {
+        diagnostics.push(Diagnostic::new(Some(path), None, DiagnosticKind::Io, e));
+        Vec::new()
+    });
+    LoadError::check(rules, diagnostics)
+}
+
+/// [`load_config`], collecting the problems in `diagnostics`; fails only when
+/// `path` cannot be opened.
+fn load_config_into(
+    path: &str,
+    pseudo_command: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> std::io::Result<Vec<GrcatConfigEntry>> {
+    let bufreader = std::io::BufReader::new(File::open(path)?);
+    let index = CommandIndex::new(GrcConfigReader::new(bufreader.lines()));
+    let Some((pattern, config)) = index.lookup(pseudo_command) else {
+        return Ok(Vec::new());
+    };
+    if std::env::var_os("RGRC_DEBUG").is_some() {
+        eprintln!(
+            "rgrc: matched pattern '{}' in {} for '{}'",
+            pattern, path, pseudo_command
+        );
+    }
+
+    // Search RESOURCE_PATHS for the colorization file - **stop at first match**
+    for base_path in RESOURCE_PATHS {
+        let expanded_path = expand_tilde(base_path);
+        let config_path = format!("{}/{}", expanded_path, config);
+        if std::env::var_os("RGRC_DEBUG").is_some() {
+            eprintln!("rgrc: checking for config file {}", config_path);
+        }
+        // Use file_exists_and_parse to distinguish "file exists but empty" from "file not found"
+        match file_exists_and_parse(&config_path, diagnostics) {
+            Some(rules) => {
                 if std::env::var_os("RGRC_DEBUG").is_some() {
                     eprintln!(
-                        "rgrc: matched pattern '{}' in {} for '{}'",
-                        re.as_str(),
-                        path,
-                        pseudo_command
+                        "rgrc: found config file {} ({} rules)",
+                        config_path,
+                        rules.len()
                     );
                 }
-                return Some(config);
+                // File found (even if empty) - STOP
+                return Ok(with_overlays(rules, config, diagnostics));
             }
+            None => continue, // File not found - keep searching
         }
-        None
-    });
+    }
 
-    if let Some(config) = filesystem_result {
-        // Search RESOURCE_PATHS for the colorization file - **stop at first match**
-        for base_path in RESOURCE_PATHS {
-            let expanded_path = expand_tilde(base_path);
-            let config_path = format!("{}/{}", expanded_path, config);
-            if std::env::var_os("RGRC_DEBUG").is_some() {
-                eprintln!("rgrc: checking for config file {}", config_path);
+    // No configuration found
+    Ok(Vec::new())
+}
+
+/// Directory holding the user's overlay files (see [`overlay`])
+const OVERLAY_DIR: &str = "~/.config/rgrc";
+
+/// Merge the user's overlays for the conf file `config` into its rules.
+fn with_overlays(
+    rules: Vec<GrcatConfigEntry>,
+    config: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<GrcatConfigEntry> {
+    let dir = expand_tilde(OVERLAY_DIR);
+    let dir = std::path::Path::new(&dir);
+    overlay::apply_overlays(rules, dir, config, load_rules, diagnostics)
+}
+
+/// Rules of the conf file `source` with the given content, with its `include=`
+/// directives replaced by the rules of the included configs.
+fn load_rules(
+    source: &str,
+    content: &[u8],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<GrcatConfigEntry> {
+    let name = std::path::Path::new(source)
+        .file_name()
+        .map_or(source.into(), |name| name.to_string_lossy());
+    let mut stack = vec![name.into_owned()];
+    let (items, found) = rule_cache::load_items(source, content);
+    diagnostics.extend(found);
+    expand_includes(items, &mut stack, diagnostics)
+}
+
+/// Splice the rules of each included config in place of its include, following
+/// nested includes. `stack` holds the configs being expanded, outermost first; an
+/// include of one of them is a cycle and is skipped.
+fn expand_includes(
+    items: Vec<GrcatConfigItem>,
+    stack: &mut Vec<String>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<GrcatConfigEntry> {
+    let mut rules = Vec::new();
+    for item in items {
+        let (name, line) = match item {
+            GrcatConfigItem::Rule(rule) => {
+                rules.push(rule);
+                continue;
             }
-            // Use file_exists_and_parse to distinguish "file exists but empty" from "file not found"
-            match file_exists_and_parse(&config_path) {
-                Some(rules) => {
-                    if std::env::var_os("RGRC_DEBUG").is_some() {
-                        eprintln!(
-                            "rgrc: found config file {} ({} rules)",
-                            config_path,
-                            rules.len()
-                        );
-                    }
-                    return rules; // File found (even if empty) - STOP
-                }
-                None => continue, // File not found - keep searching
+            GrcatConfigItem::Include { name, line } => (name, line),
+        };
+        let including = stack.last().map(String::as_str);
+        if stack.contains(&name) {
+            let message = format!("include cycle: {} -> {}", stack.join(" -> "), name);
+            diagnostics.push(Diagnostic::new(
+                including,
+                Some(line),
+                DiagnosticKind::Include,
+                message,
+            ));
+            continue;
+        }
+        let Some(included) = find_included_config(&name, diagnostics) else {
+            let message = format!("included config {} not found", name);
+            diagnostics.push(Diagnostic::new(
+                including,
+                Some(line),
+                DiagnosticKind::Include,
+                message,
+            ));
+            continue;
+        };
+        stack.push(name);
+        rules.extend(expand_includes(included, stack, diagnostics));
+        stack.pop();
+    }
+    rules
+}
+
+/// Items of an included config, found like the conf files named in rgrc.conf:
+/// the first of RESOURCE_PATHS that has it, then the embedded configs.
+fn find_included_config(
+    name: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<Vec<GrcatConfigItem>> {
+    let mut load = |source: &str, content: &[u8]| {
+        let (items, found) = rule_cache::load_items(source, content);
+        diagnostics.extend(found);
+        items
+    };
+
+    for base_path in RESOURCE_PATHS {
+        let path = format!("{}/{}", expand_tilde(base_path), name);
+        if let Ok(content) = std::fs::read(&path) {
+            if std::env::var_os("RGRC_DEBUG").is_some() {
+                eprintln!("rgrc: including {}", path);
             }
+            return Some(load(&path, &content));
         }
     }
 
-    // No configuration found
-    Vec::new()
+    #[cfg(feature = "embed-configs")]
+    if let Some(content) = embedded_config(name) {
+        return Some(load(&format!("embedded/{}", name), content.as_bytes()));
+    }
+
+    None
 }
 
 /// Check if a file exists and parse it for colorization rules.
@@ -417,36 +575,31 @@ pub fn load_config(path: &str, pseudo_command: &str) -> Vec<GrcatConfigEntry> {
 /// - `None` if file does not exist
 ///
 /// This distinguishes between "file doesn't exist" (None) and
-/// "file exists but has no rules" (Some([])).
-fn file_exists_and_parse(filename: &str) -> Option<Vec<GrcatConfigEntry>> {
-    // Try to open the file
-    if let Ok(grcat_config_file) = File::open(filename) {
-        let bufreader = std::io::BufReader::new(grcat_config_file);
-        // Parse all rules from the configuration file
-        let configreader = GrcatConfigReader::new(bufreader.lines());
-        let entries: Vec<_> = configreader.collect();
-        // Return Some (even if empty) - file exists
-        return Some(entries);
+/// "file exists but has no rules" (Some([])). A file that exists but cannot be read
+/// is reported in `diagnostics` and counts as not found.
+fn file_exists_and_parse(
+    filename: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<Vec<GrcatConfigEntry>> {
+    // Try to read the file
+    match std::fs::read(filename) {
+        Ok(content) => {
+            // Parse all rules from the configuration file (or take them from the rule cache)
+            let entries = load_rules(filename, &content, diagnostics);
+            // Return Some (even if empty) - file exists
+            return Some(entries);
+        }
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            diagnostics.push(Diagnostic::new(Some(filename), None, DiagnosticKind::Io, e));
+        }
+        Err(_) => {}
     }
 
     // Fallback to embedded configuration (only when embed-configs is enabled)
+    // Return Some (embedded file found, even if empty)
     #[cfg(feature = "embed-configs")]
-    {
-        // Extract config name from path (e.g., "conf.ping" from full path)
-        let config_name = filename;
-
-        // Ensure cache is populated
-        if let Some(cache_dir) = ensure_cache_populated() {
-            let conf_dir = cache_dir.join("conf");
-            let config_path = conf_dir.join(config_name);
-            if let Ok(grcat_config_file) = File::open(&config_path) {
-                let bufreader = std::io::BufReader::new(grcat_config_file);
-                let configreader = GrcatConfigReader::new(bufreader.lines());
-                let entries: Vec<_> = configreader.collect();
-                // Return Some (embedded file found, even if empty)
-                return Some(entries);
-            }
-        }
+    if let Some(entries) = parse_embedded_config(filename, diagnostics) {
+        return Some(entries);
     }
 
     // File not found
@@ -515,53 +668,61 @@ fn file_exists_and_parse(filename: &str) -> Option<Vec<GrcatConfigEntry>> {
 ///
 /// # Error Handling
 ///
-/// All errors (file not found, parse errors, etc.) are silently handled
-/// and result in an empty rule vector. This allows graceful degradation
-/// when configuration files are missing or malformed.
+/// Errors (file not found, parse errors, etc.) never stop loading: bad rules are
+/// left out and a missing file gives an empty rule vector. This allows graceful
+/// degradation when configuration files are missing or malformed. The problems are
+/// reported on stderr; use [`try_load_grcat_config`] to handle them instead.
 pub fn load_grcat_config<T: AsRef<str>>(filename: T) -> Vec<GrcatConfigEntry> {
+    try_load_grcat_config(filename).unwrap_or_else(LoadError::reported)
+}
+
+/// Like [`load_grcat_config`], but returns the problems found (including a file
+/// that cannot be read and errors in `rgrc.defines`) instead of printing them.
+pub fn try_load_grcat_config<T: AsRef<str>>(
+    filename: T,
+) -> Result<Vec<GrcatConfigEntry>, LoadError> {
     let filename_str = filename.as_ref();
 
     // Return empty vector for empty filename
     if filename_str.is_empty() {
-        return Vec::new();
+        return Ok(Vec::new());
     }
 
+    let mut diagnostics = Macros::global_diagnostics().to_vec();
+
     // First, try to load from filesystem
-    if let Ok(grcat_config_file) = File::open(filename_str) {
-        let bufreader = std::io::BufReader::new(grcat_config_file);
-        // Parse all rules from the configuration file
-        let configreader = GrcatConfigReader::new(bufreader.lines());
-        let entries: Vec<_> = configreader.collect();
-
-        // If we successfully loaded from filesystem and got entries, return them
-        if !entries.is_empty() {
-            return entries;
+    let read_error = match std::fs::read(filename_str) {
+        Ok(content) => {
+            // Parse all rules from the configuration file (or take them from the rule cache)
+            let entries = load_rules(filename_str, &content, &mut diagnostics);
+
+            // If we successfully loaded from filesystem and got entries, return them
+            if !entries.is_empty() {
+                return LoadError::check(entries, diagnostics);
+            }
+            None
         }
-    }
+        Err(e) => Some(e),
+    };
 
     // Fallback to embedded configuration (only when embed-configs is enabled)
     #[cfg(feature = "embed-configs")]
+    if let Some(entries) = parse_embedded_config(filename_str, &mut diagnostics)
+        && !entries.is_empty()
     {
-        // Extract config name from path (e.g., "conf.ping" from "conf.ping")
-        let config_name = filename_str;
-
-        // Ensure cache is populated
-        if let Some(cache_dir) = ensure_cache_populated() {
-            let conf_dir = cache_dir.join("conf");
-            let config_path = conf_dir.join(config_name);
-            if let Ok(grcat_config_file) = File::open(&config_path) {
-                let bufreader = std::io::BufReader::new(grcat_config_file);
-                let configreader = GrcatConfigReader::new(bufreader.lines());
-                let entries: Vec<_> = configreader.collect();
-                if !entries.is_empty() {
-                    return entries;
-                }
-            }
-        }
+        return LoadError::check(entries, diagnostics);
     }
 
     // No configuration found
-    Vec::new()
+    if let Some(e) = read_error {
+        diagnostics.push(Diagnostic::new(
+            Some(filename_str),
+            None,
+            DiagnosticKind::Io,
+            e,
+        ));
+    }
+    LoadError::check(Vec::new(), diagnostics)
 }
 
 /// Configuration file paths in priority order.
@@ -611,18 +772,63 @@ const CONFIG_PATHS: &[&str] = &[
 /// ```
 #[allow(dead_code)]
 pub fn load_rules_for_command(pseudo_command: &str) -> Vec<GrcatConfigEntry> {
-    // Always prioritize user config first
+    try_load_rules_for_command(pseudo_command).unwrap_or_else(LoadError::reported)
+}
+
+/// Like [`load_rules_for_command`], but returns the problems found in the configs
+/// it loaded (and in `rgrc.defines`) instead of printing them. Config paths that do
+/// not exist are not problems.
+pub fn try_load_rules_for_command(
+    pseudo_command: &str,
+) -> Result<Vec<GrcatConfigEntry>, LoadError> {
+    let mut diagnostics = Macros::global_diagnostics().to_vec();
+    let rules = find_rules_for_command(pseudo_command, &mut diagnostics);
+    if std::env::var_os("RGRC_DEBUG").is_some() {
+        report_slow_path_rules(&rules);
+    }
+    LoadError::check(rules, diagnostics)
+}
+
+/// Log (to stderr) the rules that do not run on the fast regex engine.
+fn report_slow_path_rules(rules: &[GrcatConfigEntry]) {
+    let slow: Vec<_> = rules
+        .iter()
+        .enumerate()
+        .filter(|(_, rule)| rule.regex.backend().is_slow_path())
+        .collect();
+    eprintln!(
+        "rgrc: {} of {} rules use the slow path (regex engine: {})",
+        slow.len(),
+        rules.len(),
+        grc::regex_engine()
+    );
+    for (idx, rule) in slow {
+        eprintln!(
+            "rgrc:   rule {} [{}]: {}",
+            rule.label(idx),
+            rule.regex.backend(),
+            rule.regex.as_str()
+        );
+    }
+}
+
+fn find_rules_for_command(
+    pseudo_command: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<GrcatConfigEntry> {
+    // Always prioritize user config first (missing config files are not problems)
     let user_config_path = "~/.config/rgrc/rgrc.conf";
     let expanded_user_config = expand_tilde(user_config_path);
-    let rules = load_config(&expanded_user_config, pseudo_command);
+    let rules =
+        load_config_into(&expanded_user_config, pseudo_command, diagnostics).unwrap_or_default();
     if !rules.is_empty() {
         return rules;
     }
 
-    // Then, if embed-configs is enabled, try embedded cache
+    // Then, if embed-configs is enabled, try embedded configs
     #[cfg(feature = "embed-configs")]
     {
-        let embedded_rules = load_config_from_embedded(pseudo_command);
+        let embedded_rules = load_config_from_embedded(pseudo_command, diagnostics);
         if !embedded_rules.is_empty() {
             return embedded_rules;
         }
@@ -634,7 +840,8 @@ pub fn load_rules_for_command(pseudo_command: &str) -> Vec<GrcatConfigEntry> {
             continue; // Already checked above
         }
         let expanded_path = expand_tilde(config_path);
-        let rules = load_config(&expanded_path, pseudo_command);
+        let rules =
+            load_config_into(&expanded_path, pseudo_command, diagnostics).unwrap_or_default();
         if !rules.is_empty() {
             return rules; // Stop at first matching config file
         }
@@ -752,7 +959,12 @@ where
                     eprintln!("{} ✓ Matched {} rule(s):", line_marker, matched_rules.len());
                     for (idx, rule) in matched_rules.iter() {
                         // Display Rule with bold formatting
-                        let rule_display = format!("Rule #{}: {}", idx + 1, rule.regex.as_str());
+                        let rule_display = format!(
+                            "Rule #{} [{}]: {}",
+                            idx + 1,
+                            rule.regex.backend(),
+                            rule.regex.as_str()
+                        );
                         eprintln!("  {}", Style::new().bold().apply_to(&rule_display));
 
                         // Display first matched text with styles applied
@@ -809,41 +1021,25 @@ where
 }
 
 /// Load colorization rules from embedded configuration.
-/// On first run, writes embedded configs to disk cache, then loads from there.
+/// Both the embedded rgrc.conf and the conf files are parsed straight from memory.
 #[cfg(feature = "embed-configs")]
-fn load_config_from_embedded(pseudo_command: &str) -> Vec<GrcatConfigEntry> {
-    // Ensure cache is populated, get cache directory
-    let cache_dir = match ensure_cache_populated() {
-        Some(dir) => dir,
-        None => return Vec::new(), // Failed to create cache
+fn load_config_from_embedded(
+    pseudo_command: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<GrcatConfigEntry> {
+    let index = CommandIndex::new(GrcConfigReader::new(EMBEDDED_GRC_CONF.as_bytes().lines()));
+    let Some((pattern, config_file)) = index.lookup(pseudo_command) else {
+        return Vec::new();
     };
-
-    // Load from cached rgrc.conf
-    let grc_conf_path = cache_dir.join("rgrc.conf");
-    let conf_dir = cache_dir.join("conf");
-
-    // Use load_config to find matching config file
-    if let Ok(f) = File::open(&grc_conf_path) {
-        let bufreader = std::io::BufReader::new(f);
-        let configreader = GrcConfigReader::new(bufreader.lines());
-        for (re, config_file) in configreader {
-            if re.is_match(pseudo_command) {
-                if std::env::var_os("RGRC_DEBUG").is_some() {
-                    eprintln!(
-                        "rgrc: embedded matched pattern '{}' -> {}",
-                        re.as_str(),
-                        config_file
-                    );
-                }
-                let config_path = conf_dir.join(&config_file);
-                if let Some(config_str) = config_path.to_str() {
-                    return load_grcat_config(config_str);
-                }
-            }
-        }
+    if std::env::var_os("RGRC_DEBUG").is_some() {
+        eprintln!(
+            "rgrc: embedded matched pattern '{}' -> {}",
+            pattern, config_file
+        );
     }
-
-    Vec::new()
+    parse_embedded_config(config_file, diagnostics)
+        .map(|rules| with_overlays(rules, config_file, diagnostics))
+        .unwrap_or_default()
 }
 
 #[cfg(test)]
@@ -862,34 +1058,17 @@ mod lib_test {
         // Behavior depends on whether embed-configs feature is enabled
         #[cfg(feature = "embed-configs")]
         {
-            // Ensure tests run reliably in CI where HOME may be unset or point
-            // to an unexpected location. Use a tempdir as HOME so ensure_cache_populated
-            // can write the embedded config cache and load_rules_for_command finds rules.
-            use tempfile::TempDir;
-            let td = TempDir::new().expect("create tempdir");
-            let prev_home = std::env::var_os("HOME");
-            unsafe {
-                std::env::set_var("HOME", td.path());
-            }
-
-            // Re-run loading after setting HOME to the tempdir-backed cache
-            let rules_after = load_rules_for_command("ping");
-
-            // Restore HOME for subsequent tests
-            if let Some(h) = prev_home {
-                unsafe {
-                    std::env::set_var("HOME", h);
-                }
-            } else {
-                unsafe {
-                    std::env::remove_var("HOME");
-                }
-            }
-
+            // Embedded configs are parsed from memory, whatever HOME points to
             assert!(
-                !rules_after.is_empty(),
+                !rules.is_empty(),
                 "Should load rules for ping command from embedded configs when embed-configs is enabled"
             );
+            assert!(!load_config_from_embedded("ping", &mut Vec::new()).is_empty());
+            let unknown =
+                load_config_from_embedded("definitely_not_a_real_command", &mut Vec::new());
+            assert!(unknown.is_empty());
+            assert!(embedded_config("conf.ping").is_some());
+            assert!(embedded_config("/usr/share/grc/conf.ping").is_none());
         }
 
         #[cfg(not(feature = "embed-configs"))]
@@ -941,6 +1120,59 @@ mod lib_test {
         }
     }
 
+    #[test]
+    fn test_include_splices_rules_and_stops_cycles() {
+        use tempfile::TempDir;
+
+        let common: Vec<_> = load_grcat_config("share/conf.common")
+            .iter()
+            .map(|rule| rule.regex.as_str().to_string())
+            .collect();
+        assert!(!common.is_empty());
+
+        let dir = TempDir::new().expect("create temp dir");
+        let conf = dir.path().join("conf.testinclude");
+        std::fs::write(
+            &conf,
+            "regexp=^FIRST\ncolours=red\n-\ninclude=conf.common\n-\nregexp=^LAST\ncolours=green\n",
+        )
+        .expect("write conf");
+        let patterns: Vec<_> = load_grcat_config(conf.to_string_lossy())
+            .iter()
+            .map(|rule| rule.regex.as_str().to_string())
+            .collect();
+        assert_eq!(patterns.len(), common.len() + 2);
+        assert_eq!(patterns[0], "^FIRST");
+        assert_eq!(patterns[1..=common.len()], common[..]);
+        assert_eq!(patterns[common.len() + 1], "^LAST");
+
+        // conf.common including itself, directly or through another config
+        let include = |name: &str| GrcatConfigItem::Include {
+            name: name.to_string(),
+            line: 3,
+        };
+        let mut diagnostics = Vec::new();
+        let mut stack = vec!["conf.common".to_string()];
+        let items = vec![include("conf.common")];
+        assert!(expand_includes(items, &mut stack, &mut diagnostics).is_empty());
+        assert_eq!(stack, ["conf.common"]);
+        let mut stack = vec!["conf.other".to_string(), "conf.common".to_string()];
+        let items = vec![include("conf.other")];
+        assert!(expand_includes(items, &mut stack, &mut diagnostics).is_empty());
+        // Missing configs are skipped
+        let items = vec![include("conf.missing_xyz")];
+        assert!(expand_includes(items, &mut stack, &mut diagnostics).is_empty());
+        let messages: Vec<_> = diagnostics.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            [
+                "conf.common:3: include cycle: conf.common -> conf.common",
+                "conf.common:3: include cycle: conf.other -> conf.common -> conf.other",
+                "conf.common:3: included config conf.missing_xyz not found",
+            ]
+        );
+    }
+
     #[test]
     fn test_config_priority_order() {
         // Test that user configs take precedence over system configs
@@ -0,0 +1,341 @@
+//! Lookaround Lowering - Keep Anchoring Lookarounds on the Fast Path
+//!
+//! Many rules use a lookaround only to anchor a colour, e.g. `(?<=ttl=)\d+`. Such a
+//! rule can run on the standard regex engine as `(?:ttl=)(\d+)`: the body becomes
+//! group 1 and the lookaround text is matched but never reported. [`LoweredRegex`]
+//! does exactly that and renumbers the captures so callers see the original groups:
+//! group 0 is the body, and group N of the original pattern is group N+1 of the
+//! lowered one. The `colours` of a rule therefore apply unchanged.
+//!
+//! A pattern is lowered when all of these hold:
+//! - it starts with a positive lookbehind of fixed length, ends with a positive
+//!   lookahead, or both
+//! - the lookarounds contain no capture groups
+//! - the body between them has no top-level alternation and no inline flags
+//! - the lowered pattern compiles with the standard regex engine
+//!
+//! A fixed-length lookbehind keeps leftmost-match semantics: the body always starts
+//! that many characters after the lowered match, so the leftmost lowered match is the
+//! leftmost original one. Searching from a position starts that many characters
+//! earlier, so the lookbehind can still see the text before the position.
+
+use regex::Regex;
+use regex_lite as regex;
+
+use crate::enhanced_regex::{find_group_end, match_length_bounds, skip_character_class};
+
+/// A lookaround pattern rewritten to run on the standard regex engine
+#[derive(Debug, Clone)]
+pub struct LoweredRegex {
+    regex: Regex,
+    /// Length in characters of the lowered lookbehind (0 without one)
+    lookbehind_len: usize,
+    /// Pattern before lowering
+    original: String,
+}
+
+impl LoweredRegex {
+    /// Lower `pattern`, or return `None` when it cannot be lowered safely.
+    pub fn new(pattern: &str) -> Option<Self> {
+        let bytes = pattern.as_bytes();
+
+        let (lookbehind, body_start) = if pattern.starts_with("(?<=") {
+            let end = find_group_end(bytes, 0)?;
+            (Some(&pattern[4..end - 1]), end)
+        } else {
+            (None, 0)
+        };
+        let (lookahead, body_end) = match trailing_lookahead(pattern, body_start) {
+            Some(open) => (Some(&pattern[open + 3..pattern.len() - 1]), open),
+            None => (None, pattern.len()),
+        };
+        if lookbehind.is_none() && lookahead.is_none() {
+            return None;
+        }
+
+        let body = &pattern[body_start..body_end];
+        if body.is_empty() || has_top_level_alternation(body) || has_inline_flags(body) {
+            return None;
+        }
+
+        let lookbehind_len = match lookbehind {
+            Some(lookbehind) => match match_length_bounds(lookbehind) {
+                (min, Some(max)) if min == max => min,
+                _ => return None,
+            },
+            None => 0,
+        };
+        // Lookarounds must compile on their own and must not capture
+        for lookaround in [lookbehind, lookahead].into_iter().flatten() {
+            if Regex::new(lookaround).ok()?.captures_len() != 1 {
+                return None;
+            }
+        }
+
+        let lowered = format!(
+            "{}({}){}",
+            lookbehind.map_or(String::new(), |l| format!("(?:{})", l)),
+            body,
+            lookahead.map_or(String::new(), |l| format!("(?:{})", l)),
+        );
+        Some(LoweredRegex {
+            regex: Regex::new(&lowered).ok()?,
+            lookbehind_len,
+            original: pattern.to_string(),
+        })
+    }
+
+    /// Find the first match whose body starts at or after `pos`.
+    pub fn captures_from_pos<'t>(&self, text: &'t str, pos: usize) -> Option<LoweredCaptures<'t>> {
+        // Start early enough for the lookbehind to see the text before `pos`
+        let mut start = text[..pos]
+            .char_indices()
+            .rev()
+            .take(self.lookbehind_len)
+            .last()
+            .map_or(pos, |(i, _)| i);
+
+        loop {
+            let caps = self.regex.captures_at(text, start)?;
+            if caps.get(1)?.start() >= pos {
+                return Some(LoweredCaptures { caps });
+            }
+            // Only reachable if the lookbehind length was misjudged: retry further on
+            let found = caps.get(0)?.start();
+            start = found + text[found..].chars().next()?.len_utf8();
+        }
+    }
+
+    /// Check if the pattern matches anywhere in the text.
+    pub fn is_match(&self, text: &str) -> bool {
+        self.captures_from_pos(text, 0).is_some()
+    }
+
+    /// Number of capture groups of the original pattern, including group 0
+    #[allow(dead_code)]
+    pub fn captures_len(&self) -> usize {
+        self.regex.captures_len() - 1
+    }
+
+    /// The original (lookaround) pattern.
+    pub fn as_str(&self) -> &str {
+        &self.original
+    }
+
+    /// The rewritten pattern run by the standard regex engine.
+    #[allow(dead_code)]
+    pub fn lowered_pattern(&self) -> &str {
+        self.regex.as_str()
+    }
+}
+
+/// Captures of a [`LoweredRegex`], numbered like the original pattern
+#[derive(Debug)]
+pub struct LoweredCaptures<'t> {
+    caps: regex::Captures<'t>,
+}
+
+impl<'t> LoweredCaptures<'t> {
+    /// Group `index` of the original pattern (0 = whole match without lookarounds)
+    pub fn get(&self, index: usize) -> Option<regex::Match<'t>> {
+        self.caps.get(index + 1)
+    }
+
+    /// Number of groups of the original pattern, including group 0
+    pub fn len(&self) -> usize {
+        self.caps.len() - 1
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// `pattern` with its leading lookbehind and trailing lookahead turned into plain
+/// non-capturing groups: `(?<=ttl=)\d+` becomes `(?:ttl=)\d+`.
+///
+/// Unlike [`LoweredRegex`], the context becomes part of the whole match, so this is
+/// a rewrite for config authors to consider rather than an equivalent pattern.
+/// `None` without such a lookaround, or with a lookaround anywhere else.
+#[allow(dead_code)]
+pub fn lookarounds_as_groups(pattern: &str) -> Option<String> {
+    let (lookbehind, body_start) = if pattern.starts_with("(?<=") {
+        let end = find_group_end(pattern.as_bytes(), 0)?;
+        (Some(&pattern[4..end - 1]), end)
+    } else {
+        (None, 0)
+    };
+    let (lookahead, body_end) = match trailing_lookahead(pattern, body_start) {
+        Some(open) => (Some(&pattern[open + 3..pattern.len() - 1]), open),
+        None => (None, pattern.len()),
+    };
+    let body = &pattern[body_start..body_end];
+    let other_lookaround = ["(?=", "(?!", "(?<=", "(?<!"]
+        .iter()
+        .any(|open| body.contains(open));
+    if (lookbehind.is_none() && lookahead.is_none()) || other_lookaround {
+        return None;
+    }
+    let group =
+        |lookaround: Option<&str>| lookaround.map_or(String::new(), |l| format!("(?:{})", l));
+    Some(format!("{}{}{}", group(lookbehind), body, group(lookahead)))
+}
+
+/// Byte offset of a positive lookahead that ends the pattern, searching from `from`
+fn trailing_lookahead(pattern: &str, from: usize) -> Option<usize> {
+    let bytes = pattern.as_bytes();
+    let mut i = from;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'[' => i = skip_character_class(bytes, i),
+            b'(' if pattern[i..].starts_with("(?=")
+                && find_group_end(bytes, i) == Some(pattern.len()) =>
+            {
+                return Some(i);
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// Whether `pattern` has a `|` outside any group or character class
+fn has_top_level_alternation(pattern: &str) -> bool {
+    let bytes = pattern.as_bytes();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' => {
+                i = skip_character_class(bytes, i);
+                continue;
+            }
+            b'(' => depth += 1,
+            b')' => depth = depth.saturating_sub(1),
+            b'|' if depth == 0 => return true,
+            _ => {}
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Whether `pattern` has an inline flag group such as `(?i)`, which would also
+/// apply to the lookahead in the original pattern
+fn has_inline_flags(pattern: &str) -> bool {
+    pattern.match_indices("(?").any(|(i, _)| {
+        let flags = pattern[i + 2..]
+            .bytes()
+            .take_while(|b| b.is_ascii_alphabetic() || *b == b'-')
+            .count();
+        flags > 0 && pattern.as_bytes().get(i + 2 + flags) == Some(&b')')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lowered(pattern: &str) -> Option<String> {
+        LoweredRegex::new(pattern).map(|re| re.lowered_pattern().to_string())
+    }
+
+    #[test]
+    fn test_lowering_rewrites() {
+        assert_eq!(lowered(r"(?<=ttl=)\d+").as_deref(), Some(r"(?:ttl=)(\d+)"));
+        assert_eq!(lowered(r"\d+(?=ms)").as_deref(), Some(r"(\d+)(?:ms)"));
+        assert_eq!(
+            lowered(r"(?<=\s)latest(?=\s+)").as_deref(),
+            Some(r"(?:\s)(latest)(?:\s+)")
+        );
+        assert_eq!(
+            lowered(r"(?<=[\{,])(\w+)(?=:)").as_deref(),
+            Some(r"(?:[\{,])((\w+))(?::)")
+        );
+    }
+
+    #[test]
+    fn test_patterns_not_lowered() {
+        // No lookaround, or one that is not leading/trailing
+        assert!(lowered(r"\d+").is_none());
+        assert!(lowered(r"a(?=b)c").is_none());
+        // Negative lookarounds
+        assert!(lowered(r"(?<!x)\d+").is_none());
+        assert!(lowered(r"\d+(?!x)").is_none());
+        // Variable-length lookbehind
+        assert!(lowered(r"(?<=a|bc)\d+").is_none());
+        // Top-level alternation in the body
+        assert!(lowered(r"(?<=x)a|b").is_none());
+        // Capturing lookaround
+        assert!(lowered(r"\d+(?=(ms))").is_none());
+        // Inline flags would change the lookahead
+        assert!(lowered(r"(?i)abc(?=d)").is_none());
+        // Body the fast engine cannot run
+        assert!(lowered(r"(a)\1(?=x)").is_none());
+        // Quantified lookahead
+        assert!(lowered(r"a(?=b)*").is_none());
+    }
+
+    #[test]
+    fn test_lookarounds_as_groups() {
+        assert_eq!(
+            lookarounds_as_groups(r"(?<=a|bc)(\d+)(?=ms)").as_deref(),
+            Some(r"(?:a|bc)(\d+)(?:ms)")
+        );
+        assert_eq!(
+            lookarounds_as_groups(r"(?<=x)a|b").as_deref(),
+            Some("(?:x)a|b")
+        );
+        assert!(lookarounds_as_groups(r"\d+").is_none());
+        assert!(lookarounds_as_groups(r"a(?=b)c").is_none());
+        assert!(lookarounds_as_groups(r"(?<=x)\d+(?!y)").is_none());
+    }
+
+    #[test]
+    fn test_captures_are_renumbered() {
+        let re = LoweredRegex::new(r"(?<=ttl=)(\d+)(ms)?(?=\s)").unwrap();
+        let caps = re.captures_from_pos("icmp_seq=1 ttl=64ms time", 0).unwrap();
+        assert_eq!(caps.len(), 3);
+        assert_eq!(caps.get(0).unwrap().as_str(), "64ms");
+        assert_eq!(caps.get(0).unwrap().start(), 15);
+        assert_eq!(caps.get(1).unwrap().as_str(), "64");
+        assert_eq!(caps.get(2).unwrap().as_str(), "ms");
+        assert!(caps.get(3).is_none());
+    }
+
+    #[test]
+    fn test_lookbehind_sees_text_before_position() {
+        let re = LoweredRegex::new(r"(?<=a)a").unwrap();
+        // Overlapping matches like the original lookbehind
+        let m = re.captures_from_pos("aaa", 0).unwrap();
+        assert_eq!(m.get(0).unwrap().start(), 1);
+        let m = re.captures_from_pos("aaa", 2).unwrap();
+        assert_eq!(m.get(0).unwrap().start(), 2);
+        assert!(re.captures_from_pos("aaa", 3).is_none());
+
+        // Multi-byte characters before the position
+        let re = LoweredRegex::new(r"(?<=é:)\d").unwrap();
+        let m = re.captures_from_pos("é:1 é:2", 4).unwrap();
+        assert_eq!(m.get(0).unwrap().as_str(), "2");
+    }
+
+    #[test]
+    fn test_lookahead_semantics() {
+        let re = LoweredRegex::new(r"\d+(?=\.\d+\.\d+\.\d+)").unwrap();
+        let m = re.captures_from_pos("ip 192.168.1.1", 0).unwrap();
+        assert_eq!(m.get(0).unwrap().as_str(), "192");
+        assert!(!re.is_match("1.2"));
+        assert_eq!(re.as_str(), r"\d+(?=\.\d+\.\d+\.\d+)");
+    }
+
+    #[test]
+    fn test_lazy_quantifier_before_lookahead() {
+        // Like Python's re: the lazy body stops at the first closing quote
+        let re = LoweredRegex::new(r#"(?<=\:\").*?[^\\](?=\")"#).unwrap();
+        let m = re.captures_from_pos(r#"{"a":"info","b":"x"}"#, 0).unwrap();
+        assert_eq!(m.get(0).unwrap().as_str(), "info");
+    }
+}
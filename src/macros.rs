@@ -0,0 +1,272 @@
+//! Pattern Macros - `{NAME}` References in Rule Regexes
+//!
+//! The same IP address, date or size regexes used to be pasted into many conf
+//! files. A grcat config can instead use a macro:
+//!
+//! ```text
+//! define PORT=\d{1,5}
+//! regexp=({IPV4}):({PORT})
+//! colours=default,bright_blue,cyan
+//! ```
+//!
+//! `{NAME}` (an uppercase letter, then uppercase letters, digits or `_`) expands to
+//! the macro inside a non-capturing group. Counted repetitions such as `{1,3}`, and
+//! the braces of `\p{..}` and `\x{..}` escapes, are left alone.
+//!
+//! Macros come from the built-in `share/rgrc.defines`, then
+//! `~/.config/rgrc/rgrc.defines` (see [`Macros::global`]), then the `define`
+//! statements of the config itself, each applying to the lines after it. The body of
+//! a `define` is expanded when it is read, so a macro can use earlier ones but never
+//! itself.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::diagnostic::{Diagnostic, DiagnosticKind};
+
+/// Macros every config starts with
+pub const BUILTIN_DEFINES: &str = include_str!("../share/rgrc.defines");
+
+/// Macro definitions by name
+#[derive(Debug, Clone, Default)]
+pub struct Macros {
+    defines: HashMap<String, String>,
+}
+
+/// Why a `define` statement or a `{NAME}` reference could not be expanded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MacroError {
+    /// `{NAME}` without a definition
+    Undefined(String),
+    /// A `define` without `NAME=regex`, or with an invalid name
+    InvalidDefine(String),
+}
+
+impl fmt::Display for MacroError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MacroError::Undefined(name) => write!(f, "undefined macro {{{}}}", name),
+            MacroError::InvalidDefine(line) => {
+                write!(f, "invalid define (expected define NAME=regex): {}", line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MacroError {}
+
+impl Macros {
+    /// Macros shared by all configs: the built-in defines, then the user's
+    /// `~/.config/rgrc/rgrc.defines`. A line with an error is ignored; see
+    /// [`global_diagnostics`](Self::global_diagnostics).
+    pub fn global() -> &'static Macros {
+        &Self::global_with_diagnostics().0
+    }
+
+    /// Problems in the defines files read by [`global`](Self::global)
+    #[allow(dead_code)]
+    pub fn global_diagnostics() -> &'static [Diagnostic] {
+        &Self::global_with_diagnostics().1
+    }
+
+    fn global_with_diagnostics() -> &'static (Macros, Vec<Diagnostic>) {
+        static GLOBAL: OnceLock<(Macros, Vec<Diagnostic>)> = OnceLock::new();
+        GLOBAL.get_or_init(|| {
+            let mut macros = Macros::default();
+            let mut diagnostics = macros.load("rgrc.defines (built-in)", BUILTIN_DEFINES);
+            if let Some(path) = user_defines_path()
+                && let Ok(content) = std::fs::read_to_string(&path)
+            {
+                diagnostics.extend(macros.load(&path.to_string_lossy(), &content));
+            }
+            (macros, diagnostics)
+        })
+    }
+
+    /// Read the `define` lines of a defines file, returning the problems found
+    pub fn load(&mut self, source: &str, content: &str) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for (idx, line) in content.lines().enumerate() {
+            if let Some(Err(e)) = self.define_line(line) {
+                let line = Some(idx + 1);
+                diagnostics.push(Diagnostic::new(
+                    Some(source),
+                    line,
+                    DiagnosticKind::Macro,
+                    e,
+                ));
+            }
+        }
+        diagnostics
+    }
+
+    /// Apply `line` if it is a `define NAME=regex` statement; `None` otherwise.
+    pub fn define_line(&mut self, line: &str) -> Option<Result<(), MacroError>> {
+        if !is_define(line) {
+            return None;
+        }
+        let rest = &line.trim_start()["define".len()..];
+        let invalid = || MacroError::InvalidDefine(line.trim().to_string());
+        let Some((name, body)) = rest.split_once('=') else {
+            return Some(Err(invalid()));
+        };
+        let name = name.trim();
+        if !is_macro_name(name) {
+            return Some(Err(invalid()));
+        }
+        Some(self.expand(body.trim_start()).map(|body| {
+            self.defines.insert(name.to_string(), body);
+        }))
+    }
+
+    /// Whether no macro is defined
+    pub fn is_empty(&self) -> bool {
+        self.defines.is_empty()
+    }
+
+    /// Definitions as (name, expanded body), sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        let mut defines: Vec<_> = self
+            .defines
+            .iter()
+            .map(|(name, body)| (name.as_str(), body.as_str()))
+            .collect();
+        defines.sort_unstable();
+        defines.into_iter()
+    }
+
+    /// Replace the `{NAME}` references in `pattern` by their definitions.
+    pub fn expand(&self, pattern: &str) -> Result<String, MacroError> {
+        if !pattern.contains('{') {
+            return Ok(pattern.to_string());
+        }
+        let mut out = String::with_capacity(pattern.len());
+        let mut rest = pattern;
+        while let Some(c) = rest.chars().next() {
+            if c == '\\' {
+                // Copy an escape as is, with the braces of \p{..}, \P{..} and \x{..}
+                let escape_len = rest[1..].chars().next().map_or(1, |e| 1 + e.len_utf8());
+                let (escape, after) = rest.split_at(escape_len);
+                out.push_str(escape);
+                rest = after;
+                if matches!(escape, "\\p" | "\\P" | "\\x")
+                    && rest.starts_with('{')
+                    && let Some(end) = rest.find('}')
+                {
+                    out.push_str(&rest[..=end]);
+                    rest = &rest[end + 1..];
+                }
+                continue;
+            }
+            if c == '{'
+                && let Some(end) = rest.find('}')
+                && is_macro_name(&rest[1..end])
+            {
+                let name = &rest[1..end];
+                let body = self
+                    .defines
+                    .get(name)
+                    .ok_or_else(|| MacroError::Undefined(name.to_string()))?;
+                out.push_str("(?:");
+                out.push_str(body);
+                out.push(')');
+                rest = &rest[end + 1..];
+                continue;
+            }
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+        Ok(out)
+    }
+}
+
+/// Whether `line` is a `define` statement (valid or not)
+pub fn is_define(line: &str) -> bool {
+    line.trim_start()
+        .strip_prefix("define")
+        .is_some_and(|rest| rest.starts_with([' ', '\t']))
+}
+
+/// `~/.config/rgrc/rgrc.defines`
+fn user_defines_path() -> Option<std::path::PathBuf> {
+    let home = std::env::var_os("HOME")?;
+    Some(std::path::PathBuf::from(home).join(".config/rgrc/rgrc.defines"))
+}
+
+/// `[A-Z][A-Z0-9_]*`
+fn is_macro_name(name: &str) -> bool {
+    name.bytes().next().is_some_and(|b| b.is_ascii_uppercase())
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn macros(defines: &str) -> Macros {
+        let mut macros = Macros::default();
+        for line in defines.lines() {
+            macros.define_line(line).unwrap().unwrap();
+        }
+        macros
+    }
+
+    #[test]
+    fn test_expand_references_only() {
+        let m = macros("define NUM=\\d+\ndefine PAIR = {NUM}:{NUM}");
+        assert_eq!(m.expand("x{NUM}+").unwrap(), "x(?:\\d+)+");
+        assert_eq!(m.expand("{PAIR}").unwrap(), "(?:(?:\\d+):(?:\\d+))");
+        // Repetitions, escapes and lowercase names are not references
+        for pattern in [
+            r"\d{1,3}", r"a{2}", r"\p{Lu}", r"\x{41}", r"\{NUM}", "{num}", "{",
+        ] {
+            assert_eq!(m.expand(pattern).unwrap(), pattern);
+        }
+        assert_eq!(
+            m.expand("{NUM}{PORT}"),
+            Err(MacroError::Undefined("PORT".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_define_line() {
+        let mut m = Macros::default();
+        assert!(m.define_line("regexp=define").is_none());
+        assert!(m.define_line("defines=1").is_none());
+        assert!(m.define_line("define X").unwrap().is_err());
+        assert!(m.define_line("define lower=x").unwrap().is_err());
+        assert!(m.define_line("define SELF={SELF}").unwrap().is_err());
+        assert!(m.is_empty());
+        assert!(m.define_line("define A_1=a b ").unwrap().is_ok());
+        assert_eq!(m.iter().collect::<Vec<_>>(), [("A_1", "a b ")]);
+    }
+
+    #[test]
+    fn test_builtin_defines_compile_and_match() {
+        let mut m = Macros::default();
+        assert!(m.load("builtin", BUILTIN_DEFINES).is_empty());
+        for (name, sample) in [
+            ("IPV4", "192.168.0.1"),
+            ("IPV6", "2001:db8::ff00:42:8329"),
+            ("IPV6", "fe80::1"),
+            ("MAC", "00:1a:2b:3c:4d:5e"),
+            ("ISO_DATE", "2024-01-31"),
+            ("TIME", "23:59:59.123"),
+            ("SIZE", "1.5 GiB"),
+            ("UUID", "123e4567-e89b-12d3-a456-426614174000"),
+        ] {
+            let pattern = format!("^{{{}}}$", name);
+            let re = crate::grc::CompiledRegex::new(&m.expand(&pattern).unwrap()).unwrap();
+            assert!(re.is_match(sample), "{} should match {}", name, sample);
+        }
+        let time_is_not_ipv6 = m.expand("^{IPV6}$").unwrap();
+        assert!(
+            !crate::grc::CompiledRegex::new(&time_is_not_ipv6)
+                .unwrap()
+                .is_match("12:34:56")
+        );
+    }
+}
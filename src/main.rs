@@ -1,11 +1,12 @@
 // Import testable components from lib
 use rgrc::{
-    ColorMode,
+    ColorMode, LoadError,
     args::{get_completion_script, parse_args},
     buffer::LineBufferedWriter,
     colorizer::colorize_regex as colorize,
     grc::GrcatConfigEntry,
-    load_rules_for_command,
+    parallel::colorize_regex_parallel,
+    try_load_rules_for_command,
     utils::{SUPPORTED_COMMANDS, command_exists, should_use_colorization_for_command_supported},
 };
 
@@ -40,6 +41,38 @@ fn handle_io_error(e: std::io::Error) -> Result<(), Box<dyn std::error::Error>>
     Err(Box::new(e))
 }
 
+/// Worker threads for colorizing `--config` input.
+///
+/// Batching holds output back until a chunk of lines is complete, so it is only used
+/// when stdin is not a terminal: by default when stdin is a regular file
+/// (`rgrc -c log < big.log`), or for any non-terminal input with `--jobs N`.
+fn batch_jobs(requested: Option<usize>) -> usize {
+    if io::stdin().is_terminal() {
+        return 1;
+    }
+    match requested {
+        Some(jobs) => jobs,
+        None if stdin_is_regular_file() => rgrc::parallel::default_jobs(),
+        None => 1,
+    }
+}
+
+fn stdin_is_regular_file() -> bool {
+    #[cfg(unix)]
+    let handle = std::os::fd::AsFd::as_fd(&io::stdin()).try_clone_to_owned();
+    #[cfg(windows)]
+    let handle = std::os::windows::io::AsHandle::as_handle(&io::stdin()).try_clone_to_owned();
+
+    #[cfg(any(unix, windows))]
+    return handle
+        .map(std::fs::File::from)
+        .and_then(|file| file.metadata())
+        .is_ok_and(|metadata| metadata.is_file());
+
+    #[cfg(not(any(unix, windows)))]
+    false
+}
+
 // Use mimalloc for faster memory allocation (reduces startup overhead)
 #[cfg(not(target_env = "msvc"))]
 #[global_allocator]
@@ -48,10 +81,11 @@ static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 /// Flush and rebuild the cache directory (embed-configs only)
 ///
 /// This function removes the existing cache directory and rebuilds it with
-/// embedded configuration files. It displays the progress and results.
+/// embedded configuration files, so they can be inspected or copied; rgrc itself
+/// reads them from memory. It displays the progress and results.
 #[cfg(feature = "embed-configs")]
 fn flush_and_rebuild_cache() {
-    use rgrc::EMBEDDED_CONFIGS;
+    use rgrc::EMBEDDED_CONFIG_NAMES;
 
     println!("Flushing and rebuilding cache directory...");
 
@@ -61,7 +95,7 @@ fn flush_and_rebuild_cache() {
             println!("  Location: {}", cache_dir.display());
             println!("  Main config: rgrc.conf");
             println!("  Color configs: {} files in conf/", config_count);
-            println!("  Total embedded configs: {}", EMBEDDED_CONFIGS.len());
+            println!("  Total embedded configs: {}", EMBEDDED_CONFIG_NAMES.len());
         }
         None => {
             eprintln!("Error: Failed to rebuild cache directory");
@@ -90,6 +124,8 @@ fn flush_and_rebuild_cache() {
 /// - --all-aliases: Print shell aliases for all known commands.
 /// - --except CMD1,CMD2,...: Exclude commands from alias generation.
 /// - --completions SHELL: Print completion script for SHELL (bash|zsh|fish|ash)
+/// - --regex-engine ENGINE: Fallback engine for lookaround patterns (auto|fast|enhanced|fancy)
+/// - --backtrack-limit N / --rule-timeout MS: Limits for slow-path (lookaround) rules
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Parse command-line arguments
     let args = match parse_args() {
@@ -100,6 +136,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
+    // Select the fallback regex engine before any rules are compiled
+    rgrc::grc::set_regex_engine(args.regex_engine);
+    rgrc::grc::set_match_limits(args.match_limits);
+
     // Handle --version flag first: print version and exit
     if args.show_version {
         println!("rgrc {}", env!("CARGO_PKG_VERSION"));
@@ -195,8 +235,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
-        // Load colorization rules for the specified config
-        let rules: Vec<GrcatConfigEntry> = load_rules_for_command(config_name);
+        // Load colorization rules for the specified config; problems go to stderr,
+        // the rules that loaded are used anyway
+        let rules: Vec<GrcatConfigEntry> =
+            try_load_rules_for_command(config_name).unwrap_or_else(LoadError::reported);
 
         if rules.is_empty() {
             // No rules found, just pass through
@@ -226,6 +268,31 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         let stdin = io::stdin();
         let mut buffered_stdin = io::BufReader::with_capacity(64 * 1024, stdin.lock());
         let mut buffered_stdout = io::BufWriter::with_capacity(64 * 1024, io::stdout());
+
+        // Large non-interactive input: colorize batches of lines on all cores
+        let jobs = batch_jobs(args.jobs);
+        #[cfg(feature = "debug")]
+        let jobs = if args.debug_level == DebugLevel::Off {
+            jobs
+        } else {
+            1
+        };
+        if jobs > 1 {
+            if let Err(e) = colorize_regex_parallel(
+                &mut buffered_stdin,
+                &mut buffered_stdout,
+                rules.as_slice(),
+                rgrc::grc::match_limits(),
+                jobs,
+            ) {
+                handle_box_error(e)?;
+            }
+            if let Err(e) = buffered_stdout.flush() {
+                handle_io_error(e)?;
+            }
+            std::process::exit(0);
+        }
+
         let mut line_buffered_writer = LineBufferedWriter::new(&mut buffered_stdout);
 
         // Use debug colorizer if debug_level is not Off
@@ -323,7 +390,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     let rules: Vec<GrcatConfigEntry> = if should_colorize {
-        load_rules_for_command(&pseudo_command)
+        // Problems in the configs go to stderr, never into the colorized stream
+        try_load_rules_for_command(&pseudo_command).unwrap_or_else(LoadError::reported)
     } else {
         Vec::new()
     };
@@ -0,0 +1,195 @@
+//! Overlays - User Rules Merged Into a Bundled Config
+//!
+//! A conf file found in `RESOURCE_PATHS` replaces the bundled one entirely, so
+//! changing one colour used to mean copying the whole file. Overlays instead hold
+//! only the differences. For a config `conf.ping`, rgrc reads
+//! `~/.config/rgrc/conf.ping.d/*.conf` (sorted by name), then
+//! `~/.config/rgrc/conf.ping.local`, and merges each into the rules loaded so far:
+//!
+//! ```text
+//! # ~/.config/rgrc/conf.ping.local
+//! # Same id as a bundled rule: replaces it in place
+//! id=ttl
+//! regexp=ttl=(\d+)
+//! colours=default,bold yellow
+//! -
+//! # New rule, before the bundled ones
+//! regexp=\bDUP!
+//! colours=bold red
+//! overlay=prepend
+//! ```
+//!
+//! A rule goes where its `overlay` key says (see [`OverlayMode`]). Without the key
+//! it replaces the rule with the same `id`, or is appended when there is none. A
+//! prepended or appended rule whose `id` is already used moves that rule instead of
+//! duplicating it.
+
+use std::path::{Path, PathBuf};
+
+use crate::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::grc::{GrcatConfigEntry, OverlayMode};
+
+/// Overlay files for `config` in `dir`, in the order they apply: the `*.conf` files
+/// of `<config>.d` sorted by name, then `<config>.local`.
+pub fn overlay_files(dir: &Path, config: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir.join(format!("{}.d", config)))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "conf") && path.is_file())
+        .collect();
+    files.sort();
+    let local = dir.join(format!("{}.local", config));
+    if local.is_file() {
+        files.push(local);
+    }
+    files
+}
+
+/// Merge the overlays of `config` found in `dir` into `rules`, parsing each overlay
+/// file with `load(path, content, diagnostics)`.
+pub fn apply_overlays(
+    mut rules: Vec<GrcatConfigEntry>,
+    dir: &Path,
+    config: &str,
+    load: impl Fn(&str, &[u8], &mut Vec<Diagnostic>) -> Vec<GrcatConfigEntry>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<GrcatConfigEntry> {
+    for path in overlay_files(dir, config) {
+        let source = path.to_string_lossy();
+        let content = match std::fs::read(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                diagnostics.push(Diagnostic::new(Some(&source), None, DiagnosticKind::Io, e));
+                continue;
+            }
+        };
+        let overlay = load(&source, &content, diagnostics);
+        if std::env::var_os("RGRC_DEBUG").is_some() {
+            eprintln!(
+                "rgrc: applying overlay {} ({} rules)",
+                source,
+                overlay.len()
+            );
+        }
+        rules = merge(rules, overlay, &source, diagnostics);
+    }
+    rules
+}
+
+/// Merge the rules of one overlay file (`source`, for diagnostics) into `rules`.
+pub fn merge(
+    mut rules: Vec<GrcatConfigEntry>,
+    overlay: Vec<GrcatConfigEntry>,
+    source: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<GrcatConfigEntry> {
+    // Prepended rules keep their order, ahead of everything else
+    let mut front = 0;
+    for rule in overlay {
+        let existing = rule
+            .id
+            .as_deref()
+            .and_then(|id| rules.iter().position(|r| r.id.as_deref() == Some(id)));
+        match (rule.overlay, existing) {
+            (None | Some(OverlayMode::Replace), Some(idx)) => rules[idx] = rule,
+            (None, None) => rules.push(rule),
+            (Some(OverlayMode::Replace), None) => diagnostics.push(Diagnostic::new(
+                Some(source),
+                None,
+                DiagnosticKind::Overlay,
+                format!(
+                    "no rule with id={} to replace",
+                    rule.id.as_deref().unwrap_or_default()
+                ),
+            )),
+            (Some(OverlayMode::Prepend), existing) => {
+                if let Some(idx) = existing {
+                    rules.remove(idx);
+                    front -= usize::from(idx < front);
+                }
+                rules.insert(front, rule);
+                front += 1;
+            }
+            (Some(OverlayMode::Append), existing) => {
+                if let Some(idx) = existing {
+                    rules.remove(idx);
+                    front -= usize::from(idx < front);
+                }
+                rules.push(rule);
+            }
+        }
+    }
+    rules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grc::GrcatConfigReader;
+    use std::io::BufRead;
+
+    fn rules(conf: &str) -> Vec<GrcatConfigEntry> {
+        GrcatConfigReader::new(conf.as_bytes().lines()).collect()
+    }
+
+    fn patterns(rules: &[GrcatConfigEntry]) -> Vec<&str> {
+        rules.iter().map(|rule| rule.regex.as_str()).collect()
+    }
+
+    const BASE: &str = "id=ip\nregexp=ip\n-\nid=ttl\nregexp=ttl\n-\nregexp=plain\n";
+
+    #[test]
+    fn test_merge_replaces_by_id_and_appends_new_rules() {
+        let merged = merge(
+            rules(BASE),
+            rules("id=ttl\nregexp=TTL\ncolours=yellow\n-\nregexp=extra\n"),
+            "test",
+            &mut Vec::new(),
+        );
+        assert_eq!(patterns(&merged), ["ip", "TTL", "plain", "extra"]);
+        assert_eq!(merged[1].id.as_deref(), Some("ttl"));
+    }
+
+    #[test]
+    fn test_merge_prepend_append_and_move() {
+        let mut diagnostics = Vec::new();
+        let merged = merge(
+            rules(BASE),
+            rules(concat!(
+                "regexp=first\noverlay=prepend\n-\n",
+                "regexp=second\noverlay=prepend\n-\n",
+                "id=ip\nregexp=IP\noverlay=append\n-\n",
+                "id=ttl\nregexp=TTL\noverlay=prepend\n-\n",
+                "id=missing\nregexp=nothing\noverlay=replace\n",
+            )),
+            "test",
+            &mut diagnostics,
+        );
+        assert_eq!(patterns(&merged), ["first", "second", "TTL", "plain", "IP"]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "test: no rule with id=missing to replace"
+        );
+    }
+
+    #[test]
+    fn test_overlay_files_order() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let overlays = dir.path().join("conf.ping.d");
+        std::fs::create_dir(&overlays).unwrap();
+        for name in ["20-b.conf", "10-a.conf", "notes.txt"] {
+            std::fs::write(overlays.join(name), "").unwrap();
+        }
+        std::fs::write(dir.path().join("conf.ping.local"), "").unwrap();
+        std::fs::write(dir.path().join("conf.ls.local"), "").unwrap();
+
+        let names: Vec<_> = overlay_files(dir.path(), "conf.ping")
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["10-a.conf", "20-b.conf", "conf.ping.local"]);
+        assert!(overlay_files(dir.path(), "conf.df").is_empty());
+    }
+}
@@ -0,0 +1,283 @@
+//! Parallel Colorization - Batches of Lines on a Thread Pool
+//!
+//! Colorizing a large file is dominated by regex matching, which is independent for
+//! each line: no rule carries state from one line to the next, since grc's
+//! cross-line counts (`block`, `unblock`, `previous`) are not supported; the config
+//! reader warns about them and treats the rule as `count=more`. This module reads
+//! the input in chunks of whole lines, colorizes the chunks on a pool of worker
+//! threads and writes the results back in input order, so the output is the same as
+//! with [`crate::colorizer::colorize_regex`].
+//!
+//! Batching delays output until a chunk is full, so it is meant for files and other
+//! non-interactive input, not for a live stream such as `tail -f`.
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+
+use crate::colorizer::{colorize_lines, disabled_flags};
+use crate::grc::{GrcatConfigEntry, GrcatConfigEntryCount, MatchLimits};
+use crate::prefilter::Prefilter;
+
+/// Target size of a chunk of input handed to a worker
+pub const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Number of worker threads to use when none is requested
+pub fn default_jobs() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Whether every rule can be applied to a line without looking at other lines.
+///
+/// Every count supported today is line independent. The match has no wildcard arm,
+/// so a count with cross-line semantics (a block of lines, or a count referring to
+/// the previous line) cannot be added without deciding here whether it must run
+/// sequentially.
+fn rules_are_line_independent(rules: &[GrcatConfigEntry]) -> bool {
+    rules.iter().all(|rule| match rule.count {
+        GrcatConfigEntryCount::Once | GrcatConfigEntryCount::More | GrcatConfigEntryCount::Stop => {
+            true
+        }
+    })
+}
+
+/// Colorize `reader` into `writer` using up to `jobs` threads.
+///
+/// Falls back to sequential colorization for a single job or when the rules are not
+/// line independent. Output is identical to the sequential colorizer; a rule disabled
+/// by its match limits in one chunk is skipped in the chunks that start after it.
+pub fn colorize_regex_parallel<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    rules: &[GrcatConfigEntry],
+    limits: MatchLimits,
+    jobs: usize,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    R: BufRead,
+    W: Write,
+{
+    colorize_in_chunks(reader, writer, rules, limits, jobs, CHUNK_SIZE)
+}
+
+fn colorize_in_chunks<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    rules: &[GrcatConfigEntry],
+    limits: MatchLimits,
+    jobs: usize,
+    chunk_size: usize,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    R: BufRead,
+    W: Write,
+{
+    if jobs <= 1 || rules.is_empty() || !rules_are_line_independent(rules) {
+        return crate::colorizer::colorize_regex_with_limits(reader, writer, rules, limits);
+    }
+
+    let prefilter = Prefilter::new(rules);
+    let disabled_rules = disabled_flags(rules.len());
+
+    // Chunks waiting for a worker: (sequence number, first line, input)
+    let (chunk_tx, chunk_rx) = mpsc::sync_channel::<(usize, usize, Vec<u8>)>(jobs * 2);
+    let chunk_rx = Arc::new(Mutex::new(chunk_rx));
+    // Colorized chunks, in completion order
+    let (done_tx, done_rx) = mpsc::channel::<(usize, Colorized)>();
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs {
+            let chunk_rx = Arc::clone(&chunk_rx);
+            let done_tx = done_tx.clone();
+            let (prefilter, disabled_rules) = (&prefilter, &disabled_rules);
+            scope.spawn(move || {
+                loop {
+                    // The lock is only held while waiting for the next chunk
+                    let next = chunk_rx.lock().map(|rx| rx.recv());
+                    let Ok(Ok((seq, first_line, input))) = next else {
+                        break;
+                    };
+                    let mut output = Vec::with_capacity(input.len() * 2);
+                    let error = colorize_lines(
+                        &mut input.as_slice(),
+                        &mut output,
+                        rules,
+                        limits,
+                        prefilter,
+                        disabled_rules,
+                        first_line,
+                    )
+                    .err()
+                    .map(|e| e.to_string());
+                    if done_tx.send((seq, Colorized { output, error })).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(done_tx);
+
+        let mut pending = Reorder::default();
+        let mut read_error = None;
+        let mut seq = 0;
+        let mut lines = 0;
+        loop {
+            let (input, chunk_lines) = match read_chunk(reader, chunk_size) {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    read_error = Some(e);
+                    break;
+                }
+            };
+            if input.is_empty() {
+                break;
+            }
+            if chunk_tx.send((seq, lines, input)).is_err() {
+                break;
+            }
+            seq += 1;
+            lines += chunk_lines;
+
+            // Write whatever is ready so finished chunks do not pile up
+            for (seq, result) in done_rx.try_iter() {
+                pending.insert(seq, result);
+            }
+            pending.write_ready(writer)?;
+        }
+        drop(chunk_tx);
+
+        for (seq, result) in done_rx.iter() {
+            pending.insert(seq, result);
+            pending.write_ready(writer)?;
+        }
+        match read_error {
+            Some(e) => Err(e.into()),
+            None => Ok(()),
+        }
+    })
+}
+
+/// Read whole lines until at least `chunk_size` bytes or the end of input,
+/// returning the bytes and the number of lines.
+fn read_chunk<R: BufRead>(reader: &mut R, chunk_size: usize) -> std::io::Result<(Vec<u8>, usize)> {
+    let mut chunk = Vec::with_capacity(chunk_size + 4096);
+    let mut lines = 0;
+    while chunk.len() < chunk_size {
+        if reader.read_until(b'\n', &mut chunk)? == 0 {
+            break;
+        }
+        lines += 1;
+    }
+    Ok((chunk, lines))
+}
+
+/// Output of a chunk, and the error that cut it short (e.g. invalid UTF-8)
+struct Colorized {
+    output: Vec<u8>,
+    error: Option<String>,
+}
+
+/// Colorized chunks waiting for their predecessors to be written
+#[derive(Default)]
+struct Reorder {
+    next: usize,
+    done: BTreeMap<usize, Colorized>,
+}
+
+impl Reorder {
+    fn insert(&mut self, seq: usize, chunk: Colorized) {
+        self.done.insert(seq, chunk);
+    }
+
+    /// Write every chunk whose predecessors have all been written
+    fn write_ready<W: Write>(&mut self, writer: &mut W) -> Result<(), Box<dyn std::error::Error>> {
+        while let Some(chunk) = self.done.remove(&self.next) {
+            // Lines before an error are written, as the sequential colorizer does
+            writer.write_all(&chunk.output)?;
+            if let Some(error) = chunk.error {
+                return Err(error.into());
+            }
+            self.next += 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grc::CompiledRegex;
+    use crate::style::Style;
+
+    fn rules() -> Vec<GrcatConfigEntry> {
+        [
+            (r"\d+", Style::new().red()),
+            (r"ERROR", Style::new().bold()),
+        ]
+        .into_iter()
+        .map(|(p, style)| GrcatConfigEntry::new(CompiledRegex::new(p).unwrap(), vec![style]))
+        .collect()
+    }
+
+    fn colorize(input: &str, jobs: usize) -> Vec<u8> {
+        let mut output = Vec::new();
+        // Small chunks to exercise reordering
+        colorize_in_chunks(
+            &mut input.as_bytes(),
+            &mut output,
+            &rules(),
+            MatchLimits::default(),
+            jobs,
+            4096,
+        )
+        .unwrap();
+        output
+    }
+
+    #[test]
+    fn test_parallel_output_matches_sequential() {
+        // Several chunks, with an unterminated last line
+        let input: String = (0..5_000)
+            .map(|i| format!("line {} ERROR {}\n\n", i, "x".repeat(i % 50)))
+            .collect::<String>()
+            + "tail 42";
+        assert!(input.len() > 40 * 4096);
+
+        let sequential = colorize(&input, 1);
+        assert_eq!(colorize(&input, 4), sequential);
+        assert_eq!(colorize(&input, 2), sequential);
+    }
+
+    #[test]
+    fn test_read_chunk_splits_on_lines() {
+        let line = "a".repeat(1000) + "\n";
+        let input = line.repeat(CHUNK_SIZE / 1000 + 10);
+        let mut reader = input.as_bytes();
+
+        let (chunk, lines) = read_chunk(&mut reader, CHUNK_SIZE).unwrap();
+        assert!(chunk.len() >= CHUNK_SIZE);
+        assert!(chunk.ends_with(b"\n"));
+        assert_eq!(chunk.len(), lines * line.len());
+
+        let (rest, _) = read_chunk(&mut reader, CHUNK_SIZE).unwrap();
+        assert_eq!(chunk.len() + rest.len(), input.len());
+        assert!(read_chunk(&mut reader, CHUNK_SIZE).unwrap().0.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_utf8_is_an_error() {
+        let mut output = Vec::new();
+        let result = colorize_regex_parallel(
+            &mut &b"ok 1\n\xff\xfe 2\nok 3\n"[..],
+            &mut output,
+            &rules(),
+            MatchLimits::default(),
+            2,
+        );
+        assert!(result.is_err());
+        // Lines before the invalid one are written
+        let red_one = Style::new().red().apply_to("1").to_string();
+        assert_eq!(output, format!("ok {}\n", red_one).into_bytes());
+    }
+}
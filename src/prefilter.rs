@@ -0,0 +1,460 @@
+//! Literal Prefilter - Skip Rules That Cannot Match a Line
+//!
+//! Most rules contain text that every match must include, such as `icmp_seq=`,
+//! `ttl=` or `DUP!`. A [`Prefilter`] extracts that text from each rule once, builds
+//! an Aho-Corasick automaton over all of it, looks for every literal in a single
+//! pass over each line, and reports which rules can possibly match. Running a regex is far more expensive than a substring search,
+//! so configs with many rules skip most of their regexes on a typical line.
+//!
+//! The requirement of a rule is a set of literals, at least one of which must occur
+//! in the line: `\s(ERR|ERROR)\b` requires `ERR` or `ERROR`, and
+//! `(?<=ttl=)\d+` requires `ttl=`. A rule without a requirement, e.g. `^\d+`, or
+//! with case-insensitive flags, is always run. The extraction is conservative: a
+//! literal is only required when the pattern cannot match without it.
+
+use aho_corasick::AhoCorasick;
+
+use crate::enhanced_regex::{find_group_end, skip_character_class};
+use crate::grc::GrcatConfigEntry;
+use crate::translate::translate;
+
+/// Per-config prefilter built once from a set of rules
+///
+/// [`Prefilter::default`] has no requirements and lets every rule through.
+#[derive(Debug, Clone, Default)]
+pub struct Prefilter {
+    /// Distinct literals searched in each line
+    literals: Vec<String>,
+    /// For each rule, indices into `literals` of which at least one must be present
+    /// (`None` = always run the rule)
+    requirements: Vec<Option<Vec<usize>>>,
+    /// Automaton finding every literal in one pass (`None` when there are none)
+    searcher: Option<AhoCorasick>,
+}
+
+impl Prefilter {
+    /// Build the prefilter for `rules`, in the same order.
+    pub fn new(rules: &[GrcatConfigEntry]) -> Self {
+        let mut prefilter = Prefilter::default();
+        for rule in rules {
+            let requirement = required_literals(rule.regex.as_str()).map(|literals| {
+                literals
+                    .into_iter()
+                    .map(|literal| prefilter.intern(literal))
+                    .collect()
+            });
+            prefilter.requirements.push(requirement);
+        }
+        if prefilter.literals.is_empty() {
+            return prefilter;
+        }
+        match AhoCorasick::new(&prefilter.literals) {
+            Ok(searcher) => prefilter.searcher = Some(searcher),
+            // Too many literals for the automaton: run every rule
+            Err(_) => return Prefilter::default(),
+        }
+        prefilter
+    }
+
+    fn intern(&mut self, literal: String) -> usize {
+        match self.literals.iter().position(|l| *l == literal) {
+            Some(idx) => idx,
+            None => {
+                self.literals.push(literal);
+                self.literals.len() - 1
+            }
+        }
+    }
+
+    /// Search `line` for every literal in one pass, storing the result in `present`.
+    ///
+    /// `present` is reused across lines to avoid an allocation per line.
+    pub fn scan(&self, line: &str, present: &mut Vec<bool>) {
+        present.clear();
+        present.resize(self.literals.len(), false);
+        let Some(searcher) = &self.searcher else {
+            return;
+        };
+        let mut missing = self.literals.len();
+        for found in searcher.find_overlapping_iter(line) {
+            let seen = &mut present[found.pattern().as_usize()];
+            if !*seen {
+                *seen = true;
+                missing -= 1;
+                if missing == 0 {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Whether rule `rule_idx` can match a line, given the result of [`Prefilter::scan`]
+    pub fn may_match(&self, present: &[bool], rule_idx: usize) -> bool {
+        match self.requirements.get(rule_idx) {
+            Some(Some(literals)) => literals.iter().any(|&idx| present[idx]),
+            _ => true,
+        }
+    }
+
+    /// Literals of which rule `rule_idx` needs at least one (`None` = always run)
+    #[allow(dead_code)]
+    pub fn requirement(&self, rule_idx: usize) -> Option<Vec<&str>> {
+        let literals = self.requirements.get(rule_idx)?.as_ref()?;
+        Some(
+            literals
+                .iter()
+                .map(|&idx| self.literals[idx].as_str())
+                .collect(),
+        )
+    }
+
+    /// Number of rules with a requirement
+    #[allow(dead_code)]
+    pub fn filtered_rules(&self) -> usize {
+        self.requirements.iter().filter(|r| r.is_some()).count()
+    }
+}
+
+/// Literals of which any match of `pattern` contains at least one, or `None` when no
+/// such set can be derived.
+pub fn required_literals(pattern: &str) -> Option<Vec<String>> {
+    let pattern = translate(pattern).pattern;
+    if has_case_insensitive_flag(&pattern) {
+        return None;
+    }
+    alternation_requirement(&pattern)
+}
+
+/// Requirement of a pattern that may contain a top-level `|`: one literal per branch
+fn alternation_requirement(pattern: &str) -> Option<Vec<String>> {
+    let mut literals: Vec<String> = Vec::new();
+    for branch in split_alternatives(pattern) {
+        for literal in sequence_requirement(branch)? {
+            if !literals.contains(&literal) {
+                literals.push(literal);
+            }
+        }
+    }
+    Some(literals)
+}
+
+/// Requirement of a sequence without top-level `|`: the most selective of the
+/// literal runs and mandatory groups it contains
+fn sequence_requirement(pattern: &str) -> Option<Vec<String>> {
+    let bytes = pattern.as_bytes();
+    let mut candidates: Vec<Vec<String>> = Vec::new();
+    let mut run = String::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' => {
+                flush(&mut run, &mut candidates);
+                let end = find_group_end(bytes, i)?;
+                let (optional, next) = quantifier(bytes, end);
+                if !optional && let Some(inner) = group_body(&pattern[i..end]) {
+                    candidates.extend(alternation_requirement(inner));
+                }
+                i = next;
+            }
+            b'[' => {
+                flush(&mut run, &mut candidates);
+                i = quantifier(bytes, skip_character_class(bytes, i)).1;
+            }
+            b'.' | b'^' | b'$' => {
+                flush(&mut run, &mut candidates);
+                i = quantifier(bytes, i + 1).1;
+            }
+            b'\\' => match escaped_literal(pattern, i) {
+                Some((ch, next)) => i = push_literal(bytes, ch, next, &mut run, &mut candidates),
+                None => {
+                    flush(&mut run, &mut candidates);
+                    i = quantifier(bytes, skip_escape(bytes, i)).1;
+                }
+            },
+            _ => {
+                let ch = pattern[i..].chars().next()?;
+                i = push_literal(bytes, ch, i + ch.len_utf8(), &mut run, &mut candidates);
+            }
+        }
+    }
+    flush(&mut run, &mut candidates);
+
+    // Longest shortest literal wins, then the fewest alternatives
+    candidates
+        .into_iter()
+        .filter(|c| !c.is_empty())
+        .max_by_key(|c| {
+            let shortest = c.iter().map(String::len).min().unwrap_or(0);
+            (shortest, usize::MAX - c.len())
+        })
+}
+
+/// Append a literal character to `run`, honouring a quantifier after it
+fn push_literal(
+    bytes: &[u8],
+    ch: char,
+    next: usize,
+    run: &mut String,
+    candidates: &mut Vec<Vec<String>>,
+) -> usize {
+    let (optional, after) = quantifier(bytes, next);
+    if optional {
+        flush(run, candidates);
+    } else {
+        run.push(ch);
+        if after != next {
+            // Repeated: the character is required, but what follows is not adjacent
+            flush(run, candidates);
+        }
+    }
+    after
+}
+
+fn flush(run: &mut String, candidates: &mut Vec<Vec<String>>) {
+    if !run.is_empty() {
+        candidates.push(vec![std::mem::take(run)]);
+    }
+}
+
+/// Body of a group whose contents must be present in the line, or `None` for
+/// negative lookarounds and flag groups
+fn group_body(group: &str) -> Option<&str> {
+    let inner = &group[1..group.len() - 1];
+    let Some(rest) = inner.strip_prefix('?') else {
+        return Some(inner);
+    };
+    if let Some(body) = rest
+        .strip_prefix(':')
+        .or_else(|| rest.strip_prefix('='))
+        .or_else(|| rest.strip_prefix("<="))
+    {
+        return Some(body);
+    }
+    // Named group: (?P<name>...) or (?<name>...)
+    let named = rest.strip_prefix('P').unwrap_or(rest).strip_prefix('<')?;
+    if named.starts_with('=') || named.starts_with('!') {
+        return None;
+    }
+    named.find('>').map(|end| &named[end + 1..])
+}
+
+/// Parse an optional quantifier at `i`: whether it allows zero repetitions, and the
+/// offset after it (`i` when there is none)
+fn quantifier(bytes: &[u8], i: usize) -> (bool, usize) {
+    let (optional, mut next) = match bytes.get(i) {
+        Some(b'?') | Some(b'*') => (true, i + 1),
+        Some(b'+') => (false, i + 1),
+        Some(b'{') => {
+            let Some(len) = bytes[i..].iter().position(|&b| b == b'}') else {
+                return (false, i);
+            };
+            let body = &bytes[i + 1..i + len];
+            if body.is_empty() || !body.iter().all(|b| b.is_ascii_digit() || *b == b',') {
+                // Not a quantifier: a literal `{`
+                return (false, i);
+            }
+            let zero = !body[0].is_ascii_digit()
+                || body
+                    .iter()
+                    .take_while(|b| b.is_ascii_digit())
+                    .all(|&b| b == b'0');
+            (zero, i + len + 1)
+        }
+        _ => return (false, i),
+    };
+    // Lazy or possessive suffix
+    if matches!(bytes.get(next), Some(b'?') | Some(b'+')) {
+        next += 1;
+    }
+    (optional, next)
+}
+
+/// Literal character denoted by the escape at `i`, and the offset after it
+fn escaped_literal(pattern: &str, i: usize) -> Option<(char, usize)> {
+    let ch = pattern[i + 1..].chars().next()?;
+    let literal = match ch {
+        't' => '\t',
+        'n' => '\n',
+        'r' => '\r',
+        c if c.is_ascii_alphanumeric() => return None,
+        c => c,
+    };
+    Some((literal, i + 1 + ch.len_utf8()))
+}
+
+/// Offset after a non-literal escape such as `\d`, `\x41`, `\p{L}` or `\12`
+fn skip_escape(bytes: &[u8], i: usize) -> usize {
+    let next = i + 2;
+    match bytes.get(i + 1) {
+        Some(b'x') | Some(b'u') | Some(b'U') | Some(b'p') | Some(b'P')
+            if bytes.get(next) == Some(&b'{') =>
+        {
+            bytes[next..]
+                .iter()
+                .position(|&b| b == b'}')
+                .map_or(bytes.len(), |end| next + end + 1)
+        }
+        Some(b'x') => (next + 2).min(bytes.len()),
+        Some(b'u') => (next + 4).min(bytes.len()),
+        Some(b'U') => (next + 8).min(bytes.len()),
+        Some(b'p') | Some(b'P') => (next + 1).min(bytes.len()),
+        Some(b) if b.is_ascii_digit() => {
+            next + bytes[next..]
+                .iter()
+                .take_while(|b| b.is_ascii_digit())
+                .count()
+        }
+        _ => next.min(bytes.len()),
+    }
+}
+
+/// Split `pattern` at each `|` outside groups and character classes
+fn split_alternatives(pattern: &str) -> Vec<&str> {
+    let bytes = pattern.as_bytes();
+    let mut branches = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' => {
+                i = skip_character_class(bytes, i);
+                continue;
+            }
+            b'(' => depth += 1,
+            b')' => depth = depth.saturating_sub(1),
+            b'|' if depth == 0 => {
+                branches.push(&pattern[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    branches.push(&pattern[start..]);
+    branches
+}
+
+/// Whether `pattern` turns on case-insensitive matching anywhere, e.g. `(?i)` or `(?i:...)`
+fn has_case_insensitive_flag(pattern: &str) -> bool {
+    pattern.match_indices("(?").any(|(i, _)| {
+        let flags: Vec<u8> = pattern[i + 2..]
+            .bytes()
+            .take_while(|b| b.is_ascii_alphabetic() || *b == b'-')
+            .collect();
+        let on = flags.split(|&b| b == b'-').next().unwrap_or(&[]);
+        on.contains(&b'i')
+            && matches!(
+                pattern.as_bytes().get(i + 2 + flags.len()),
+                Some(b')') | Some(b':')
+            )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn required(pattern: &str) -> Option<Vec<String>> {
+        required_literals(pattern)
+    }
+
+    fn literals(items: &[&str]) -> Option<Vec<String>> {
+        Some(items.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[test]
+    fn test_literal_runs() {
+        assert_eq!(required(r"icmp_seq=(\d+)"), literals(&["icmp_seq="]));
+        assert_eq!(required(r"(?<=ttl=)\d+"), literals(&["ttl="]));
+        assert_eq!(required(r"\bDUP!"), literals(&["DUP!"]));
+        // Longest run wins
+        assert_eq!(required(r"ab\s+\d+ seconds"), literals(&[" seconds"]));
+        // Escaped punctuation is literal
+        assert_eq!(required(r"\[(\d+)\]:"), literals(&["]:"]));
+    }
+
+    #[test]
+    fn test_alternations() {
+        assert_eq!(required(r"\s(ERR|ERROR)\b"), literals(&["ERR", "ERROR"]));
+        assert_eq!(
+            required(r"\buser\s+(\w+)|uid=(\d+)|gid=(\d+)"),
+            literals(&["user", "uid=", "gid="])
+        );
+        // A branch without literals means no requirement
+        assert_eq!(required(r"foo|\d+"), None);
+        // An optional group with alternatives does not matter
+        assert_eq!(required(r"(a|\d)?xyz"), literals(&["xyz"]));
+    }
+
+    #[test]
+    fn test_quantifiers() {
+        // Optional characters end the run and are dropped
+        assert_eq!(required(r"colou?r"), literals(&["colo"]));
+        assert_eq!(required(r"abcx*yz"), literals(&["abc"]));
+        assert_eq!(required(r"abcx{0,3}y"), literals(&["abc"]));
+        // Repeated characters are kept, but end the run
+        assert_eq!(required(r"abc+de"), literals(&["abc"]));
+        assert_eq!(required(r"a{2}bcd"), literals(&["bcd"]));
+        // A brace that is not a quantifier is literal
+        assert_eq!(required(r"x{y}"), literals(&["x{y}"]));
+        // Lazy quantifiers
+        assert_eq!(required(r"abc.*?de"), literals(&["abc"]));
+    }
+
+    #[test]
+    fn test_no_requirement() {
+        assert_eq!(required(r"^\d+"), None);
+        assert_eq!(required(r"[a-z]+\s\w"), None);
+        assert_eq!(required(r"(?<!foo)\d+"), None);
+        // Case-insensitive matching cannot use a plain substring search
+        assert_eq!(required(r"(?i)error"), None);
+        assert_eq!(required(r"x(?i:error)"), None);
+        assert_eq!(required(r"(?-i)error"), literals(&["error"]));
+        // Escapes that are not literal characters
+        assert_eq!(required(r"\x41\p{L}\12"), None);
+        assert_eq!(required(r"\x41B"), literals(&["B"]));
+    }
+
+    #[test]
+    fn test_prefilter_candidates() {
+        let rules: Vec<GrcatConfigEntry> = [r"ttl=(\d+)", r"^\d+", r"\s(ERR|WARN)\b"]
+            .iter()
+            .map(|p| GrcatConfigEntry::new(crate::grc::CompiledRegex::new(p).unwrap(), vec![]))
+            .collect();
+        let prefilter = Prefilter::new(&rules);
+        assert_eq!(prefilter.filtered_rules(), 2);
+        assert_eq!(prefilter.requirement(2), Some(vec!["ERR", "WARN"]));
+
+        let mut present = Vec::new();
+        prefilter.scan("64 bytes: ttl=64 WARN", &mut present);
+        assert!(prefilter.may_match(&present, 0));
+        assert!(prefilter.may_match(&present, 1));
+        assert!(prefilter.may_match(&present, 2));
+
+        prefilter.scan("nothing here", &mut present);
+        assert!(!prefilter.may_match(&present, 0));
+        assert!(prefilter.may_match(&present, 1));
+        assert!(!prefilter.may_match(&present, 2));
+
+        // The default prefilter lets everything through
+        assert!(Prefilter::default().may_match(&[], 0));
+    }
+
+    #[test]
+    fn test_scan_finds_overlapping_literals() {
+        let rules: Vec<GrcatConfigEntry> = [r"ERROR:", r"ERR\d", r"ROR"]
+            .iter()
+            .map(|p| GrcatConfigEntry::new(crate::grc::CompiledRegex::new(p).unwrap(), vec![]))
+            .collect();
+        let prefilter = Prefilter::new(&rules);
+
+        let mut present = Vec::new();
+        prefilter.scan("xERROR: ERR", &mut present);
+        assert_eq!(present, vec![true, true, true]);
+        prefilter.scan("ROR", &mut present);
+        assert_eq!(present, vec![false, false, true]);
+    }
+}
@@ -0,0 +1,459 @@
+//! Rule Cache - Parsed Rules Stored on Disk
+//!
+//! Even with lazy compilation, every run parses the text of its conf file. The rule
+//! cache stores the parsed rules of each conf file under `~/.cache/rgrc/rules`: the
+//! pattern (with macros expanded, as written and translated), styles, count, skip
+//! flag, replacement, id, overlay mode, tags, severity and the backend the pattern
+//! compiles for, so the next run decodes them without parsing, translating or
+//! probing engines. The diagnostics found while parsing are stored too, so a cached
+//! config reports the same problems. `include=` directives are kept as such and
+//! resolved by the caller, so editing an included file needs no invalidation.
+//!
+//! An entry is keyed by a hash of the source content, the rgrc version, the regex
+//! engine and the global macros. Editing a file under `~/.config/rgrc` or `share/`
+//! (including `rgrc.defines`), upgrading rgrc or switching `--regex-engine` makes
+//! the entry stale, and it is rebuilt on the next load. The cache is best effort:
+//! when it cannot be read or written (e.g. no HOME or a read-only home) rules are
+//! parsed as usual and only storing them is skipped. Either way every pattern is
+//! compiled once when the entry is built, so rules with an invalid regex are
+//! reported and left out whether or not the cache is writable.
+
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+use crate::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::grc::{
+    CompiledRegex, GrcatConfigEntry, GrcatConfigEntryCount, GrcatConfigItem, GrcatConfigReader,
+    OverlayMode, RegexBackend, Severity,
+};
+use crate::macros::Macros;
+use crate::style::Style;
+
+/// Start of every cache file
+const MAGIC: &[u8; 8] = b"RGRCRULE";
+/// Version of the layout below; bump it when the layout changes
+const FORMAT: u32 = 6;
+/// Backend byte of a rule whose pattern does not compile
+const NO_BACKEND: u8 = u8::MAX;
+/// Tag bytes of the items
+const RULE: u8 = 0;
+const INCLUDE: u8 = 1;
+
+/// Rules and includes of the conf file `source` (a path, or a name for embedded
+/// configs) with the given content, from the cache when it is up to date, and the
+/// problems found in it. Rules with an invalid regex are reported and left out, as
+/// [`GrcatConfigReader`] does when it compiles eagerly.
+pub fn load_items(source: &str, content: &[u8]) -> (Vec<GrcatConfigItem>, Vec<Diagnostic>) {
+    let key = cache_key(content);
+    let path = cache_path(source);
+    let debug = std::env::var_os("RGRC_DEBUG").is_some();
+
+    if let Some(path) = &path
+        && let Ok(bytes) = std::fs::read(path)
+        && let Some(loaded) = decode(&bytes, key, source)
+    {
+        if debug {
+            eprintln!("rgrc: rule cache hit for {} ({})", source, path.display());
+        }
+        return loaded;
+    }
+
+    let mut reader = GrcatConfigReader::lazy(content.lines()).with_source(source);
+    let mut items = Vec::new();
+    let mut invalid = Vec::new();
+    while let Some(item) = reader.next_item() {
+        // Compiling also learns the backend stored in the entry
+        if let GrcatConfigItem::Rule(rule) = &item
+            && let Err(e) = rule.regex.try_backend()
+        {
+            let line = Some(reader.regexp_line());
+            invalid.push(Diagnostic::new(
+                Some(source),
+                line,
+                DiagnosticKind::Regex,
+                e,
+            ));
+            continue;
+        }
+        items.push(item);
+    }
+    let mut diagnostics = reader.take_diagnostics();
+    diagnostics.extend(invalid);
+    diagnostics.sort_by_key(|diagnostic| diagnostic.line);
+    if let Some(path) = &path {
+        let stored = store(path, &encode(key, &items, &diagnostics));
+        if debug {
+            eprintln!(
+                "rgrc: rule cache {} for {} ({})",
+                if stored { "updated" } else { "not writable" },
+                source,
+                path.display()
+            );
+        }
+    }
+    (items, diagnostics)
+}
+
+/// Hash of everything the cached rules depend on, including the global macros
+pub fn cache_key(content: &[u8]) -> u64 {
+    let engine = crate::grc::regex_engine().to_string();
+    let fancy = if cfg!(feature = "fancy-regex") {
+        "fancy"
+    } else {
+        ""
+    };
+    let macros = Macros::global()
+        .iter()
+        .flat_map(|(name, body)| [name.as_bytes(), body.as_bytes()]);
+    [
+        env!("CARGO_PKG_VERSION").as_bytes(),
+        engine.as_bytes(),
+        fancy.as_bytes(),
+        content,
+    ]
+    .into_iter()
+    .chain(macros)
+    .fold(FNV_OFFSET, |hash, part| fnv1a(fnv1a(hash, part), &[0]))
+}
+
+/// Cache file of `source`: `~/.cache/rgrc/rules/<hash of source>.bin`
+///
+/// Unit tests get none, so they never write to the user's cache.
+fn cache_path(source: &str) -> Option<PathBuf> {
+    if cfg!(test) {
+        return None;
+    }
+    let home = std::env::var_os("HOME")?;
+    let name = format!("{:016x}.bin", fnv1a(FNV_OFFSET, source.as_bytes()));
+    Some(
+        PathBuf::from(home)
+            .join(".cache")
+            .join("rgrc")
+            .join("rules")
+            .join(name),
+    )
+}
+
+/// Write `bytes` to `path` through a temporary file, so that concurrent runs never
+/// read a partial entry. Returns whether the entry was written.
+fn store(path: &Path, bytes: &[u8]) -> bool {
+    let Some(dir) = path.parent() else {
+        return false;
+    };
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    let written = std::fs::create_dir_all(dir).is_ok()
+        && std::fs::write(&tmp, bytes).is_ok()
+        && std::fs::rename(&tmp, path).is_ok();
+    if !written {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    written
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// 64-bit FNV-1a, continuing from `hash`; stable across builds, unlike `DefaultHasher`
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Serialize items: header, then for each item a tag byte followed by the name and
+/// line of an include, or by a rule's pattern (as written and translated), backend, styles,
+/// count, skip flag, replacement, id (empty for none), overlay mode, tags and
+/// severity; then the diagnostics as line (0 for none), kind and message. Compiles
+/// the patterns to learn their backend.
+fn encode(key: u64, items: &[GrcatConfigItem], diagnostics: &[Diagnostic]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&FORMAT.to_le_bytes());
+    out.extend_from_slice(&key.to_le_bytes());
+    push_u32(&mut out, items.len());
+    for item in items {
+        let rule = match item {
+            GrcatConfigItem::Rule(rule) => rule,
+            GrcatConfigItem::Include { name, line } => {
+                out.push(INCLUDE);
+                push_str(&mut out, name);
+                push_u32(&mut out, *line);
+                continue;
+            }
+        };
+        out.push(RULE);
+        push_str(&mut out, rule.regex.source());
+        push_str(&mut out, rule.regex.as_str());
+        out.push(match rule.regex.try_backend() {
+            Ok(RegexBackend::Fast) => 0,
+            Ok(RegexBackend::Lowered) => 1,
+            Ok(RegexBackend::Enhanced) => 2,
+            Ok(RegexBackend::Fancy) => 3,
+            Err(_) => NO_BACKEND,
+        });
+        push_u32(&mut out, rule.colors.len());
+        for style in &rule.colors {
+            out.extend_from_slice(&style.to_bits().to_le_bytes());
+        }
+        out.push(match rule.count {
+            GrcatConfigEntryCount::Once => 0,
+            GrcatConfigEntryCount::More => 1,
+            GrcatConfigEntryCount::Stop => 2,
+        });
+        out.push(rule.skip as u8);
+        push_str(&mut out, &rule.replace);
+        push_str(&mut out, rule.id.as_deref().unwrap_or_default());
+        out.push(match rule.overlay {
+            None => 0,
+            Some(OverlayMode::Prepend) => 1,
+            Some(OverlayMode::Append) => 2,
+            Some(OverlayMode::Replace) => 3,
+        });
+        push_u32(&mut out, rule.tags.len());
+        for tag in &rule.tags {
+            push_str(&mut out, tag);
+        }
+        out.push(match rule.severity {
+            None => 0,
+            Some(Severity::Error) => 1,
+            Some(Severity::Warning) => 2,
+            Some(Severity::Info) => 3,
+            Some(Severity::Ok) => 4,
+        });
+    }
+    push_u32(&mut out, diagnostics.len());
+    for diagnostic in diagnostics {
+        push_u32(&mut out, diagnostic.line.unwrap_or_default());
+        out.push(match diagnostic.kind {
+            DiagnosticKind::Io => 0,
+            DiagnosticKind::Regex => 1,
+            DiagnosticKind::Style => 2,
+            DiagnosticKind::Value => 3,
+            DiagnosticKind::Macro => 4,
+            DiagnosticKind::Include => 5,
+            DiagnosticKind::Overlay => 6,
+        });
+        push_str(&mut out, &diagnostic.message);
+    }
+    out
+}
+
+/// Deserialize items and diagnostics (located in `source`) written by [`encode`]
+/// with the same key, or `None` if the entry is stale or malformed
+fn decode(bytes: &[u8], key: u64, source: &str) -> Option<(Vec<GrcatConfigItem>, Vec<Diagnostic>)> {
+    let mut reader = Reader { bytes };
+    if reader.take(MAGIC.len())? != MAGIC || reader.u32()? != FORMAT || reader.u64()? != key {
+        return None;
+    }
+    let count = reader.u32()? as usize;
+    let mut items = Vec::with_capacity(count.min(bytes.len()));
+    for _ in 0..count {
+        match reader.u8()? {
+            RULE => {}
+            INCLUDE => {
+                let name = reader.str()?.to_string();
+                let line = reader.u32()? as usize;
+                items.push(GrcatConfigItem::Include { name, line });
+                continue;
+            }
+            _ => return None,
+        }
+        let (source, translated) = (reader.str()?, reader.str()?);
+        let backend = match reader.u8()? {
+            0 => Some(RegexBackend::Fast),
+            1 => Some(RegexBackend::Lowered),
+            2 => Some(RegexBackend::Enhanced),
+            3 => Some(RegexBackend::Fancy),
+            NO_BACKEND => None,
+            _ => return None,
+        };
+        let regex = CompiledRegex::lazy_translated(source, translated, backend);
+        let colors = (0..reader.u32()?)
+            .map(|_| reader.u32().and_then(Style::from_bits))
+            .collect::<Option<Vec<_>>>()?;
+        let count = match reader.u8()? {
+            0 => GrcatConfigEntryCount::Once,
+            1 => GrcatConfigEntryCount::More,
+            2 => GrcatConfigEntryCount::Stop,
+            _ => return None,
+        };
+        let skip = reader.u8()? != 0;
+        let replace = reader.str()?.to_string();
+        let id = Some(reader.str()?)
+            .filter(|id| !id.is_empty())
+            .map(str::to_string);
+        let overlay = match reader.u8()? {
+            0 => None,
+            1 => Some(OverlayMode::Prepend),
+            2 => Some(OverlayMode::Append),
+            3 => Some(OverlayMode::Replace),
+            _ => return None,
+        };
+        let tags = (0..reader.u32()?)
+            .map(|_| reader.str().map(str::to_string))
+            .collect::<Option<Vec<_>>>()?;
+        let severity = match reader.u8()? {
+            0 => None,
+            1 => Some(Severity::Error),
+            2 => Some(Severity::Warning),
+            3 => Some(Severity::Info),
+            4 => Some(Severity::Ok),
+            _ => return None,
+        };
+        items.push(GrcatConfigItem::Rule(GrcatConfigEntry {
+            regex,
+            colors,
+            skip,
+            count,
+            replace,
+            id,
+            overlay,
+            tags,
+            severity,
+        }));
+    }
+    let count = reader.u32()? as usize;
+    let mut diagnostics = Vec::with_capacity(count.min(bytes.len()));
+    for _ in 0..count {
+        let line = Some(reader.u32()? as usize).filter(|&line| line > 0);
+        let kind = match reader.u8()? {
+            0 => DiagnosticKind::Io,
+            1 => DiagnosticKind::Regex,
+            2 => DiagnosticKind::Style,
+            3 => DiagnosticKind::Value,
+            4 => DiagnosticKind::Macro,
+            5 => DiagnosticKind::Include,
+            6 => DiagnosticKind::Overlay,
+            _ => return None,
+        };
+        let message = reader.str()?;
+        diagnostics.push(Diagnostic::new(Some(source), line, kind, message));
+    }
+    reader.bytes.is_empty().then_some((items, diagnostics))
+}
+
+fn push_u32(out: &mut Vec<u8>, n: usize) {
+    out.extend_from_slice(&(n as u32).to_le_bytes());
+}
+
+fn push_str(out: &mut Vec<u8>, s: &str) {
+    push_u32(out, s.len());
+    out.extend_from_slice(s.as_bytes());
+}
+
+/// Cursor over a cache file; every read fails on truncated input
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let (head, rest) = self.bytes.split_at_checked(n)?;
+        self.bytes = rest;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4)?.try_into().ok().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take(8)?.try_into().ok().map(u64::from_le_bytes)
+    }
+
+    fn str(&mut self) -> Option<&'a str> {
+        let len = self.u32()? as usize;
+        std::str::from_utf8(self.take(len)?).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONF: &str = "regexp=(?<=ttl=)\\d+\ncolours=bold red\ncount=once\n\n\
+                        regexp=^(ERROR|WARN)\\b (.*)$\ncolours=default,yellow on_blue,cyan\nskip=yes\nreplace=\\1\n\
+                        id=level\noverlay=prepend\ntags=log, level\nseverity=warning\n\n\
+                        include=conf.common\n\
+                        regexp=broken(\ncolours=red\ncount=stop\n";
+
+    fn parse(content: &str) -> Vec<GrcatConfigItem> {
+        let mut reader = GrcatConfigReader::lazy(content.as_bytes().lines());
+        std::iter::from_fn(|| reader.next_item()).collect()
+    }
+
+    fn rules(items: &[GrcatConfigItem]) -> Vec<&GrcatConfigEntry> {
+        items
+            .iter()
+            .filter_map(|item| match item {
+                GrcatConfigItem::Rule(rule) => Some(rule),
+                GrcatConfigItem::Include { .. } => None,
+            })
+            .collect()
+    }
+
+    fn summary(items: &[GrcatConfigItem]) -> Vec<String> {
+        items
+            .iter()
+            .map(|item| match item {
+                GrcatConfigItem::Rule(r) => format!(
+                    "{} {:?} {:?} {:?} {} {} {:?} {:?} {:?} {:?}",
+                    r.regex.source(),
+                    r.regex.try_backend().ok(),
+                    r.colors,
+                    r.count,
+                    r.skip,
+                    r.replace,
+                    r.id,
+                    r.overlay,
+                    r.tags,
+                    r.severity
+                ),
+                GrcatConfigItem::Include { name, line } => format!("include {} {}", name, line),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_decode_round_trip() {
+        let key = cache_key(CONF.as_bytes());
+        let parsed = parse(CONF);
+        assert_eq!(parsed.len(), 4);
+
+        let diagnostics = [
+            Diagnostic::new(Some("conf.x"), Some(12), DiagnosticKind::Regex, "unclosed"),
+            Diagnostic::new(Some("conf.x"), None, DiagnosticKind::Value, "unknown"),
+        ];
+        let bytes = encode(key, &parsed, &diagnostics);
+        let (decoded, decoded_diagnostics) = decode(&bytes, key, "conf.x").unwrap();
+        assert_eq!(decoded_diagnostics, diagnostics);
+        assert_eq!(summary(&decoded), summary(&parsed));
+        assert_eq!(summary(&decoded)[2], "include conf.common 14");
+        // Backends are known without compiling, and the invalid pattern still fails
+        let decoded = rules(&decoded);
+        assert_eq!(decoded[0].regex.backend(), RegexBackend::Lowered);
+        assert!(decoded[2].regex.try_is_match("broken(").is_err());
+        assert_eq!(decoded[1].id.as_deref(), Some("level"));
+        assert_eq!(decoded[1].overlay, Some(OverlayMode::Prepend));
+        assert_eq!(decoded[1].tags, ["log", "level"]);
+        assert_eq!(decoded[1].severity, Some(Severity::Warning));
+        let caps = decoded[0].regex.captures_from_pos("ttl=64", 0).unwrap();
+        assert_eq!(caps.get(0).unwrap().as_str(), "64");
+    }
+
+    #[test]
+    fn test_stale_or_malformed_entries_are_rejected() {
+        let key = cache_key(CONF.as_bytes());
+        let bytes = encode(key, &parse(CONF), &[]);
+
+        assert!(decode(&bytes, cache_key(b"regexp=other\n"), "x").is_none());
+        assert!(decode(&bytes[..bytes.len() - 1], key, "x").is_none());
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(decode(&trailing, key, "x").is_none());
+        assert!(decode(b"", key, "x").is_none());
+    }
+}
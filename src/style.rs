@@ -51,6 +51,34 @@ enum Color {
     White,
 }
 
+impl Color {
+    /// Every color, in declaration order
+    const ALL: [Color; 8] = [
+        Color::Black,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::White,
+    ];
+
+    /// Keyword of the color in grcat configs
+    const fn name(self) -> &'static str {
+        match self {
+            Color::Black => "black",
+            Color::Red => "red",
+            Color::Green => "green",
+            Color::Yellow => "yellow",
+            Color::Blue => "blue",
+            Color::Magenta => "magenta",
+            Color::Cyan => "cyan",
+            Color::White => "white",
+        }
+    }
+}
+
 impl Style {
     /// Create a new empty style with no formatting
     #[inline]
@@ -216,6 +244,124 @@ impl Style {
         StyledText { text, style: *self }
     }
 
+    /// Pack this style into an integer, e.g. to store it in the rule cache.
+    ///
+    /// Bits 0-3 hold the foreground color (0 = none), bits 4-7 the background
+    /// color and bits 8-14 the attributes.
+    #[allow(dead_code)]
+    pub fn to_bits(self) -> u32 {
+        let color = |c: Option<Color>| c.map_or(0, |c| c as u32 + 1);
+        let flags = [
+            self.bold,
+            self.dim,
+            self.underlined,
+            self.italic,
+            self.blink,
+            self.reverse,
+            self.bright,
+        ];
+        let flags = flags
+            .iter()
+            .enumerate()
+            .fold(0, |bits, (i, &set)| bits | ((set as u32) << i));
+        color(self.fg_color) | (color(self.bg_color) << 4) | (flags << 8)
+    }
+
+    /// Unpack a style packed by [`to_bits`](Self::to_bits), or `None` if `bits`
+    /// does not come from a style.
+    #[allow(dead_code)]
+    pub fn from_bits(bits: u32) -> Option<Self> {
+        let color = |n: u32| match n {
+            0 => Some(None),
+            n => Color::ALL.get(n as usize - 1).copied().map(Some),
+        };
+        if bits >> 15 != 0 {
+            return None;
+        }
+        let flag = |i: u32| bits & (1 << (8 + i)) != 0;
+        Some(Style {
+            fg_color: color(bits & 0xf)?,
+            bg_color: color((bits >> 4) & 0xf)?,
+            bold: flag(0),
+            dim: flag(1),
+            underlined: flag(2),
+            italic: flag(3),
+            blink: flag(4),
+            reverse: flag(5),
+            bright: flag(6),
+        })
+    }
+
+    /// The grcat keywords of this style, such as `bold bright_red on_blue`, or
+    /// `default` when it has no formatting. Parsing them gives this style back, except
+    /// for `bright` without a foreground color, which has no keyword.
+    pub fn keywords(self) -> String {
+        let attributes = [
+            (self.bold, "bold"),
+            (self.dim, "dim"),
+            (self.underlined, "underline"),
+            (self.italic, "italic"),
+            (self.blink, "blink"),
+            (self.reverse, "reverse"),
+        ];
+        let mut words: Vec<String> = attributes
+            .iter()
+            .filter(|(set, _)| *set)
+            .map(|(_, word)| word.to_string())
+            .collect();
+        if let Some(fg) = self.fg_color {
+            let bright = if self.bright { "bright_" } else { "" };
+            words.push(format!("{}{}", bright, fg.name()));
+        }
+        if let Some(bg) = self.bg_color {
+            words.push(format!("on_{}", bg.name()));
+        }
+        if words.is_empty() {
+            "default".to_string()
+        } else {
+            words.join(" ")
+        }
+    }
+
+    /// This style after an SGR escape such as `\x1b[1;31m`, given its parameters
+    /// (`1;31`). `0` or no parameter resets it; codes rgrc never writes are ignored.
+    #[allow(dead_code)]
+    pub fn with_sgr(self, params: &str) -> Self {
+        params.split(';').fold(self, |style, code| {
+            let code: usize = match code {
+                "" => 0,
+                code => match code.parse() {
+                    Ok(code) => code,
+                    Err(_) => return style,
+                },
+            };
+            match code {
+                0 => Style::new(),
+                1 => style.bold(),
+                2 => style.dim(),
+                3 => style.italic(),
+                4 => style.underlined(),
+                5 => style.blink(),
+                7 => style.reverse(),
+                30..=37 => Style {
+                    fg_color: Some(Color::ALL[code - 30]),
+                    bright: false,
+                    ..style
+                },
+                90..=97 => Style {
+                    fg_color: Some(Color::ALL[code - 90]),
+                    bright: true,
+                    ..style
+                },
+                40..=47 => Style {
+                    bg_color: Some(Color::ALL[code - 40]),
+                    ..style
+                },
+                _ => style,
+            }
+        })
+    }
+
     /// Generate ANSI escape codes for this style
     fn to_ansi_codes(self) -> String {
         if self.is_empty() {
@@ -300,6 +446,50 @@ impl Style {
     }
 }
 
+/// `text` with its ANSI styles written out as keywords, e.g.
+/// `[bold red]ERROR[/]: disk full` for `\x1b[1;31mERROR\x1b[0m: disk full`, so that
+/// colorized output can be read and compared. Text with the same style is one span
+/// however the escapes split it; other escapes are kept as they are.
+#[allow(dead_code)]
+pub fn ansi_to_markup(text: &str) -> String {
+    let mut spans: Vec<(Style, String)> = Vec::new();
+    let mut push = |style: Style, text: &str| match spans.last_mut() {
+        Some((last, span)) if *last == style => span.push_str(text),
+        _ if text.is_empty() => {}
+        _ => spans.push((style, text.to_string())),
+    };
+
+    let mut style = Style::new();
+    let mut rest = text;
+    while let Some(esc) = rest.find("\x1b[") {
+        push(style, &rest[..esc]);
+        let after = &rest[esc + 2..];
+        let params = after
+            .bytes()
+            .take_while(|b| b.is_ascii_digit() || *b == b';')
+            .count();
+        if after[params..].starts_with('m') {
+            style = style.with_sgr(&after[..params]);
+            rest = &after[params + 1..];
+        } else {
+            push(style, "\x1b[");
+            rest = after;
+        }
+    }
+    push(style, rest);
+
+    spans
+        .iter()
+        .map(|(style, text)| {
+            if style.is_empty() {
+                text.clone()
+            } else {
+                format!("[{}]{}[/]", style.keywords(), text)
+            }
+        })
+        .collect()
+}
+
 /// Wrapper for styled text that implements Display
 pub struct StyledText<'a> {
     text: &'a str,
@@ -394,4 +584,72 @@ mod tests {
         let styled = style.apply_to("hello");
         assert_eq!(format!("{}", styled), "hello");
     }
+
+    #[test]
+    fn test_bits_round_trip() {
+        for style in [
+            Style::new(),
+            Style::new().red().bold(),
+            Style::new().white().on_blue().underlined().bright(),
+            Style::new().on_black().dim().italic().blink().reverse(),
+        ] {
+            assert_eq!(Style::from_bits(style.to_bits()), Some(style));
+        }
+        assert_eq!(Style::from_bits(9), None);
+        assert_eq!(Style::from_bits(1 << 15), None);
+    }
+
+    #[test]
+    fn test_sgr_round_trip() {
+        for style in [
+            Style::new().red().bold(),
+            Style::new().bright().white().on_blue().underlined(),
+            Style::new().on_black().dim().italic().blink().reverse(),
+        ] {
+            let codes = style.to_ansi_codes();
+            let params = &codes[2..codes.len() - 1];
+            assert_eq!(Style::new().with_sgr(params), style);
+        }
+        assert_eq!(Style::new().red().with_sgr("0"), Style::new());
+        assert_eq!(Style::new().red().with_sgr(""), Style::new());
+    }
+
+    #[test]
+    fn test_ansi_to_markup() {
+        let red = Style::new().red();
+        let line = format!(
+            "{}{}: {} x",
+            red.apply_to("ER"),
+            red.apply_to("ROR"),
+            Style::new().bold().bright().green().apply_to("ok")
+        );
+        assert_eq!(
+            ansi_to_markup(&line),
+            "[red]ERROR[/]: [bold bright_green]ok[/] x"
+        );
+        assert_eq!(ansi_to_markup("plain \x1b[2K"), "plain \x1b[2K");
+    }
+
+    #[test]
+    fn test_keywords_parse_back() {
+        for style in [
+            Style::new(),
+            Style::new().red().bold(),
+            Style::new().bright().white().on_blue().underlined(),
+            Style::new().on_black().dim().italic().blink().reverse(),
+        ] {
+            let keywords = style.keywords();
+            assert_eq!(
+                crate::grc::style_from_str(&keywords),
+                Ok(style),
+                "{}",
+                keywords
+            );
+        }
+        assert_eq!(Style::new().keywords(), "default");
+        assert_eq!(
+            Style::new().bright().red().bold().on_cyan().keywords(),
+            "bold bright_red on_cyan"
+        );
+    }
 }
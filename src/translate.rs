@@ -0,0 +1,635 @@
+//! Python `re` Compatibility - Translation Layer for grc Patterns
+//!
+//! Upstream grc configuration files are written for Python's `re` module. Most of
+//! that syntax is shared with the engines used by rgrc, but a few constructs are
+//! rejected by `regex-lite` or, worse, accepted with a different meaning. [`translate`]
+//! rewrites them before a pattern is compiled and records every rewrite, so configs
+//! from grc's repository can be used unchanged and users can see what was changed.
+//!
+//! ## Rewrites
+//!
+//! | Python                 | Rewritten to         | Reason                                           |
+//! |------------------------|----------------------|--------------------------------------------------|
+//! | `(?P=name)`            | `\N`                 | named backreference (N = number of group `name`) |
+//! | `\Z`                   | `\z`                 | end of text                                      |
+//! | `(?x)`                 | expanded             | verbose mode: whitespace and `#` comments removed |
+//! | `(?#...)`              | removed              | inline comment                                   |
+//! | `(?a)`, `(?u)`, `(?L)` | removed              | ASCII/Unicode/locale flags                       |
+//! | `\<`, `\>`             | `<`, `>`             | literals in Python, word boundaries in regex-lite |
+//! | `{,n}`                 | `{0,n}`              | omitted lower bound                              |
+//! | `\0`, `\0NN`           | `\x00`, `\xHH`       | octal escapes                                    |
+//! | `[` inside a class     | `\[`                 | literal in Python, nested class in regex-lite    |
+//! | `[...\b...]`           | `(?:[...]\|\b)`      | grc configs use `[:\b]` for "colon or boundary"  |
+//!
+//! Named groups `(?P<name>...)` are kept, except in a pattern with a backreference:
+//! fancy-regex rejects numbered backreferences next to named groups, so there they
+//! become plain groups (`(?P<name>` -> `(`) with the same numbers.
+//!
+//! Translation is idempotent: translating an already translated pattern is a no-op,
+//! so it is safe for both [`CompiledRegex`](crate::grc::CompiledRegex) and
+//! [`EnhancedRegex`](crate::enhanced_regex::EnhancedRegex) to apply it.
+
+use std::fmt;
+
+/// A single rewrite performed by [`translate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rewrite {
+    /// Byte offset of the rewritten construct in the original pattern
+    pub position: usize,
+    /// Original text
+    pub from: String,
+    /// Replacement text (empty when the construct was removed)
+    pub to: String,
+    /// Short explanation of the rewrite
+    pub reason: &'static str,
+}
+
+impl fmt::Display for Rewrite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.to.is_empty() {
+            write!(
+                f,
+                "`{}` removed at {} ({})",
+                self.from, self.position, self.reason
+            )
+        } else {
+            write!(
+                f,
+                "`{}` -> `{}` at {} ({})",
+                self.from, self.to, self.position, self.reason
+            )
+        }
+    }
+}
+
+/// Result of [`translate`]: the rewritten pattern and what was changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Translation {
+    pub pattern: String,
+    pub rewrites: Vec<Rewrite>,
+}
+
+impl Translation {
+    /// Whether the pattern was changed at all.
+    #[allow(dead_code)]
+    pub fn is_rewritten(&self) -> bool {
+        !self.rewrites.is_empty()
+    }
+}
+
+/// Translate Python `re` syntax into syntax accepted by every rgrc regex backend.
+///
+/// Patterns that use no Python-only syntax are returned unchanged with no rewrites.
+pub fn translate(pattern: &str) -> Translation {
+    let mut translator = Translator {
+        src: pattern,
+        out: String::with_capacity(pattern.len()),
+        rewrites: Vec::new(),
+        verbose: false,
+        groups: 0,
+        names: Vec::new(),
+        unname_groups: has_backreference(pattern),
+    };
+    translator.run();
+    Translation {
+        pattern: translator.out,
+        rewrites: translator.rewrites,
+    }
+}
+
+struct Translator<'a> {
+    src: &'a str,
+    out: String,
+    rewrites: Vec<Rewrite>,
+    /// Inside `(?x)` verbose mode
+    verbose: bool,
+    /// Number of capture groups opened so far
+    groups: usize,
+    /// Named groups seen so far with their group number
+    names: Vec<(&'a str, usize)>,
+    /// Turn named groups into plain ones, as the pattern has a backreference
+    unname_groups: bool,
+}
+
+impl<'a> Translator<'a> {
+    fn run(&mut self) {
+        let mut i = 0;
+        while i < self.src.len() {
+            let c = self.char_at(i);
+            i = match c {
+                '\\' => self.escape(i),
+                '[' => self.class(i),
+                '(' => self.group(i),
+                '{' => self.quantifier(i),
+                '#' if self.verbose => self.src[i..]
+                    .find('\n')
+                    .map_or(self.src.len(), |end| i + end + 1),
+                c if self.verbose && c.is_whitespace() => i + c.len_utf8(),
+                c => {
+                    self.out.push(c);
+                    i + c.len_utf8()
+                }
+            };
+        }
+    }
+
+    fn char_at(&self, i: usize) -> char {
+        self.src[i..].chars().next().unwrap_or('\0')
+    }
+
+    fn rewrite(&mut self, position: usize, from: &str, to: &str, reason: &'static str) {
+        self.rewrites.push(Rewrite {
+            position,
+            from: from.to_string(),
+            to: to.to_string(),
+            reason,
+        });
+    }
+
+    /// Translate the escape sequence at `i` (outside a character class)
+    fn escape(&mut self, i: usize) -> usize {
+        let Some(next) = self.src[i + 1..].chars().next() else {
+            self.out.push('\\');
+            return i + 1;
+        };
+        match next {
+            'Z' => {
+                self.out.push_str("\\z");
+                self.rewrite(i, "\\Z", "\\z", "Python end-of-text anchor");
+                i + 2
+            }
+            '<' | '>' => {
+                self.out.push(next);
+                let from = &self.src[i..i + 2];
+                self.rewrite(i, from, &from[1..], "literal in Python, word boundary here");
+                i + 2
+            }
+            '0' => self.octal(i),
+            _ => {
+                self.out.push('\\');
+                self.out.push(next);
+                i + 1 + next.len_utf8()
+            }
+        }
+    }
+
+    /// Translate an octal escape `\0`, `\0N` or `\0NN` at `i` into a hex escape
+    fn octal(&mut self, i: usize) -> usize {
+        let digits = self.src.as_bytes()[i + 2..]
+            .iter()
+            .take(2)
+            .take_while(|b| matches!(b, b'0'..=b'7'))
+            .count();
+        let end = i + 2 + digits;
+        let value = u8::from_str_radix(&self.src[i + 1..end], 8).unwrap_or(0);
+        let to = format!("\\x{:02X}", value);
+        self.out.push_str(&to);
+        let from = &self.src[i..end];
+        self.rewrite(i, from, &to, "octal escape");
+        end
+    }
+
+    /// Translate the character class starting at `i`
+    fn class(&mut self, i: usize) -> usize {
+        let Some(end) = class_end(self.src, i) else {
+            // Unterminated class: leave it for the regex engine to report
+            self.out.push_str(&self.src[i..]);
+            return self.src.len();
+        };
+
+        let original = &self.src[i..=end];
+        let bytes = self.src.as_bytes();
+        let mut body = String::with_capacity(end - i);
+        let mut boundary = false;
+        let mut reasons = Vec::new();
+        let mut j = i + 1;
+
+        // A leading `^` and/or `]` belong to the class literally
+        if bytes[j] == b'^' {
+            body.push('^');
+            j += 1;
+        }
+        if bytes[j] == b']' {
+            body.push(']');
+            j += 1;
+        }
+
+        while j < end {
+            let c = self.char_at(j);
+            match c {
+                '\\' => {
+                    let next = self.char_at(j + 1);
+                    match next {
+                        '<' | '>' => {
+                            body.push(next);
+                            reasons.push("literal in Python, invalid in a class here");
+                        }
+                        'b' => {
+                            boundary = true;
+                            reasons.push("word boundary inside a class");
+                        }
+                        _ => {
+                            body.push('\\');
+                            body.push(next);
+                        }
+                    }
+                    j += 1 + next.len_utf8();
+                }
+                '[' if bytes.get(j + 1) == Some(&b':') => {
+                    // POSIX class such as [:alpha:] is kept as-is
+                    let close = self.src[j..end].find(":]").map_or(end, |p| j + p + 2);
+                    body.push_str(&self.src[j..close]);
+                    j = close;
+                }
+                '[' => {
+                    body.push_str("\\[");
+                    reasons.push("literal in Python, nested class here");
+                    j += 1;
+                }
+                c => {
+                    body.push(c);
+                    j += c.len_utf8();
+                }
+            }
+        }
+
+        let translated = if !boundary {
+            format!("[{}]", body)
+        } else if body.is_empty() {
+            "\\b".to_string()
+        } else {
+            format!("(?:[{}]|\\b)", body)
+        };
+        self.out.push_str(&translated);
+        if translated != original {
+            let reason = if boundary {
+                "word boundary inside a class"
+            } else {
+                reasons[0]
+            };
+            self.rewrite(i, original, &translated, reason);
+        }
+        end + 1
+    }
+
+    /// Translate the group opening at `i`
+    fn group(&mut self, i: usize) -> usize {
+        let rest = &self.src[i..];
+
+        if !rest.starts_with("(?") {
+            self.groups += 1;
+            self.out.push('(');
+            return i + 1;
+        }
+
+        // Inline comment
+        if rest.starts_with("(?#") {
+            let end = rest.find(')').map_or(self.src.len(), |p| i + p + 1);
+            let from = &self.src[i..end];
+            self.rewrite(i, from, "", "inline comment");
+            return end;
+        }
+
+        // Named backreference
+        if let Some(name_start) = rest.strip_prefix("(?P=")
+            && let Some(len) = name_start.find(')')
+        {
+            let name = &name_start[..len];
+            let end = i + 4 + len + 1;
+            if let Some(&(_, group)) = self.names.iter().find(|(n, _)| *n == name) {
+                let to = format!("\\{}", group);
+                self.out.push_str(&to);
+                let from = &self.src[i..end];
+                self.rewrite(i, from, &to, "Python named backreference");
+            } else {
+                // Unknown group: leave it for the regex engine to report
+                self.out.push_str(&self.src[i..end]);
+            }
+            return end;
+        }
+
+        // Named group, kept as-is
+        let name_offset = if rest.starts_with("(?P<") {
+            Some(4)
+        } else if rest.starts_with("(?<") && !rest.starts_with("(?<=") && !rest.starts_with("(?<!")
+        {
+            Some(3)
+        } else {
+            None
+        };
+        if let Some(offset) = name_offset
+            && let Some(len) = rest[offset..].find('>')
+        {
+            self.groups += 1;
+            self.names
+                .push((&self.src[i + offset..i + offset + len], self.groups));
+            let end = i + offset + len + 1;
+            if self.unname_groups {
+                self.out.push('(');
+                let from = &self.src[i..end];
+                self.rewrite(
+                    i,
+                    from,
+                    "(",
+                    "named group in a pattern with a backreference",
+                );
+            } else {
+                self.out.push_str(&self.src[i..end]);
+            }
+            return end;
+        }
+
+        // Flag group: (?flags) or (?flags:...)
+        let flags_len = rest[2..]
+            .bytes()
+            .take_while(|b| b.is_ascii_alphabetic() || *b == b'-')
+            .count();
+        let terminator = rest.as_bytes().get(2 + flags_len).copied();
+        if flags_len > 0 && matches!(terminator, Some(b')') | Some(b':')) {
+            return self.flags(i, &rest[2..2 + flags_len], terminator == Some(b')'));
+        }
+
+        self.out.push_str("(?");
+        i + 2
+    }
+
+    /// Translate the flag group at `i` with the given flag letters
+    fn flags(&mut self, i: usize, flags: &str, global: bool) -> usize {
+        let end = i + 2 + flags.len() + 1;
+        let mut kept = String::new();
+        let mut reason = None;
+        for flag in flags.chars() {
+            match flag {
+                // Python-only ASCII/Unicode/locale flags
+                'a' | 'u' | 'L' => {
+                    reason.get_or_insert("ASCII/Unicode/locale flag");
+                }
+                // Global verbose mode is expanded here
+                'x' if global => {
+                    self.verbose = true;
+                    reason = Some("verbose mode expanded (whitespace and comments removed)");
+                }
+                _ => kept.push(flag),
+            }
+        }
+        let from = &self.src[i..end];
+        let Some(reason) = reason else {
+            self.out.push_str(from);
+            return end;
+        };
+
+        let kept = if kept.trim_matches('-').is_empty() {
+            ""
+        } else {
+            kept.as_str()
+        };
+        let to = match (kept.is_empty(), global) {
+            (true, true) => String::new(),
+            (true, false) => "(?:".to_string(),
+            (false, true) => format!("(?{})", kept),
+            (false, false) => format!("(?{}:", kept),
+        };
+        self.out.push_str(&to);
+        self.rewrite(i, from, &to, reason);
+        end
+    }
+
+    /// Translate `{,n}` at `i`, copying any other brace as-is
+    fn quantifier(&mut self, i: usize) -> usize {
+        let rest = &self.src[i..];
+        if let Some(upper) = rest.strip_prefix("{,")
+            && let Some(len) = upper.find('}')
+            && len > 0
+            && upper[..len].bytes().all(|b| b.is_ascii_digit())
+        {
+            let end = i + 2 + len + 1;
+            let to = format!("{{0,{}}}", &upper[..len]);
+            self.out.push_str(&to);
+            let from = &self.src[i..end];
+            self.rewrite(i, from, &to, "omitted lower bound");
+            return end;
+        }
+        self.out.push('{');
+        i + 1
+    }
+}
+
+/// Byte index of the `]` closing the class that starts at `start`
+/// Whether a pattern has a backreference, `\N` or `(?P=name)`, outside classes
+fn has_backreference(src: &str) -> bool {
+    let bytes = src.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if matches!(bytes.get(i + 1), Some(b'1'..=b'9')) => return true,
+            b'\\' => i += 2,
+            b'[' => i = class_end(src, i).map_or(bytes.len(), |end| end + 1),
+            b'(' if src[i..].starts_with("(?P=") => return true,
+            _ => i += 1,
+        }
+    }
+    false
+}
+
+fn class_end(src: &str, start: usize) -> Option<usize> {
+    let bytes = src.as_bytes();
+    let mut j = start + 1;
+    if bytes.get(j) == Some(&b'^') {
+        j += 1;
+    }
+    if bytes.get(j) == Some(&b']') {
+        j += 1;
+    }
+    while j < bytes.len() {
+        match bytes[j] {
+            b'\\' => j += 2,
+            b'[' if bytes.get(j + 1) == Some(&b':') => {
+                j = src[j..].find(":]").map_or(j + 1, |p| j + p + 2);
+            }
+            b']' => return Some(j),
+            _ => j += 1,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enhanced_regex::EnhancedRegex;
+
+    fn translated(pattern: &str) -> String {
+        translate(pattern).pattern
+    }
+
+    #[test]
+    fn test_plain_pattern_unchanged() {
+        for pattern in [
+            r"^\s*(\d+)\s+(?P<name>\w+)",
+            r"(?<=\s)latest(?=\s+)",
+            r"[\[{,]\s*(-?\d+\.?\d*)",
+            r"(?i)error|warn",
+            r"\d{1,3}(\.\d{1,3}){3}",
+        ] {
+            let translation = translate(pattern);
+            assert_eq!(translation.pattern, pattern);
+            assert!(!translation.is_rewritten());
+        }
+    }
+
+    #[test]
+    fn test_invalid_escapes_outside_char_class() {
+        assert_eq!(translated(r"^\>"), r"^>");
+        assert_eq!(translated(r"^\<"), r"^<");
+    }
+
+    #[test]
+    fn test_character_class_escapes() {
+        assert_eq!(translated(r"[^\>]"), r"[^>]");
+        assert_eq!(translated(r"[^\<]"), r"[^<]");
+    }
+
+    #[test]
+    fn test_boundary_in_character_class() {
+        assert_eq!(translated(r"[:\b]"), r"(?:[:]|\b)");
+        assert_eq!(translated(r"[Ww]arning[:\b]"), r"[Ww]arning(?:[:]|\b)");
+    }
+
+    #[test]
+    fn test_complex_pattern_diff() {
+        // Pattern from conf.diff: ^\>([^\>].*|$)
+        assert_eq!(translated(r"^\>([^\>].*|$)"), r"^>([^>].*|$)");
+
+        let re = EnhancedRegex::new(r"^\>([^\>].*|$)").unwrap();
+        assert!(re.is_match(">test"));
+        assert!(re.is_match(">"));
+    }
+
+    #[test]
+    fn test_complex_pattern_gcc() {
+        // Pattern from conf.gcc: [Ww]arning[:\b]
+        assert_eq!(translated(r"[Ww]arning[:\b]"), r"[Ww]arning(?:[:]|\b)");
+
+        let re = EnhancedRegex::new(r"[Ww]arning[:\b]").unwrap();
+        assert!(re.is_match("warning:"));
+        assert!(re.is_match("Warning:"));
+        // Will also match "warning" or "Warning" due to the alternation
+        assert!(re.is_match("warning"));
+    }
+
+    #[test]
+    fn test_multiple_escapes() {
+        assert_eq!(translated(r"^\>.*?\<"), r"^>.*?<");
+    }
+
+    #[test]
+    fn test_nested_character_classes() {
+        assert_eq!(translated(r"[a\>b][c\<d]"), r"[a>b][c<d]");
+        // A `[` inside a class is a literal in Python
+        assert_eq!(translated(r"[[(]"), r"[\[(]");
+        // POSIX classes and a leading `]` are kept
+        assert_eq!(translated(r"[[:alpha:]_]"), r"[[:alpha:]_]");
+        assert_eq!(translated(r"[]\>]"), r"[]>]");
+    }
+
+    #[test]
+    fn test_named_backreference() {
+        let translation = translate(r"(\d+) (?P<word>\w+) (?P=word)");
+        assert_eq!(translation.pattern, r"(\d+) (\w+) \2");
+        assert_eq!(translation.rewrites.len(), 2);
+        assert_eq!(translation.rewrites[0].from, "(?P<word>");
+        assert_eq!(translation.rewrites[0].to, "(");
+        assert_eq!(translation.rewrites[1].position, 20);
+        assert_eq!(translation.rewrites[1].from, "(?P=word)");
+
+        // Lookbehinds are not groups
+        assert_eq!(translated(r"(?<=x)(?<tag>a)(?P=tag)"), r"(?<=x)(a)\1");
+        // A numbered backreference also unnames the groups
+        assert_eq!(translated(r"(?P<q>['])\w+\1"), r"(['])\w+\1");
+        // Without a backreference named groups are kept
+        assert_eq!(translated(r"(?P<n>\d+)[\1]"), r"(?P<n>\d+)[\1]");
+        // Unknown names are left for the engine to report
+        assert_eq!(translated(r"(?P=nope)"), r"(?P=nope)");
+    }
+
+    #[cfg(feature = "fancy-regex")]
+    #[test]
+    fn test_named_backreference_with_fancy_regex() {
+        // fancy-regex rejects numbered backreferences next to named groups
+        let re = fancy_regex::Regex::new(&translated(r"^(?P<word>\w+) (?P=word)\Z")).unwrap();
+        assert!(re.is_match("hello hello").unwrap());
+        assert!(!re.is_match("hello world").unwrap());
+
+        let re = fancy_regex::Regex::new(&translated(r"(?P<q>['])\w+\1")).unwrap();
+        assert!(re.is_match("'hi'").unwrap());
+        assert!(!re.is_match("'hi\"").unwrap());
+    }
+
+    #[test]
+    fn test_end_of_text_anchor() {
+        assert_eq!(translated(r"done\Z"), r"done\z");
+        // An escaped backslash followed by Z is not an anchor
+        assert_eq!(translated(r"\\Z"), r"\\Z");
+    }
+
+    #[test]
+    fn test_verbose_mode() {
+        let translation =
+            translate("(?x) ^ (\\d+)  # number\n  \\s+ [a b]  # class keeps spaces\n");
+        assert_eq!(translation.pattern, r"^(\d+)\s+[a b]");
+        assert_eq!(translation.rewrites.len(), 1);
+        assert_eq!(translation.rewrites[0].from, "(?x)");
+
+        // Other flags are kept
+        assert_eq!(translated("(?ix) a b"), "(?i)ab");
+        // Escaped whitespace and `#` stay
+        assert_eq!(translated(r"(?x)a\ b\#c"), r"a\ b\#c");
+    }
+
+    #[test]
+    fn test_python_only_flags_and_comments() {
+        assert_eq!(translated(r"(?u)\w+"), r"\w+");
+        assert_eq!(translated(r"(?ai)\w+"), r"(?i)\w+");
+        assert_eq!(translated(r"(?u:\w+)"), r"(?:\w+)");
+        assert_eq!(translated(r"a(?#comment)b"), r"ab");
+        // Flags supported everywhere are kept
+        assert_eq!(translated(r"(?s:.)"), r"(?s:.)");
+    }
+
+    #[test]
+    fn test_quantifier_and_octal() {
+        assert_eq!(translated(r"a{,3}"), r"a{0,3}");
+        assert_eq!(translated(r"a{2,}"), r"a{2,}");
+        assert_eq!(translated(r"\{,3}"), r"\{,3}");
+        assert_eq!(translated(r"\0"), r"\x00");
+        assert_eq!(translated(r"\033\[0m"), r"\x1B\[0m");
+    }
+
+    #[test]
+    fn test_translation_is_idempotent() {
+        for pattern in [
+            r"^\>([^\>].*|$)",
+            r"[Ww]arning[:\b]",
+            "(?x) (?P<a>\\d)  (?P=a) \\Z",
+            r"a{,3}\0[[]",
+        ] {
+            let once = translated(pattern);
+            let twice = translate(&once);
+            assert_eq!(twice.pattern, once);
+            assert!(!twice.is_rewritten(), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn test_rewrite_display() {
+        let translation = translate(r"a\Z(?#x)");
+        assert_eq!(
+            translation.rewrites[0].to_string(),
+            r"`\Z` -> `\z` at 1 (Python end-of-text anchor)"
+        );
+        assert_eq!(
+            translation.rewrites[1].to_string(),
+            "`(?#x)` removed at 3 (inline comment)"
+        );
+    }
+}
@@ -4,7 +4,7 @@ fn test_generated_embedded_configs_consistency() {
     use std::collections::HashSet;
 
     // Access constants from the library
-    use rgrc::{EMBEDDED_CONFIG_NAMES, EMBEDDED_CONFIGS};
+    use rgrc::{EMBEDDED_CONFIG_NAMES, embedded_configs};
 
     // Basic assertions
     assert!(
@@ -12,14 +12,14 @@ fn test_generated_embedded_configs_consistency() {
         "EMBEDDED_CONFIG_NAMES should not be empty"
     );
     assert_eq!(
-        EMBEDDED_CONFIGS.len(),
+        embedded_configs().count(),
         EMBEDDED_CONFIG_NAMES.len(),
         "Names list and configs length should match"
     );
 
     let names: HashSet<&str> = EMBEDDED_CONFIG_NAMES.iter().copied().collect();
 
-    for (name, content) in EMBEDDED_CONFIGS.iter() {
+    for (name, content) in embedded_configs() {
         assert!(
             names.contains(name),
             "Config name {} not in names list",
@@ -38,3 +38,29 @@ fn test_generated_embedded_configs_consistency() {
         "Expected conf.ping to be embedded"
     );
 }
+
+#[cfg(feature = "embed-configs")]
+#[test]
+fn test_embedded_bundle_round_trips_share_directory() {
+    use rgrc::{EMBEDDED_CONFIG_NAMES, embedded_config};
+
+    let mut on_disk: Vec<String> = std::fs::read_dir("share")
+        .unwrap()
+        .filter_map(|e| e.ok()?.file_name().into_string().ok())
+        .filter(|name| name.starts_with("conf."))
+        .collect();
+    on_disk.sort();
+    assert_eq!(EMBEDDED_CONFIG_NAMES, on_disk.as_slice());
+
+    // Every config decompresses to the file it was built from
+    for name in EMBEDDED_CONFIG_NAMES {
+        let expected = std::fs::read_to_string(format!("share/{}", name)).unwrap();
+        assert_eq!(
+            embedded_config(name).as_deref(),
+            Some(expected.as_str()),
+            "{}",
+            name
+        );
+    }
+    assert!(embedded_config("conf.does-not-exist").is_none());
+}
@@ -28,6 +28,7 @@ fn timetrace_env_var_handling() {
         count: GrcatConfigEntryCount::More,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     let result = run_colorize("test line\ntest", rules);
@@ -48,6 +49,7 @@ fn replace_with_backrefs_modifies_line() {
         count: GrcatConfigEntryCount::More,
         replace: "\\1=\\2".to_string(), // Replace with = separator
         skip: false,
+        ..Default::default()
     }];
 
     let result = run_colorize("server:8080 test", rules);
@@ -67,6 +69,7 @@ fn replace_breaks_outer_loop_and_restarts() {
         count: GrcatConfigEntryCount::More,
         replace: "\\1_\\2".to_string(), // Replace dot with underscore
         skip: false,
+        ..Default::default()
     }];
 
     let result = run_colorize("version 1.2.3 test", rules);
@@ -86,6 +89,7 @@ fn zero_width_lookahead_prevents_infinite_loop() {
         count: GrcatConfigEntryCount::More,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     // This should complete without infinite loop (offset+=1 on zero-width)
@@ -104,6 +108,7 @@ fn word_boundary_zero_width_advances_correctly() {
         count: GrcatConfigEntryCount::More,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     let result = run_colorize("one two three", rules);
@@ -121,6 +126,7 @@ fn style_range_bounds_check_prevents_panic() {
         count: GrcatConfigEntryCount::More,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     // Short line to test bounds checking
@@ -137,6 +143,7 @@ fn run_length_encoding_merges_consecutive_same_style() {
         count: GrcatConfigEntryCount::More,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     // Multiple digit sequences should each be styled as one segment
@@ -154,6 +161,7 @@ fn final_segment_output_for_partial_styling() {
         count: GrcatConfigEntryCount::Once,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     // Only "hello" is styled, " world" should still be output
@@ -171,6 +179,7 @@ fn cache_optimization_skips_overlapping_regions() {
         count: GrcatConfigEntryCount::More,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     // Multiple matches should use cache optimization to skip redundant checks
@@ -190,6 +199,7 @@ fn capture_group_index_out_of_colors_bounds() {
         count: GrcatConfigEntryCount::More,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     let result = run_colorize("time 12:34:56 test", rules);
@@ -206,6 +216,7 @@ fn last_end_tracking_updates_correctly() {
         count: GrcatConfigEntryCount::More,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     // Multiple word matches should update last_end progressively
@@ -223,6 +234,7 @@ fn count_once_matches_only_first_occurrence() {
         count: GrcatConfigEntryCount::Once,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     let result = run_colorize("test test test", rules);
@@ -240,6 +252,7 @@ fn count_stop_prevents_subsequent_rules() {
             count: GrcatConfigEntryCount::Stop,
             replace: String::new(),
             skip: false,
+            ..Default::default()
         },
         GrcatConfigEntry {
             regex: CompiledRegex::new(r"here").unwrap(),
@@ -247,6 +260,7 @@ fn count_stop_prevents_subsequent_rules() {
             count: GrcatConfigEntryCount::More,
             replace: String::new(),
             skip: false,
+            ..Default::default()
         },
     ];
 
@@ -264,6 +278,7 @@ fn no_match_breaks_while_loop() {
         count: GrcatConfigEntryCount::More,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     // No match should cause break and output unchanged line
@@ -280,6 +295,7 @@ fn empty_style_ranges_outputs_line_unchanged() {
         count: GrcatConfigEntryCount::More,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     // No match -> empty style_ranges -> fast path
@@ -296,6 +312,7 @@ fn style_application_respects_line_length_bounds() {
         count: GrcatConfigEntryCount::More,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     let result = run_colorize("x", rules); // Very short line
@@ -312,6 +329,7 @@ fn style_boundary_at_position_zero_handled() {
             count: GrcatConfigEntryCount::Once,
             replace: String::new(),
             skip: false,
+            ..Default::default()
         },
         GrcatConfigEntry {
             regex: CompiledRegex::new(r"\d+$").unwrap(),
@@ -319,6 +337,7 @@ fn style_boundary_at_position_zero_handled() {
             count: GrcatConfigEntryCount::Once,
             replace: String::new(),
             skip: false,
+            ..Default::default()
         },
     ];
 
@@ -336,6 +355,7 @@ fn multiple_style_boundaries_tracked_correctly() {
             count: GrcatConfigEntryCount::More,
             replace: String::new(),
             skip: false,
+            ..Default::default()
         },
         GrcatConfigEntry {
             regex: CompiledRegex::new(r"b").unwrap(),
@@ -343,6 +363,7 @@ fn multiple_style_boundaries_tracked_correctly() {
             count: GrcatConfigEntryCount::More,
             replace: String::new(),
             skip: false,
+            ..Default::default()
         },
     ];
 
@@ -361,6 +382,7 @@ fn skip_rule_is_ignored_in_processing() {
             count: GrcatConfigEntryCount::More,
             replace: String::new(),
             skip: true, // This rule should be skipped
+            ..Default::default()
         },
         GrcatConfigEntry {
             regex: CompiledRegex::new(r"process").unwrap(),
@@ -368,6 +390,7 @@ fn skip_rule_is_ignored_in_processing() {
             count: GrcatConfigEntryCount::More,
             replace: String::new(),
             skip: false,
+            ..Default::default()
         },
     ];
 
@@ -385,6 +408,7 @@ fn offset_advances_past_match_end() {
         count: GrcatConfigEntryCount::More,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     // Each digit should match separately (offset advances by 1)
@@ -401,6 +425,7 @@ fn rule_matched_once_flag_stops_matching() {
         count: GrcatConfigEntryCount::Once,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     let result = run_colorize("abc", rules);
@@ -421,6 +446,7 @@ fn multiple_capture_groups_iterate_correctly() {
         count: GrcatConfigEntryCount::More,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     let result = run_colorize("host:8080", rules);
@@ -447,6 +473,7 @@ fn empty_line_fast_path_writes_newline_only() {
         count: GrcatConfigEntryCount::More,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     // Input with multiple empty lines between content
@@ -479,6 +506,7 @@ fn cache_skip_when_offset_behind_last_end() {
         count: GrcatConfigEntryCount::More,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     let result = run_colorize("test word", rules);
@@ -501,6 +529,7 @@ fn timetrace_feature_increments_counter_and_reports() {
         count: GrcatConfigEntryCount::More,
         replace: String::new(),
         skip: false,
+        ..Default::default()
     }];
 
     // Process multiple lines to increment lines_processed (line 159)
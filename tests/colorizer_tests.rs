@@ -592,6 +592,43 @@ mod edge_case_tests {
         Ok(())
     }
 
+    #[test]
+    fn test_later_rule_splits_earlier_span() -> Result<(), Box<dyn std::error::Error>> {
+        let rules = vec![
+            rule("abcdef", Style::new().red())?,
+            rule("cd", Style::new().blue())?,
+        ];
+        let output = colorize_test("xabcdefx", &rules)?;
+
+        let red = Style::new().red();
+        let blue = Style::new().blue();
+        let expected = format!(
+            "x{}{}{}x\n",
+            red.apply_to("ab"),
+            blue.apply_to("cd"),
+            red.apply_to("ef")
+        );
+        assert_eq!(output, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_adjacent_spans_with_same_style_merge() -> Result<(), Box<dyn std::error::Error>> {
+        // Two rules, same style, touching ranges: a single escape sequence
+        let rules = vec![
+            rule("ab", Style::new().green())?,
+            rule("cd", Style::new().green())?,
+            // Covers the gap with the default style: merged into the plain text
+            rule("xy", Style::new())?,
+        ];
+        let output = colorize_test("abcd xy", &rules)?;
+        assert_eq!(
+            output,
+            format!("{} xy\n", Style::new().green().apply_to("abcd"))
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_consecutive_empty_lines() -> Result<(), Box<dyn std::error::Error>> {
         let rules = vec![rule("test", Style::new().red())?];
@@ -656,6 +693,22 @@ mod advanced_features_tests {
     use rgrc::Style;
     use rgrc::grc::GrcatConfigEntryCount;
 
+    /// Load a conf file with HOME pointed at a temporary directory, so the rule
+    /// cache never writes to the user's home
+    fn load_conf(conf: &str) -> Vec<GrcatConfigEntry> {
+        static HOME_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+        let _guard = HOME_LOCK.lock().expect("HOME_LOCK mutex poisoned");
+        let home = tempfile::TempDir::new().expect("create tempdir");
+        let prev_home = std::env::var_os("HOME");
+        unsafe { std::env::set_var("HOME", home.path()) };
+        let rules = rgrc::load_grcat_config(conf);
+        match prev_home {
+            Some(h) => unsafe { std::env::set_var("HOME", h) },
+            None => unsafe { std::env::remove_var("HOME") },
+        }
+        rules
+    }
+
     /// Lines 290-293: Count::Stop prevents subsequent rule processing
     /// Tests that when a rule has count=Stop, processing stops after the first match
     /// and subsequent rules are not applied to the remainder of the line.
@@ -692,6 +745,258 @@ mod advanced_features_tests {
         Ok(())
     }
 
+    /// A slow-path rule that exceeds its time budget is disabled for the rest of the
+    /// stream, while fast-path rules keep working.
+    #[test]
+    fn test_rule_disabled_after_time_budget() -> Result<(), Box<dyn std::error::Error>> {
+        use rgrc::colorizer::colorize_regex_with_limits;
+        use rgrc::grc::{MatchLimits, RegexEngine};
+        use std::time::Duration;
+
+        let slow = GrcatConfigEntry::new(
+            CompiledRegex::with_engine(r"foo(?=\d)", RegexEngine::Enhanced)?,
+            vec![Style::new().red()],
+        );
+        let fast = rule(r"bar", Style::new().blue())?;
+        let limits = MatchLimits {
+            rule_time_budget: Some(Duration::ZERO),
+            ..MatchLimits::default()
+        };
+
+        let mut output = Vec::new();
+        colorize_regex_with_limits(
+            &mut "foo1 foo2 bar\nfoo3 bar\n".as_bytes(),
+            &mut output,
+            &[slow, fast],
+            limits,
+        )?;
+        let output = String::from_utf8(output)?;
+        let lines: Vec<&str> = output.lines().collect();
+
+        let red_foo = Style::new().red().apply_to("foo").to_string();
+        let blue_bar = Style::new().blue().apply_to("bar").to_string();
+        // First match on the first line is kept, then the rule is disabled
+        assert_eq!(lines[0].matches(&red_foo).count(), 1);
+        assert!(lines[0].contains(&blue_bar));
+        assert!(!lines[1].contains(&red_foo));
+        assert!(lines[1].contains(&blue_bar));
+
+        // Without a budget the rule keeps matching
+        let slow = GrcatConfigEntry::new(
+            CompiledRegex::with_engine(r"foo(?=\d)", RegexEngine::Enhanced)?,
+            vec![Style::new().red()],
+        );
+        let limits = MatchLimits {
+            rule_time_budget: None,
+            ..MatchLimits::default()
+        };
+        let mut output = Vec::new();
+        colorize_regex_with_limits(
+            &mut "foo1 foo2\nfoo3\n".as_bytes(),
+            &mut output,
+            &[slow],
+            limits,
+        )?;
+        assert_eq!(String::from_utf8(output)?.matches(&red_foo).count(), 3);
+        Ok(())
+    }
+
+    /// An EnhancedRegex rule is stopped by its step limit inside the match attempt,
+    /// even without a time budget, and disabled for the rest of the stream.
+    #[test]
+    fn test_rule_disabled_after_step_limit() -> Result<(), Box<dyn std::error::Error>> {
+        use rgrc::colorizer::colorize_regex_with_limits;
+        use rgrc::enhanced_regex::EnhancedRegex;
+        use rgrc::grc::MatchLimits;
+
+        let slow = GrcatConfigEntry::new(
+            CompiledRegex::Enhanced(EnhancedRegex::new(r"a+(?=b)")?.with_step_limit(100_000)),
+            vec![Style::new().red()],
+        );
+        let fast = rule(r"bar", Style::new().blue())?;
+        let limits = MatchLimits {
+            rule_time_budget: None,
+            ..MatchLimits::default()
+        };
+
+        let input = format!(
+            "{}c bar
+aab bar
+",
+            "a".repeat(5000)
+        );
+        let mut output = Vec::new();
+        colorize_regex_with_limits(&mut input.as_bytes(), &mut output, &[slow, fast], limits)?;
+        let output = String::from_utf8(output)?;
+        let lines: Vec<&str> = output.lines().collect();
+
+        let blue_bar = Style::new().blue().apply_to("bar").to_string();
+        assert!(lines[0].contains(&blue_bar));
+        // Disabled on the first line, so the second line's match is not coloured
+        assert!(lines[1].starts_with("aab "));
+        assert!(lines[1].contains(&blue_bar));
+        Ok(())
+    }
+
+    /// The literal prefilter skips rules that cannot match without changing the output
+    #[test]
+    fn test_prefilter_output_unchanged() -> Result<(), Box<dyn std::error::Error>> {
+        use rgrc::colorizer::colorize_regex_with_prefilter;
+        use rgrc::grc::MatchLimits;
+        use rgrc::prefilter::Prefilter;
+
+        let input = "Jan 12 10:00:01 host sshd[42]: Accepted publickey for user alice port 52114\n\
+                     Jan 12 10:00:02 host kernel: ERROR disk sda failed\n\
+                     pod/web-1   1/1   Running   0   5m\n\
+                     nothing to see here\n";
+        for conf in ["share/conf.journalctl", "share/conf.kubectl"] {
+            let rules = load_conf(conf);
+            let prefilter = Prefilter::new(&rules);
+            assert!(prefilter.filtered_rules() > 0);
+
+            let mut filtered = Vec::new();
+            let mut unfiltered = Vec::new();
+            for (prefilter, output) in [
+                (&prefilter, &mut filtered),
+                (&Prefilter::default(), &mut unfiltered),
+            ] {
+                colorize_regex_with_prefilter(
+                    &mut input.as_bytes(),
+                    output,
+                    &rules,
+                    MatchLimits::default(),
+                    prefilter,
+                )?;
+            }
+            assert_eq!(filtered, unfiltered, "{}", conf);
+        }
+        Ok(())
+    }
+
+    /// explain_line colours lines exactly as the colorizer does
+    #[test]
+    fn test_explain_line_matches_colorizer() -> Result<(), Box<dyn std::error::Error>> {
+        use rgrc::colorizer::explain_line;
+
+        let input = "64 bytes from 127.0.0.1: icmp_seq=1 ttl=64 time=0.045 ms\n\
+                     Jan 12 10:00:02 host kernel: ERROR disk sda failed\n\
+                     \n\
+                     pod/web-1   1/1   Running   0   5m\n";
+        for conf in [
+            "share/conf.ping",
+            "share/conf.journalctl",
+            "share/conf.kubectl",
+        ] {
+            let rules = load_conf(conf);
+            let expected = colorize_test(input, &rules)?;
+            let explained: String = input
+                .lines()
+                .map(|line| explain_line(line, &rules).output + "\n")
+                .collect();
+            assert_eq!(explained, expected, "{}", conf);
+        }
+        Ok(())
+    }
+
+    /// explain_line records matches, stops, replacements and the winning rule groups
+    #[test]
+    fn test_explain_line_trace() -> Result<(), Box<dyn std::error::Error>> {
+        use rgrc::colorizer::{NotRun, SpanOwner, explain_line};
+
+        let mut skipped = rule("a", Style::new().red())?;
+        skipped.skip = true;
+        let word = GrcatConfigEntry::new(
+            CompiledRegex::new(r"(\w+)=(\d+)?")?,
+            vec![Style::new().blue(), Style::new().bold()],
+        );
+        let mut stop = rule("x", Style::new().green())?;
+        stop.count = GrcatConfigEntryCount::Stop;
+        let rules = [skipped, word, stop, rule("y", Style::new().red())?];
+
+        let explanation = explain_line("ax=1 b=y", &rules);
+        let traces = &explanation.rules;
+        assert_eq!(traces.len(), 4);
+        assert_eq!(traces[0].not_run, Some(NotRun::Skip));
+        assert_eq!(traces[1].not_run, None);
+        assert_eq!(traces[1].matches.len(), 2);
+        assert_eq!(
+            traces[1].matches[0].groups,
+            vec![Some((0, 4)), Some((0, 2)), Some((3, 4))]
+        );
+        assert_eq!(traces[1].matches[0].painted, vec![0, 1]);
+        assert_eq!(
+            traces[1].matches[1].groups,
+            vec![Some((5, 7)), Some((5, 6)), None]
+        );
+        assert_eq!(traces[2].matches.len(), 1);
+        assert_eq!(traces[3].not_run, Some(NotRun::Stopped));
+        assert_eq!(
+            explanation.spans,
+            vec![
+                SpanOwner {
+                    start: 0,
+                    end: 1,
+                    rule: 1,
+                    group: 1
+                },
+                SpanOwner {
+                    start: 1,
+                    end: 2,
+                    rule: 2,
+                    group: 0
+                },
+                SpanOwner {
+                    start: 2,
+                    end: 4,
+                    rule: 1,
+                    group: 0
+                },
+                SpanOwner {
+                    start: 5,
+                    end: 6,
+                    rule: 1,
+                    group: 1
+                },
+                SpanOwner {
+                    start: 6,
+                    end: 7,
+                    rule: 1,
+                    group: 0
+                },
+            ]
+        );
+
+        let mut replace = rule(r"(\d+)", Style::new())?;
+        replace.replace = "<\\1>".to_string();
+        let rules = [replace, rule("x", Style::new().red())?];
+        let explanation = explain_line("x=12", &rules);
+        assert_eq!(explanation.line, "x=<12>");
+        assert_eq!(explanation.rules[0].replaced.as_deref(), Some("x=<12>"));
+        assert_eq!(explanation.rules[1].not_run, Some(NotRun::Replaced));
+
+        assert!(explain_line("", &rules).rules.is_empty());
+        Ok(())
+    }
+
+    /// Rules after a count=stop match are stopped, even when skipped or prefiltered out
+    #[test]
+    fn test_explain_line_stop_before_skip_and_prefilter() -> Result<(), Box<dyn std::error::Error>>
+    {
+        use rgrc::colorizer::{NotRun, explain_line};
+
+        let mut stop = rule("q", Style::new().green())?;
+        stop.count = GrcatConfigEntryCount::Stop;
+        let mut skipped = rule("q", Style::new().red())?;
+        skipped.skip = true;
+        let rules = [stop, rule("zz", Style::new().red())?, skipped];
+
+        let traces = explain_line("q", &rules).rules;
+        assert_eq!(traces[0].matches.len(), 1);
+        assert_eq!(traces[1].not_run, Some(NotRun::Stopped));
+        assert_eq!(traces[2].not_run, Some(NotRun::Stopped));
+        Ok(())
+    }
+
     /// Lines 248-274: Replace functionality breaks outer loop
     /// Tests that when a rule performs text replacement, it breaks the outer rule
     /// processing loop and follow-up rules are not applied.
@@ -10,7 +10,9 @@
 // - Error paths: unknown keywords, invalid regex, empty colours, invalid count values
 
 use rgrc::grc::{
-    CompiledRegex, GrcConfigReader, GrcatConfigEntry, GrcatConfigEntryCount, GrcatConfigReader,
+    CompiledRegex, ConfigAst, ConfigLineKind, ConfigNode, GrcConfigReader, GrcatConfigEntry,
+    GrcatConfigEntryCount, GrcatConfigItem, GrcatConfigReader, Severity, write_grc_config,
+    write_grcat_config,
 };
 use std::io::BufRead;
 
@@ -544,3 +546,301 @@ fn grcatreader_missing_colours_is_empty_vector() {
         "missing colours should lead to empty colors vector"
     );
 }
+
+#[test]
+fn grcatreader_lazy_keeps_invalid_regex_for_first_use() {
+    let input =
+        "regexp=invalid(regex\ncolours=red\n\nRegexp: not a key\nregexp = ^B\ncolours=blue\n";
+    let reader = std::io::Cursor::new(input);
+    let entries: Vec<_> =
+        GrcatConfigReader::lazy(std::io::BufReader::new(reader).lines()).collect();
+
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0].regex.try_is_match("invalid(regex").is_err());
+    assert_eq!(entries[1].regex.as_str(), "^B");
+    assert!(entries[1].regex.is_match("B"));
+}
+
+#[test]
+fn grcatreader_include_lines_are_items_of_their_own() {
+    // An include ends the entry before it, in both spellings
+    let input = "regexp=^A\ncolours=red\ninclude=conf.common\nregexp=^B\n\n@include  conf.ip \n\
+                 regexp=^C\n@includeconf.x\n";
+    let mut reader = GrcatConfigReader::new(std::io::Cursor::new(input).lines());
+    let items: Vec<String> = std::iter::from_fn(|| reader.next_item())
+        .map(|item| match item {
+            GrcatConfigItem::Rule(rule) => rule.regex.as_str().to_string(),
+            GrcatConfigItem::Include { name, .. } => format!("include {}", name),
+        })
+        .collect();
+    assert_eq!(
+        items,
+        ["^A", "include conf.common", "^B", "include conf.ip", "^C"]
+    );
+
+    // Plain iteration only yields the rules
+    let rules = GrcatConfigReader::new(std::io::Cursor::new(input).lines()).count();
+    assert_eq!(rules, 3);
+}
+
+#[test]
+fn grcatreader_expands_macros_and_skips_undefined_ones() {
+    let input = "define PORT=\\d+\nregexp=({IPV4}):({PORT})\ncolours=default,red,blue\n-\n\
+                 regexp={NOPE}\ncolours=red\n-\nregexp=\\d{2}\ncolours=green\n";
+    let rules: Vec<GrcatConfigEntry> =
+        GrcatConfigReader::new(std::io::Cursor::new(input).lines()).collect();
+    // The rule with an undefined macro is dropped, repetitions are left alone
+    assert_eq!(rules.len(), 2);
+    let caps = rules[0]
+        .regex
+        .captures_from_pos("to 10.0.0.1:8080", 0)
+        .unwrap();
+    assert_eq!(caps.get(1).unwrap().as_str(), "10.0.0.1");
+    assert_eq!(caps.get(2).unwrap().as_str(), "8080");
+    assert_eq!(rules[1].regex.as_str(), "\\d{2}");
+}
+
+#[test]
+fn grcatreader_keeps_tags_and_severity() {
+    let input = "id=dup\nregexp=DUP!\ntags=network, duplicate\ntags=network,,icmp\nseverity=warning\n\
+                 -\nregexp=plain\nseverity=fatal\n";
+    let rules: Vec<GrcatConfigEntry> =
+        GrcatConfigReader::new(std::io::Cursor::new(input).lines()).collect();
+    assert_eq!(rules[0].tags, ["network", "duplicate", "icmp"]);
+    assert_eq!(rules[0].severity, Some(Severity::Warning));
+    assert_eq!(
+        rules[0].label(0),
+        "#1 id=dup severity=warning tags=network,duplicate,icmp"
+    );
+    // Unknown severities are reported and ignored
+    assert!(rules[1].tags.is_empty());
+    assert_eq!(rules[1].severity, None);
+    assert_eq!(rules[1].label(1), "#2");
+}
+
+/// ConfigAst keeps every line and groups them as the reader does
+#[test]
+fn config_ast_is_lossless_and_groups_rules() {
+    let input = "# ping\r\nregexp = ^(\\d+)  \r\ncolors=red\r\n  count=once\r\n\n\
+                 include=conf.common\nid=x\n@include conf.more\n=====\nregexp=a";
+    let ast = ConfigAst::parse(input);
+    assert_eq!(ast.to_string(), input);
+    assert!(!ast.final_newline);
+
+    let kinds: Vec<&str> = ast
+        .nodes
+        .iter()
+        .map(|node| match node {
+            ConfigNode::Trivia(_) => "trivia",
+            ConfigNode::Include(_) => "include",
+            ConfigNode::Rule(_) => "rule",
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            "trivia", "rule", "trivia", "trivia", "include", "rule", "include", "trivia", "rule"
+        ]
+    );
+
+    // The first line of a rule is trimmed, spans are byte columns of the line
+    let ConfigNode::Rule(lines) = &ast.nodes[1] else {
+        unreachable!()
+    };
+    assert_eq!(lines[0].key_value(), Some(("regexp", "^(\\d+)")));
+    let ConfigLineKind::KeyValue { key, value } = &lines[0].kind else {
+        unreachable!()
+    };
+    assert_eq!((key.line, key.start, key.end), (2, 0, 6));
+    assert_eq!(value.column(), 10);
+    assert_eq!(lines[1].key_value(), Some(("colors", "red")));
+    // An indented line ends the rule
+    let ConfigNode::Trivia(indented) = &ast.nodes[2] else {
+        unreachable!()
+    };
+    assert_eq!(indented.kind, ConfigLineKind::Other);
+    assert_eq!(ast.nodes[4].lines()[0].include_name(), Some("conf.common"));
+    assert_eq!(ast.nodes[7].lines()[0].kind, ConfigLineKind::Separator);
+
+    assert_eq!(ConfigAst::parse("").to_string(), "");
+    assert_eq!(ConfigAst::parse("\n\n").lines().count(), 2);
+}
+
+/// Like any first line of a rule, a define line starting one is read trimmed
+#[test]
+fn grcatreader_trims_a_define_that_starts_a_rule() {
+    let input = "define X=a \t\nregexp=({X})b\n\nregexp=c\ndefine Y=d \nregexp={Y}\n";
+    let patterns: Vec<String> = GrcatConfigReader::new(std::io::Cursor::new(input).lines())
+        .map(|rule| rule.regex.as_str().to_string())
+        .collect();
+    assert_eq!(patterns, ["((?:a))b", "(?:d )"]);
+}
+
+/// An unknown colour leaves the rule uncoloured but keeps its other keys
+#[test]
+fn grcatreader_bad_colours_keep_the_rest_of_the_rule() {
+    let input = "colours=bold nope\nregexp=ERROR\ncount=once\n";
+    let mut reader = GrcatConfigReader::new(std::io::Cursor::new(input).lines());
+    let rules: Vec<GrcatConfigEntry> = reader.by_ref().collect();
+    assert_eq!(rules.len(), 1);
+    assert!(rules[0].colors.is_empty());
+    assert!(matches!(rules[0].count, GrcatConfigEntryCount::Once));
+    assert_eq!(reader.take_diagnostics()[0].line, Some(1));
+}
+
+fn read_items(text: &[u8]) -> Vec<GrcatConfigItem> {
+    let mut reader = GrcatConfigReader::new(text.lines());
+    std::iter::from_fn(|| reader.next_item()).collect()
+}
+
+/// Everything a rule holds, comparable
+fn item_key(item: &GrcatConfigItem) -> String {
+    match item {
+        GrcatConfigItem::Include { name, .. } => format!("include {}", name),
+        GrcatConfigItem::Rule(rule) => format!(
+            "{:?} {:?} {} {:?} {:?} {:?} {:?} {:?} {:?}",
+            rule.regex.as_str(),
+            rule.colors.iter().map(|s| s.to_bits()).collect::<Vec<_>>(),
+            rule.skip,
+            rule.count,
+            rule.replace,
+            rule.id,
+            rule.overlay,
+            rule.tags,
+            rule.severity
+        ),
+    }
+}
+
+fn write_items(items: &[GrcatConfigItem]) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_grcat_config(&mut out, items).unwrap();
+    out
+}
+
+/// The bundled conf.* files, sorted
+fn share_confs() -> Vec<std::path::PathBuf> {
+    let mut confs: Vec<_> = std::fs::read_dir("share")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("conf.")
+        })
+        .collect();
+    confs.sort();
+    assert!(!confs.is_empty());
+    confs
+}
+
+/// Parse, write and parse again gives the same rules for every bundled config,
+/// and writing is a fixed point
+#[test]
+fn grcat_config_round_trips_through_the_writer() {
+    for path in share_confs() {
+        let items = read_items(&std::fs::read(&path).unwrap());
+        let text = write_items(&items);
+        let again = read_items(&text);
+        assert_eq!(
+            items.iter().map(item_key).collect::<Vec<_>>(),
+            again.iter().map(item_key).collect::<Vec<_>>(),
+            "{}",
+            path.display()
+        );
+        assert_eq!(write_items(&again), text, "{}", path.display());
+    }
+}
+
+/// Every key is written, only when it differs from its default
+#[test]
+fn grcat_writer_uses_canonical_keys() {
+    let input = "id=r\nregexp=a  \ncolour=bold red,default\ncount=once\nskip=yes\n\
+                 replace=x \\1\ntags=a, b\nseverity=info\noverlay=append\n\
+                 @include conf.common\n\
+                 regexp=b\ncount=more\n\n\
+                 count=stop\nregexp=c \n";
+    let items = read_items(input.as_bytes());
+    let text = String::from_utf8(write_items(&items)).unwrap();
+    assert_eq!(
+        text,
+        "id=r\nregexp=a  \ncolours=bold red,default\ncount=once\nskip=true\n\
+         replace=x \\1\noverlay=append\ntags=a,b\nseverity=info\n\
+         -\ninclude=conf.common\n\
+         -\nregexp=b\n\
+         -\ncount=stop\nregexp=c \n"
+    );
+    // The trailing space of the last pattern survives because count= comes first
+    assert_eq!(
+        read_items(text.as_bytes())
+            .iter()
+            .map(item_key)
+            .collect::<Vec<_>>(),
+        items.iter().map(item_key).collect::<Vec<_>>()
+    );
+}
+
+/// grc.conf mappings read back the same after writing
+#[test]
+fn grc_config_round_trips_through_the_writer() {
+    let read = |text: &[u8]| -> Vec<(CompiledRegex, String)> {
+        GrcConfigReader::new(text.lines()).collect()
+    };
+    let mappings = read(&std::fs::read("etc/rgrc.conf").unwrap());
+    assert!(mappings.len() > 50);
+    let mut text = Vec::new();
+    write_grc_config(&mut text, &mappings).unwrap();
+    assert!(String::from_utf8_lossy(&text).starts_with("^([/\\w\\.]+\\/)?asdf"));
+    let again = read(&text);
+    let names = |mappings: &[(CompiledRegex, String)]| -> Vec<(String, String)> {
+        mappings
+            .iter()
+            .map(|(regex, conf)| (regex.as_str().to_string(), conf.clone()))
+            .collect()
+    };
+    assert_eq!(names(&mappings), names(&again));
+}
+
+/// Formatting keeps the rules of every bundled config and is a fixed point
+#[test]
+fn config_formatting_keeps_rules() {
+    for path in share_confs() {
+        let content = std::fs::read_to_string(&path).unwrap();
+        let formatted = ConfigAst::parse(&content).formatted();
+        assert_eq!(
+            read_items(content.as_bytes())
+                .iter()
+                .map(item_key)
+                .collect::<Vec<_>>(),
+            read_items(formatted.as_bytes())
+                .iter()
+                .map(item_key)
+                .collect::<Vec<_>>(),
+            "{}",
+            path.display()
+        );
+        assert_eq!(
+            ConfigAst::parse(&formatted).formatted(),
+            formatted,
+            "{}",
+            path.display()
+        );
+    }
+}
+
+/// Keys, separators, comments and blank lines get one layout; whitespace the
+/// reader keeps stays
+#[test]
+fn config_formatting_layout() {
+    let input = "\n\n  # ping  \r\nregexp = ^(\\d+)  \r\ncolors=bold  red, yellow\r\n\
+                 replace=x \ntags= a,,b \n\n\n\n.........\n@include   conf.common\n\
+                 define  NUM = \\d+ \nregexp={NUM} \ncolour=green\n  count=once\n%%%%\n\n";
+    let formatted = ConfigAst::parse(input).formatted();
+    assert_eq!(
+        formatted,
+        "# ping\nregexp=^(\\d+)\ncolours=bold red,yellow\nreplace=x \ntags=a,b\n\n\
+         -\ninclude=conf.common\ndefine NUM=\\d+\nregexp={NUM} \ncolours=green\n  count=once\n-\n"
+    );
+    assert_eq!(ConfigAst::parse("\n \n").formatted(), "");
+}
@@ -12,6 +12,19 @@ mod enhanced_regex;
 #[path = "../src/grc.rs"]
 mod grc;
 
+#[path = "../src/translate.rs"]
+mod translate;
+
+#[path = "../src/lowering.rs"]
+mod lowering;
+
+#[path = "../src/macros.rs"]
+mod macros;
+
+#[path = "../src/diagnostic.rs"]
+#[allow(dead_code)]
+mod diagnostic;
+
 use grc::{GrcConfigReader, GrcatConfigEntry, GrcatConfigReader};
 
 /// Helper function to get the project root directory
@@ -2,7 +2,7 @@
 // Simple patterns should use fast regex::Regex
 // Complex patterns (with lookahead/lookbehind) use Enhanced regex
 
-use rgrc::grc::CompiledRegex;
+use rgrc::grc::{CompiledRegex, RegexBackend, RegexEngine};
 
 #[test]
 fn test_simple_pattern_uses_fast_regex() {
@@ -18,6 +18,16 @@ fn test_simple_pattern_uses_fast_regex() {
         CompiledRegex::Enhanced(_) => {
             panic!("Simple pattern should use Fast regex, not Enhanced");
         }
+        #[cfg(feature = "fancy-regex")]
+        CompiledRegex::Fancy(_) => {
+            panic!("Simple pattern should use Fast regex, not Fancy");
+        }
+        CompiledRegex::Lowered(_) => {
+            panic!("CompiledRegex::new should not lower patterns");
+        }
+        CompiledRegex::Lazy(_) => {
+            panic!("CompiledRegex::new should compile eagerly");
+        }
     }
 }
 
@@ -35,6 +45,16 @@ fn test_complex_pattern_uses_enhanced() {
             // Success! EnhancedRegex can handle lookahead
             println!("✓ Lookahead pattern uses Enhanced regex engine");
         }
+        #[cfg(feature = "fancy-regex")]
+        CompiledRegex::Fancy(_) => {
+            println!("✓ Lookahead pattern uses fancy-regex engine");
+        }
+        CompiledRegex::Lowered(_) => {
+            panic!("CompiledRegex::new should not lower patterns");
+        }
+        CompiledRegex::Lazy(_) => {
+            panic!("CompiledRegex::new should compile eagerly");
+        }
     }
 }
 
@@ -52,22 +72,41 @@ fn test_lookbehind_pattern_uses_enhanced() {
             // Success! EnhancedRegex can handle lookbehind
             println!("✓ Lookbehind pattern uses Enhanced regex engine");
         }
+        #[cfg(feature = "fancy-regex")]
+        CompiledRegex::Fancy(_) => {
+            println!("✓ Lookbehind pattern uses fancy-regex engine");
+        }
+        CompiledRegex::Lowered(_) => {
+            panic!("CompiledRegex::new should not lower patterns");
+        }
+        CompiledRegex::Lazy(_) => {
+            panic!("CompiledRegex::new should compile eagerly");
+        }
     }
 }
 
 #[test]
 #[cfg(not(feature = "fancy-regex"))]
-fn test_backreference_fails() {
-    // Pattern with backreference is not supported by EnhancedRegex
+fn test_backreference_works_with_enhanced() {
+    // Pattern with backreference is verified by EnhancedRegex after matching
     let pattern = r"(\w+)\s+\1";
     let compiled = CompiledRegex::new(pattern);
 
-    // Backreferences are not supported, should fail to compile
     assert!(
-        compiled.is_err(),
-        "Backreference pattern should fail to compile"
+        matches!(compiled, Ok(CompiledRegex::Enhanced(_))),
+        "Backreference pattern should compile with EnhancedRegex"
+    );
+    println!("✓ Backreference pattern uses Enhanced regex engine");
+
+    let regex = compiled.unwrap();
+    assert!(
+        regex.is_match("hello hello"),
+        "Should match duplicated word"
+    );
+    assert!(
+        !regex.is_match("hello world"),
+        "Should not match different words"
     );
-    println!("✓ Backreference pattern correctly fails to compile (not supported)");
 }
 
 #[test]
@@ -96,6 +135,85 @@ fn test_backreference_works_with_fancy() {
     );
 }
 
+#[test]
+fn test_explicit_engine_selection() {
+    let pattern = r"hello(?=\d+)";
+
+    // Simple patterns stay on the fast path whatever the engine
+    let simple = CompiledRegex::with_engine(r"\d+", RegexEngine::Enhanced).unwrap();
+    assert_eq!(simple.backend(), RegexBackend::Fast);
+
+    // `fast` disables the fallback entirely
+    assert!(CompiledRegex::with_engine(pattern, RegexEngine::Fast).is_err());
+
+    // `enhanced` is available in every build
+    let enhanced = CompiledRegex::with_engine(pattern, RegexEngine::Enhanced).unwrap();
+    assert_eq!(enhanced.backend(), RegexBackend::Enhanced);
+    assert!(enhanced.backend().is_slow_path());
+    assert!(enhanced.is_match("hello42"));
+    assert!(!enhanced.is_match("hello"));
+
+    let fancy = CompiledRegex::with_engine(pattern, RegexEngine::Fancy);
+    if cfg!(feature = "fancy-regex") {
+        assert_eq!(fancy.unwrap().backend(), RegexBackend::Fancy);
+    } else {
+        assert!(fancy.is_err());
+    }
+
+    let auto = CompiledRegex::with_engine(pattern, RegexEngine::Auto).unwrap();
+    let expected = if cfg!(feature = "fancy-regex") {
+        RegexBackend::Fancy
+    } else {
+        RegexBackend::Enhanced
+    };
+    assert_eq!(auto.backend(), expected);
+}
+
+#[test]
+fn test_findmnt_rule_matches_on_every_backend() {
+    // conf.findmnt greys out cgroup and tmpfs lines with `^.*(?=cgroup|tmpfs).*$`,
+    // whose lookahead can sit anywhere in the line
+    let conf = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/share/conf.findmnt"))
+        .expect("conf.findmnt");
+    let pattern = conf
+        .lines()
+        .filter_map(|line| line.strip_prefix("regexp="))
+        .find(|pattern| pattern.contains("(?=cgroup|tmpfs)"))
+        .expect("cgroup rule in conf.findmnt");
+
+    let lines = [
+        "├─/dev                       devtmpfs   devtmpfs   rw,nosuid,size=4096k",
+        "│ ├─/sys/fs/cgroup           cgroup2    cgroup2    rw,nosuid,nodev,noexec",
+        "├─/boot                      /dev/sda1  ext4       rw,relatime",
+    ];
+    let expected = [Some((0, lines[0].len())), Some((0, lines[1].len())), None];
+
+    let mut engines = vec![RegexEngine::Enhanced];
+    if cfg!(feature = "fancy-regex") {
+        engines.push(RegexEngine::Fancy);
+    }
+    for engine in engines {
+        let re = CompiledRegex::with_engine(pattern, engine).unwrap();
+        for (line, expected) in lines.iter().zip(expected) {
+            let found = re
+                .captures_from_pos(line, 0)
+                .and_then(|caps| caps.get(0))
+                .map(|mat| (mat.start(), mat.end()));
+            assert_eq!(found, expected, "{:?} on {:?}", engine, line);
+        }
+    }
+}
+
+#[test]
+fn test_regex_engine_from_str() {
+    assert_eq!("auto".parse::<RegexEngine>(), Ok(RegexEngine::Auto));
+    assert_eq!("fast".parse::<RegexEngine>(), Ok(RegexEngine::Fast));
+    assert_eq!("enhanced".parse::<RegexEngine>(), Ok(RegexEngine::Enhanced));
+    assert_eq!("fancy".parse::<RegexEngine>(), Ok(RegexEngine::Fancy));
+    assert!("pcre".parse::<RegexEngine>().is_err());
+    assert_eq!(RegexEngine::Enhanced.to_string(), "enhanced");
+}
+
 #[test]
 fn test_multiple_simple_patterns() {
     // Test that various common simple patterns use Fast regex
@@ -122,6 +240,19 @@ fn test_multiple_simple_patterns() {
                     pattern
                 );
             }
+            #[cfg(feature = "fancy-regex")]
+            CompiledRegex::Fancy(_) => {
+                panic!(
+                    "Simple pattern '{}' should use Fast regex, not Fancy",
+                    pattern
+                );
+            }
+            CompiledRegex::Lowered(_) => {
+                panic!("CompiledRegex::new should not lower '{}'", pattern);
+            }
+            CompiledRegex::Lazy(_) => {
+                panic!("CompiledRegex::new should compile '{}' eagerly", pattern);
+            }
         }
     }
 }
@@ -249,3 +380,70 @@ fn test_enhanced_ipv4_find_from_different_positions() {
     assert!(mat2.is_some(), "Should find second IPv4");
     assert_eq!(mat2.unwrap().as_str(), "10", "Second match should be '10'");
 }
+
+#[test]
+fn test_optimized_lowers_anchoring_lookarounds() {
+    // Leading lookbehind / trailing lookahead move to the fast path
+    for pattern in [r"(?<=ttl=)\d+", r"\d+(?=ms)", r"(?<=\s)(\w+)(?=:)"] {
+        let compiled = CompiledRegex::new(pattern).unwrap().optimized();
+        assert_eq!(compiled.backend(), RegexBackend::Lowered, "{}", pattern);
+        assert!(!compiled.backend().is_slow_path());
+        assert_eq!(compiled.as_str(), pattern);
+    }
+
+    // Same matches and groups as the enhanced engine
+    let enhanced = CompiledRegex::new(r"(?<=\s)(\w+)(?=:)").unwrap();
+    let lowered = enhanced.clone().optimized();
+    let text = "key: a b: value c:";
+    let mut pos = 0;
+    while let Some(expected) = enhanced.captures_from_pos(text, pos) {
+        let actual = lowered.captures_from_pos(text, pos).unwrap();
+        assert_eq!(actual.len(), expected.len());
+        for i in 0..expected.len() {
+            let (e, a) = (expected.get(i).unwrap(), actual.get(i).unwrap());
+            assert_eq!((a.start(), a.end()), (e.start(), e.end()));
+        }
+        pos = expected.get(0).unwrap().end();
+    }
+    assert!(lowered.captures_from_pos(text, pos).is_none());
+
+    // Negative lookarounds stay on the slow path
+    let compiled = CompiledRegex::new(r"(?<!x)\d+").unwrap().optimized();
+    assert!(compiled.backend().is_slow_path());
+}
+
+#[test]
+fn test_lazy_regex_compiles_on_first_use() {
+    // The pattern is available before compiling, already translated
+    let lazy = CompiledRegex::lazy(r"(?<=ttl=)\d+");
+    assert_eq!(lazy.as_str(), r"(?<=ttl=)\d+");
+
+    // First use compiles and optimizes like an eagerly loaded rule
+    let caps = lazy.captures_from_pos("ttl=64 time=1", 0).unwrap();
+    assert_eq!(caps.get(0).unwrap().as_str(), "64");
+    assert_eq!(lazy.backend(), RegexBackend::Lowered);
+    assert!(lazy.clone().is_match("ttl=1"));
+
+    // Syntax errors surface on first use
+    let invalid = CompiledRegex::lazy(r"^test(");
+    assert!(!invalid.is_match("test"));
+    assert!(invalid.try_captures_from_pos("test(", 0).is_err());
+    assert_eq!(invalid.backend(), RegexBackend::Fast);
+}
+
+#[test]
+fn test_captures_len_counts_original_groups() {
+    let cases = [
+        (r"(\d+) (\w+)", RegexBackend::Fast, 3),
+        (r"(?<=ttl=)(\d+)", RegexBackend::Lowered, 2),
+        (r"(\w+)(?!x)", RegexBackend::Enhanced, 2),
+    ];
+    for (pattern, backend, len) in cases {
+        let re = CompiledRegex::with_engine(pattern, RegexEngine::Enhanced)
+            .unwrap()
+            .optimized();
+        assert_eq!(re.backend(), backend, "{}", pattern);
+        assert_eq!(re.captures_len(), len, "{}", pattern);
+    }
+    assert_eq!(CompiledRegex::lazy(r"(a)(b)").captures_len(), 3);
+}
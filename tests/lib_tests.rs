@@ -1,6 +1,42 @@
 // Functional tests for main.rs - testing the actually executable code paths
 // This file tests the functions that are exposed for testing: load_config and load_grcat_config
 
+// Helper to run a test with a temporary HOME directory so cache creation is
+// isolated and reliable in CI or cross-build environments.
+// Serialize tests that modify HOME so multiple tests in this file don't
+// race by clobbering the process global HOME environment variable.
+static HOME_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+fn with_temp_home<R>(f: impl FnOnce() -> R) -> R {
+    // Acquire module-level lock so that only one test at a time will change HOME
+    let _guard = HOME_LOCK.lock().expect("HOME_LOCK mutex poisoned");
+    let td = tempfile::TempDir::new().expect("create tempdir");
+    let prev_home = std::env::var_os("HOME");
+
+    // Set HOME to our temporary directory for the duration of the test
+    // Use a stringified path here to ensure the environment value is stable
+    let td_path = td.path().to_str().expect("tempdir path is valid utf8");
+    unsafe {
+        std::env::set_var("HOME", td_path);
+    }
+
+    // Run provided closure while the TempDir is kept alive
+    let res = f();
+
+    // Restore original HOME
+    if let Some(h) = prev_home {
+        unsafe {
+            std::env::set_var("HOME", h);
+        }
+    } else {
+        unsafe {
+            std::env::remove_var("HOME");
+        }
+    }
+
+    res
+}
+
 #[test]
 fn test_load_grcat_config_nonexistent_file() {
     // Test that nonexistent files return empty vector
@@ -49,6 +85,69 @@ fn test_load_grcat_config_with_relative_paths() {
     let _ = result2;
 }
 
+#[test]
+fn test_try_load_grcat_config_reports_problems_with_lines() {
+    use rgrc::DiagnosticKind;
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let conf = dir.path().join("conf.problems");
+    std::fs::write(
+        &conf,
+        "regexp=(\\d+\ncolours=red\n-\nregexp=ok\ncolours=bogus\n-\n\
+         regexp=x{NOPE}\ncolours=red\n-\nregexp=y\ncolours=green\ncount=sometimes\n",
+    )
+    .unwrap();
+    let path = conf.to_str().unwrap();
+
+    let check = || {
+        let err = rgrc::try_load_grcat_config(path).unwrap_err();
+        let found: Vec<_> = err
+            .diagnostics
+            .iter()
+            .map(|d| (d.file.as_deref(), d.line, d.kind))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (Some(path), Some(1), DiagnosticKind::Regex),
+                (Some(path), Some(5), DiagnosticKind::Style),
+                (Some(path), Some(7), DiagnosticKind::Macro),
+                (Some(path), Some(12), DiagnosticKind::Value),
+            ]
+        );
+        // The rules with an invalid regex or an undefined macro are dropped, and the
+        // bad style leaves its rule uncoloured
+        assert_eq!(err.into_rules().len(), 2);
+    };
+
+    with_temp_home(|| {
+        // The second load comes from the rule cache and reports the same problems
+        check();
+        check();
+
+        // Without a writable cache the regex is still checked while loading
+        unsafe {
+            std::env::set_var("HOME", "/dev/null/home");
+        }
+        check();
+    });
+}
+
+#[test]
+fn test_try_load_grcat_config_missing_file() {
+    let err = rgrc::try_load_grcat_config("/nonexistent/path/conf.x").unwrap_err();
+    assert_eq!(err.diagnostics.len(), 1);
+    assert_eq!(err.diagnostics[0].kind, rgrc::DiagnosticKind::Io);
+    assert!(err.into_rules().is_empty());
+    // A command without a config is not a problem
+    assert!(rgrc::try_load_config("/nonexistent/grc.conf", "ls").is_err());
+    assert!(
+        rgrc::try_load_rules_for_command("definitely_not_a_real_command_12345")
+            .unwrap()
+            .is_empty()
+    );
+}
+
 #[test]
 fn test_load_config_nonexistent_config_file() {
     // load_config should handle missing files gracefully
@@ -214,44 +313,9 @@ fn test_color_mode_clone_semantics() {
 #[cfg(feature = "embed-configs")]
 mod embed_configs_tests {
     use std::io::Write;
-    use std::sync::Mutex;
-    use tempfile::{NamedTempFile, TempDir};
-
-    // Helper to run a test with a temporary HOME directory so cache creation is
-    // isolated and reliable in CI or cross-build environments.
-    // Serialize tests that modify HOME so multiple tests in this module don't
-    // race by clobbering the process global HOME environment variable.
-    static HOME_LOCK: Mutex<()> = Mutex::new(());
-
-    fn with_temp_home<R>(f: impl FnOnce() -> R) -> R {
-        // Acquire module-level lock so that only one test at a time will change HOME
-        let _guard = HOME_LOCK.lock().expect("HOME_LOCK mutex poisoned");
-        let td = TempDir::new().expect("create tempdir");
-        let prev_home = std::env::var_os("HOME");
-
-        // Set HOME to our temporary directory for the duration of the test
-        // Use a stringified path here to ensure the environment value is stable
-        let td_path = td.path().to_str().expect("tempdir path is valid utf8");
-        unsafe {
-            std::env::set_var("HOME", td_path);
-        }
-
-        // Run provided closure while the TempDir is kept alive
-        let res = f();
-
-        // Restore original HOME
-        if let Some(h) = prev_home {
-            unsafe {
-                std::env::set_var("HOME", h);
-            }
-        } else {
-            unsafe {
-                std::env::remove_var("HOME");
-            }
-        }
+    use tempfile::NamedTempFile;
 
-        res
-    }
+    use super::with_temp_home;
 
     #[test]
     fn test_embed_configs_filesystem_priority() {
@@ -683,4 +683,141 @@ mod cli_integration_tests {
             "Output should not contain ANSI escape codes when piped to a non-TTY"
         );
     }
+
+    /// Batched `--config` input from a file gives the same output as sequential mode
+    #[test]
+    fn test_config_jobs_match_sequential_output() {
+        use std::io::Write;
+        use tempfile::TempDir;
+
+        let td = TempDir::new().unwrap();
+        let input = td.path().join("input.log");
+        let mut file = std::fs::File::create(&input).unwrap();
+        for i in 0..2_000 {
+            writeln!(
+                file,
+                "Nov 30 10:21:{:02} host sshd[{}]: Accepted publickey for alice port {} ERROR",
+                i % 60,
+                i,
+                40000 + i % 1000
+            )
+            .unwrap();
+        }
+        drop(file);
+
+        let run = |jobs: &str| {
+            let output = Command::new(env!("CARGO_BIN_EXE_rgrc"))
+                .args(["--color=on", "--jobs", jobs, "-c", "journalctl"])
+                .env("HOME", td.path())
+                .stdin(std::process::Stdio::from(
+                    std::fs::File::open(&input).unwrap(),
+                ))
+                .output()
+                .expect("failed to run rgrc");
+            assert!(output.status.success());
+            output.stdout
+        };
+
+        let sequential = run("1");
+        assert!(sequential.contains(&0x1b));
+        assert_eq!(run("3"), sequential);
+    }
+
+    /// CLI Test: embedded configs are used without a writable HOME
+    ///
+    /// Rules come straight from the binary, so a missing or read-only HOME still
+    /// colorizes. With a writable HOME the parsed rules are kept in the rule cache
+    /// and the next run loads them from there.
+    #[cfg(feature = "embed-configs")]
+    #[test]
+    fn test_embedded_configs_do_not_touch_home() {
+        use std::io::Write;
+        use tempfile::TempDir;
+
+        let td = TempDir::new().unwrap();
+        let run = |home: Option<&std::path::Path>| {
+            let mut cmd = Command::new(env!("CARGO_BIN_EXE_rgrc"));
+            match home {
+                Some(home) => cmd.env("HOME", home),
+                None => cmd.env_remove("HOME"),
+            };
+            let mut child = cmd
+                .args(["--color=on", "-c", "ping"])
+                .stdin(std::process::Stdio::piped())
+                .stdout(std::process::Stdio::piped())
+                .spawn()
+                .expect("failed to run rgrc");
+            child
+                .stdin
+                .take()
+                .unwrap()
+                .write_all(b"64 bytes from 127.0.0.1: icmp_seq=1 ttl=64 time=0.045 ms\n")
+                .unwrap();
+            let output = child.wait_with_output().unwrap();
+            assert!(output.status.success());
+            output.stdout
+        };
+
+        let with_home = run(Some(td.path()));
+        assert!(with_home.contains(&0x1b));
+        assert_eq!(run(None), with_home);
+        assert_eq!(
+            run(Some(std::path::Path::new("/nonexistent/home"))),
+            with_home
+        );
+
+        // The first run filled the rule cache, the second one used it
+        let rules = td.path().join(".cache/rgrc/rules");
+        assert_eq!(std::fs::read_dir(&rules).unwrap().count(), 1);
+        assert_eq!(run(Some(td.path())), with_home);
+    }
+
+    /// CLI Test: a user overlay replaces one bundled rule and keeps the others
+    #[test]
+    fn test_overlay_replaces_bundled_rule() {
+        use rgrc::style::Style;
+        use std::io::Write;
+        use tempfile::TempDir;
+
+        let td = TempDir::new().unwrap();
+        let run = || {
+            let mut child = Command::new(env!("CARGO_BIN_EXE_rgrc"))
+                .env("HOME", td.path())
+                .args(["--color=on", "-c", "ping"])
+                .stdin(std::process::Stdio::piped())
+                .stdout(std::process::Stdio::piped())
+                .spawn()
+                .expect("failed to run rgrc");
+            child
+                .stdin
+                .take()
+                .unwrap()
+                .write_all(b"64 bytes from 10.0.0.1: icmp_seq=1 ttl=57 time=0.045 ms\n")
+                .unwrap();
+            String::from_utf8(child.wait_with_output().unwrap().stdout).unwrap()
+        };
+
+        let bundled = run();
+        let magenta = Style::new().magenta().apply_to("57").to_string();
+        assert!(bundled.contains(&magenta), "{:?}", bundled);
+
+        let config = td.path().join(".config/rgrc");
+        std::fs::create_dir_all(&config).unwrap();
+        std::fs::write(
+            config.join("conf.ping.local"),
+            "id=ttl\nregexp=ttl=(\\d+)\ncolours=default,bold yellow\n",
+        )
+        .unwrap();
+        let overlaid = run();
+        let yellow = Style::new().bold().yellow().apply_to("57").to_string();
+        assert!(overlaid.contains(&yellow), "{:?}", overlaid);
+        assert!(!overlaid.contains(&magenta));
+        // The other bundled rules still apply
+        let seq = Style::new().yellow().apply_to("1").to_string();
+        assert!(
+            overlaid.contains(&format!("icmp_seq={}", seq)),
+            "{:?}",
+            overlaid
+        );
+    }
 }
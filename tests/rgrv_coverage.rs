@@ -292,8 +292,12 @@ mod rgrv {
         // Create a valid conf file
         let mut file = fs::File::create(&conf_file).unwrap();
         writeln!(file, "# Test configuration").unwrap();
-        writeln!(file, r"^\d+\s+bytes\s+from red bold").unwrap();
-        writeln!(file, r"time=[\d\.]+ green").unwrap();
+        writeln!(file, r"regexp=^\d+\s+bytes\s+from").unwrap();
+        writeln!(file, "colours=red bold").unwrap();
+        writeln!(file, "-").unwrap();
+        writeln!(file, r"regexp=time=[\d\.]+").unwrap();
+        writeln!(file, "colours=green").unwrap();
+        writeln!(file, "-").unwrap();
         writeln!(file).unwrap();
         drop(file);
 
@@ -317,7 +321,9 @@ mod rgrv {
         // Create conf file with invalid regex
         let mut file = fs::File::create(&conf_file).unwrap();
         writeln!(file, "# Invalid regex test").unwrap();
-        writeln!(file, r"^\d+[unclosed red").unwrap();
+        writeln!(file, r"regexp=^\d+[unclosed").unwrap();
+        writeln!(file, "colours=red").unwrap();
+        writeln!(file, "-").unwrap();
         drop(file);
 
         let output = Command::new(get_rgrv_binary())
@@ -331,6 +337,96 @@ mod rgrv {
         assert!(stderr.contains("RegexError") || stderr.contains("Invalid regex"));
     }
 
+    /// Test conf file with macros: defined ones compile, others report their line
+    #[test]
+    fn test_validate_conf_file_macros() {
+        let temp_dir = TempDir::new().unwrap();
+        let conf_file = temp_dir.path().join("conf.test");
+
+        let mut file = fs::File::create(&conf_file).unwrap();
+        writeln!(file, r"define PORT=\d{{1,5}}").unwrap();
+        writeln!(file, "regexp=({{IPV4}}):({{PORT}})").unwrap();
+        writeln!(file, "colours=default,blue,cyan").unwrap();
+        writeln!(file, "-").unwrap();
+        writeln!(file, "regexp={{NOPE}}").unwrap();
+        writeln!(file, "colours=red").unwrap();
+        drop(file);
+
+        let output = Command::new(get_rgrv_binary())
+            .arg("conf")
+            .arg(conf_file.to_str().unwrap())
+            .output()
+            .expect("Failed to execute rgrv");
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("undefined macro {NOPE}"));
+        assert_eq!(stderr.matches("MacroError").count(), 1);
+    }
+
+    /// Test conf file with rule metadata: tags are free-form, severities are checked
+    #[test]
+    fn test_validate_conf_file_tags_and_severity() {
+        let temp_dir = TempDir::new().unwrap();
+        let conf_file = temp_dir.path().join("conf.test");
+
+        let mut file = fs::File::create(&conf_file).unwrap();
+        writeln!(file, "regexp=DUP!").unwrap();
+        writeln!(file, "colours=red").unwrap();
+        writeln!(file, "tags=network,duplicate").unwrap();
+        writeln!(file, "severity=warning").unwrap();
+        writeln!(file, "-").unwrap();
+        writeln!(file, "regexp=lost").unwrap();
+        writeln!(file, "colours=red").unwrap();
+        writeln!(file, "severity=critical").unwrap();
+        drop(file);
+
+        let output = Command::new(get_rgrv_binary())
+            .arg("conf")
+            .arg(conf_file.to_str().unwrap())
+            .output()
+            .expect("Failed to execute rgrv");
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Unknown severity value: critical"));
+        assert_eq!(stderr.matches("FormatError").count(), 1);
+    }
+
+    /// Test conf file checked as rgrc reads it: colors= is a colours key, styles
+    /// use rgrc's keywords, and lines that end a rule are reported with their column
+    #[test]
+    fn test_validate_conf_file_as_rgrc_reads_it() {
+        let temp_dir = TempDir::new().unwrap();
+        let conf_file = temp_dir.path().join("conf.test");
+
+        let mut file = fs::File::create(&conf_file).unwrap();
+        writeln!(file, r"regexp=^(\d+) ms").unwrap();
+        writeln!(file, "colors=bold red,bright-red").unwrap();
+        writeln!(file, "count=sometimes").unwrap();
+        writeln!(file, "  skip=yes").unwrap();
+        writeln!(file, "colours=green").unwrap();
+        writeln!(file, "-").unwrap();
+        drop(file);
+
+        let output = Command::new(get_rgrv_binary())
+            .arg("conf")
+            .arg(conf_file.to_str().unwrap())
+            .output()
+            .expect("Failed to execute rgrv");
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(stderr.matches("StyleError").count(), 1);
+        assert!(stderr.contains("Unknown style: 'bright-red'"));
+        assert!(stderr.contains("Use bright_red"));
+        assert!(stderr.contains("2:17"));
+        assert!(stderr.contains("Unknown count value: sometimes"));
+        assert!(stderr.contains("Indented line ends the rule and is ignored: skip=yes"));
+        assert!(stderr.contains("Rule without regexp="));
+        assert_eq!(stderr.matches("FormatError").count(), 3);
+    }
+
     /// Test conf file with missing style definition
     #[test]
     fn test_validate_conf_file_missing_style() {
@@ -362,7 +458,9 @@ mod rgrv {
 
         // Create conf file with unknown style
         let mut file = fs::File::create(&conf_file).unwrap();
-        writeln!(file, r"^\d+ unknown_style").unwrap();
+        writeln!(file, r"regexp=^\d+").unwrap();
+        writeln!(file, "colours=unknown_style").unwrap();
+        writeln!(file, "-").unwrap();
         drop(file);
 
         let output = Command::new(get_rgrv_binary())
@@ -384,7 +482,9 @@ mod rgrv {
 
         // Create conf file with multiple unknown styles
         let mut file = fs::File::create(&conf_file).unwrap();
-        writeln!(file, r"^\d+ red unknown1 blue unknown2").unwrap();
+        writeln!(file, r"regexp=^\d+").unwrap();
+        writeln!(file, "colours=red unknown1 blue unknown2").unwrap();
+        writeln!(file, "-").unwrap();
         drop(file);
 
         let output = Command::new(get_rgrv_binary())
@@ -404,7 +504,9 @@ mod rgrv {
 
         // Create conf file with bright colors
         let mut file = fs::File::create(&conf_file).unwrap();
-        writeln!(file, r"^\d+ bright-red bright-green bright-blue").unwrap();
+        writeln!(file, r"regexp=^\d+").unwrap();
+        writeln!(file, "colours=bright_red bright_green bright_blue").unwrap();
+        writeln!(file, "-").unwrap();
         drop(file);
 
         let output = Command::new(get_rgrv_binary())
@@ -424,7 +526,9 @@ mod rgrv {
 
         // Create conf file with background colors
         let mut file = fs::File::create(&conf_file).unwrap();
-        writeln!(file, r"^\d+ on_red on_blue on_green").unwrap();
+        writeln!(file, r"regexp=^\d+").unwrap();
+        writeln!(file, "colours=on_red on_blue on_green").unwrap();
+        writeln!(file, "-").unwrap();
         drop(file);
 
         let output = Command::new(get_rgrv_binary())
@@ -444,8 +548,12 @@ mod rgrv {
 
         // Create conf file with text attributes
         let mut file = fs::File::create(&conf_file).unwrap();
-        writeln!(file, r"^\d+ bold italic underline").unwrap();
-        writeln!(file, r"^\w+ dim blink reverse").unwrap();
+        writeln!(file, r"regexp=^\d+").unwrap();
+        writeln!(file, "colours=bold italic underline").unwrap();
+        writeln!(file, "-").unwrap();
+        writeln!(file, r"regexp=^\w+").unwrap();
+        writeln!(file, "colours=dim blink reverse").unwrap();
+        writeln!(file, "-").unwrap();
         drop(file);
 
         let output = Command::new(get_rgrv_binary())
@@ -457,15 +565,18 @@ mod rgrv {
         assert!(output.status.success());
     }
 
-    /// Test conf file with tab separator
+    /// Test conf file with legacy pattern<TAB>styles lines, which rgrc ignores
     #[test]
     fn test_validate_conf_file_tab_separator() {
         let temp_dir = TempDir::new().unwrap();
         let conf_file = temp_dir.path().join("conf.test");
 
-        // Create conf file with tab separator
         let mut file = fs::File::create(&conf_file).unwrap();
         writeln!(file, "^\\d+\tred bold").unwrap();
+        writeln!(file, "regexp=foo").unwrap();
+        writeln!(file, "colours=red").unwrap();
+        writeln!(file, "-").unwrap();
+        writeln!(file, "bar\tbold red").unwrap();
         drop(file);
 
         let output = Command::new(get_rgrv_binary())
@@ -474,7 +585,10 @@ mod rgrv {
             .output()
             .expect("Failed to execute rgrv");
 
-        assert!(output.status.success());
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(stderr.matches("ignored by rgrc").count(), 2);
+        assert!(!stderr.contains("StyleError"));
     }
 
     /// Test conf file with comments
@@ -486,9 +600,13 @@ mod rgrv {
         // Create conf file with comments
         let mut file = fs::File::create(&conf_file).unwrap();
         writeln!(file, "# This is a comment").unwrap();
-        writeln!(file, r"^\d+ red").unwrap();
+        writeln!(file, r"regexp=^\d+").unwrap();
+        writeln!(file, "colours=red").unwrap();
+        writeln!(file, "-").unwrap();
         writeln!(file, "# Another comment").unwrap();
-        writeln!(file, r"^\w+ blue").unwrap();
+        writeln!(file, r"regexp=^\w+").unwrap();
+        writeln!(file, "colours=blue").unwrap();
+        writeln!(file, "-").unwrap();
         drop(file);
 
         let output = Command::new(get_rgrv_binary())
@@ -509,9 +627,13 @@ mod rgrv {
         // Create conf file with empty lines
         let mut file = fs::File::create(&conf_file).unwrap();
         writeln!(file).unwrap();
-        writeln!(file, r"^\d+ red").unwrap();
+        writeln!(file, r"regexp=^\d+").unwrap();
+        writeln!(file, "colours=red").unwrap();
+        writeln!(file, "-").unwrap();
         writeln!(file).unwrap();
-        writeln!(file, r"^\w+ blue").unwrap();
+        writeln!(file, r"regexp=^\w+").unwrap();
+        writeln!(file, "colours=blue").unwrap();
+        writeln!(file, "-").unwrap();
         writeln!(file).unwrap();
         drop(file);
 
@@ -533,11 +655,15 @@ mod rgrv {
 
         // Create two valid conf files
         let mut file1 = fs::File::create(&conf_file1).unwrap();
-        writeln!(file1, r"^\d+ red").unwrap();
+        writeln!(file1, r"regexp=^\d+").unwrap();
+        writeln!(file1, "colours=red").unwrap();
+        writeln!(file1, "-").unwrap();
         drop(file1);
 
         let mut file2 = fs::File::create(&conf_file2).unwrap();
-        writeln!(file2, r"^\w+ blue").unwrap();
+        writeln!(file2, r"regexp=^\w+").unwrap();
+        writeln!(file2, "colours=blue").unwrap();
+        writeln!(file2, "-").unwrap();
         drop(file2);
 
         let output = Command::new(get_rgrv_binary())
@@ -561,11 +687,15 @@ mod rgrv {
 
         // Create one valid and one invalid conf file
         let mut file1 = fs::File::create(&conf_file1).unwrap();
-        writeln!(file1, r"^\d+ red").unwrap();
+        writeln!(file1, r"regexp=^\d+").unwrap();
+        writeln!(file1, "colours=red").unwrap();
+        writeln!(file1, "-").unwrap();
         drop(file1);
 
         let mut file2 = fs::File::create(&conf_file2).unwrap();
-        writeln!(file2, r"^\w+ unknown_style").unwrap();
+        writeln!(file2, r"regexp=^\w+").unwrap();
+        writeln!(file2, "colours=unknown_style").unwrap();
+        writeln!(file2, "-").unwrap();
         drop(file2);
 
         let output = Command::new(get_rgrv_binary())
@@ -642,17 +772,22 @@ mod rgrv {
 
         // Create conf file with complex regex patterns
         let mut file = fs::File::create(&conf_file).unwrap();
-        writeln!(file, r"^\d{{1,3}}\.\d{{1,3}}\.\d{{1,3}}\.\d{{1,3}} cyan").unwrap();
+        writeln!(file, r"regexp=^\d{{1,3}}\.\d{{1,3}}\.\d{{1,3}}\.\d{{1,3}}").unwrap();
+        writeln!(file, "colours=cyan").unwrap();
+        writeln!(file, "-").unwrap();
         writeln!(
             file,
-            r"([A-Za-z0-9+/]{{4}})*([A-Za-z0-9+/]{{2}}==|[A-Za-z0-9+/]{{3}}=)? yellow"
+            r"regexp=([A-Za-z0-9+/]{{4}})*([A-Za-z0-9+/]{{2}}==|[A-Za-z0-9+/]{{3}}=)?"
         )
         .unwrap();
+        writeln!(file, "colours=yellow").unwrap();
+        writeln!(file, "-").unwrap();
         writeln!(
             file,
-            r"(?:(?:25[0-5]|2[0-4][0-9]|[01]?[0-9][0-9]?)\.){{3}} green"
+            r"regexp=(?:(?:25[0-5]|2[0-4][0-9]|[01]?[0-9][0-9]?)\.){{3}}"
         )
         .unwrap();
+        writeln!(file, "colours=green").unwrap();
         drop(file);
 
         let output = Command::new(get_rgrv_binary())
@@ -740,9 +875,15 @@ mod rgrv {
 
         // Create conf file with mixed entries
         let mut file = fs::File::create(&conf_file).unwrap();
-        writeln!(file, r"^\d+ red").unwrap();
-        writeln!(file, r"^\w+ invalid_style").unwrap();
-        writeln!(file, r"^test blue").unwrap();
+        writeln!(file, r"regexp=^\d+").unwrap();
+        writeln!(file, "colours=red").unwrap();
+        writeln!(file, "-").unwrap();
+        writeln!(file, r"regexp=^\w+").unwrap();
+        writeln!(file, "colours=invalid_style").unwrap();
+        writeln!(file, "-").unwrap();
+        writeln!(file, r"regexp=^test").unwrap();
+        writeln!(file, "colours=blue").unwrap();
+        writeln!(file, "-").unwrap();
         drop(file);
 
         let output = Command::new(get_rgrv_binary())
@@ -753,4 +894,379 @@ mod rgrv {
 
         assert!(!output.status.success());
     }
+
+    /// Test engines command lists slow-path rules with their line numbers
+    #[test]
+    fn test_engines_lists_slow_path_rules() {
+        let temp_dir = TempDir::new().unwrap();
+        let conf_file = temp_dir.path().join("conf.test");
+
+        let mut file = fs::File::create(&conf_file).unwrap();
+        writeln!(file, r"regexp=^\d+").unwrap();
+        writeln!(file, "colours=red").unwrap();
+        writeln!(file, "-").unwrap();
+        writeln!(file, r"regexp=foo(?!bar)").unwrap();
+        writeln!(file, "colours=blue").unwrap();
+        writeln!(file, "-").unwrap();
+        // Trailing lookahead is lowered onto the fast path
+        writeln!(file, r"regexp=baz(?=qux)").unwrap();
+        writeln!(file, "colours=green").unwrap();
+        drop(file);
+
+        let output = Command::new(get_rgrv_binary())
+            .arg("engines")
+            .arg("--regex-engine=enhanced")
+            .arg(conf_file.to_str().unwrap())
+            .output()
+            .expect("Failed to execute rgrv");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(output.status.success());
+        assert!(stdout.contains("1 of 3 rules use the slow path"));
+        assert!(stdout.contains("line 4"));
+        assert!(stdout.contains("enhanced"));
+        assert!(stdout.contains("foo(?!bar)"));
+        assert!(!stdout.contains("baz(?=qux)"));
+        assert!(stdout.contains("1 lowered to the fast path"));
+
+        // With the fast engine only, the lookahead rule cannot be compiled
+        let output = Command::new(get_rgrv_binary())
+            .arg("engines")
+            .arg("--regex-engine=fast")
+            .arg(conf_file.to_str().unwrap())
+            .output()
+            .expect("Failed to execute rgrv");
+
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("error"));
+    }
+
+    /// Test conf command reports Python regex rewrites without failing
+    #[test]
+    fn test_conf_reports_python_rewrites() {
+        let temp_dir = TempDir::new().unwrap();
+        let conf_file = temp_dir.path().join("conf.test");
+
+        let mut file = fs::File::create(&conf_file).unwrap();
+        writeln!(file, r"regexp=^(?P<word>\w+) (?P=word)\Z").unwrap();
+        writeln!(file, "colours=red").unwrap();
+        drop(file);
+
+        let output = Command::new(get_rgrv_binary())
+            .arg("conf")
+            .arg(conf_file.to_str().unwrap())
+            .output()
+            .expect("Failed to execute rgrv");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(output.status.success());
+        assert!(stdout.contains(r"line 1: `(?P=word)` -> `\1`"));
+        assert!(stdout.contains(r"`\Z` -> `\z`"));
+    }
+
+    /// Test fmt --check fails on an unformatted file and fmt rewrites it
+    #[test]
+    fn test_fmt_check_and_rewrite() {
+        let temp_dir = TempDir::new().unwrap();
+        let conf_file = temp_dir.path().join("conf.test");
+        fs::write(
+            &conf_file,
+            "# test\nregexp=\\d+\ncolors=bold  red\n======\n\n\nregexp=x\ncolour=blue\n",
+        )
+        .unwrap();
+
+        let output = Command::new(get_rgrv_binary())
+            .args(["fmt", "--check", conf_file.to_str().unwrap()])
+            .output()
+            .expect("Failed to execute rgrv");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(!output.status.success());
+        assert!(stdout.contains("first difference at line 3"));
+        assert!(stdout.contains("1 not formatted"));
+
+        let output = Command::new(get_rgrv_binary())
+            .args(["fmt", conf_file.to_str().unwrap()])
+            .output()
+            .expect("Failed to execute rgrv");
+
+        assert!(output.status.success());
+        assert_eq!(
+            fs::read_to_string(&conf_file).unwrap(),
+            "# test\nregexp=\\d+\ncolours=bold red\n-\n\nregexp=x\ncolours=blue\n"
+        );
+
+        let output = Command::new(get_rgrv_binary())
+            .args(["fmt", "--check", conf_file.to_str().unwrap()])
+            .output()
+            .expect("Failed to execute rgrv");
+
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("0 not formatted"));
+    }
+
+    /// Test the bundled configs are already formatted
+    #[test]
+    fn test_fmt_check_bundled_configs() {
+        let mut paths: Vec<PathBuf> = fs::read_dir("share")
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.to_string_lossy().contains("conf."))
+            .collect();
+        paths.sort();
+
+        let output = Command::new(get_rgrv_binary())
+            .args(["fmt", "--check"])
+            .args(&paths)
+            .output()
+            .expect("Failed to execute rgrv");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(output.status.success(), "{}", stdout);
+        assert!(stdout.contains("0 not formatted"));
+    }
+
+    /// Test lint reports each check once and honours nolint=
+    #[test]
+    fn test_lint_reports_and_suppresses() {
+        let temp_dir = TempDir::new().unwrap();
+        let conf_file = temp_dir.path().join("conf.test");
+        fs::write(
+            &conf_file,
+            "regexp=(\\d+) (\\w+)\ncolours=red,green,blue,yellow\n-\n\
+             regexp=^ERROR\ncount=stop\ncolours=red\n-\n\
+             regexp=^ERROR\ncolours=blue\n-\n\
+             regexp=x(\\d)\ncolours=green\n-\n\
+             regexp=x(\\d)\ncolours=green\n-\n\
+             regexp=(a)b\nreplace=\\2\n-\n\
+             regexp=\\d*\ncolours=red\n-\n\
+             regexp=(?i)\\w+(?=ms)\ncolours=red\n-\n\
+             regexp=[0-9]*\ncolours=red\nnolint=empty-match\n-\n\
+             regexp=warn\ncount=stop\n-\n\
+             regexp=\\s(warning:)\ncolours=yellow,bold\n",
+        )
+        .unwrap();
+
+        let output = Command::new(get_rgrv_binary())
+            .args(["lint", conf_file.to_str().unwrap()])
+            .output()
+            .expect("Failed to execute rgrv");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(!output.status.success());
+        assert!(stdout.contains("4 colours for 2 groups: the last one never applies"));
+        assert!(stdout.contains("line 8    shadowed"));
+        assert!(stdout.contains("count=stop rule at line 4 has the same regexp"));
+        assert!(stdout.contains("line 14   duplicate-regexp same regexp as the rule at line 11"));
+        assert!(stdout.contains("replace= uses \\2, but the regexp has 1 group"));
+        assert!(stdout.contains("line 20   empty-match"));
+        assert!(stdout.contains("(?i)\\w+(?:ms) runs on the fast engine"));
+        assert!(stdout.contains("line 33   shadowed"));
+        assert!(stdout.contains("7 warnings"));
+
+        // nolint= takes known lint IDs only
+        fs::write(
+            &conf_file,
+            "regexp=a\ncolours=red\nnolint=empty-match,bogus\n",
+        )
+        .unwrap();
+        let output = Command::new(get_rgrv_binary())
+            .args(["conf", conf_file.to_str().unwrap()])
+            .output()
+            .expect("Failed to execute rgrv");
+
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown lint in nolint=: bogus"));
+    }
+
+    /// Test the bundled configs lint clean
+    #[test]
+    fn test_lint_bundled_configs() {
+        let mut paths: Vec<PathBuf> = fs::read_dir("share")
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.to_string_lossy().contains("conf."))
+            .collect();
+        paths.sort();
+
+        let output = Command::new(get_rgrv_binary())
+            .arg("lint")
+            .args(&paths)
+            .output()
+            .expect("Failed to execute rgrv");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(output.status.success(), "{}", stdout);
+        assert!(stdout.contains("0 warnings, 0 errors"));
+    }
+
+    /// Test the bundled configs still colour each fixture as recorded
+    #[test]
+    fn test_fixtures_match_bundled_configs() {
+        let home = TempDir::new().unwrap();
+        let mut inputs: Vec<PathBuf> = fs::read_dir("share/tests")
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .collect();
+        inputs.sort();
+        assert!(!inputs.is_empty());
+
+        for input in inputs {
+            let name = input.file_stem().unwrap().to_string_lossy();
+            let output = Command::new(get_rgrv_binary())
+                .env("HOME", home.path())
+                .arg("test")
+                .arg(format!("share/conf.{}", name))
+                .arg("--input")
+                .arg(&input)
+                .arg("--expect")
+                .arg(input.with_extension("ansi"))
+                .output()
+                .expect("Failed to execute rgrv");
+
+            assert!(
+                output.status.success(),
+                "{}",
+                String::from_utf8_lossy(&output.stdout)
+            );
+        }
+    }
+
+    /// Test --update records the colouring and a change shows up as a readable diff
+    #[test]
+    fn test_test_update_and_diff() {
+        let temp_dir = TempDir::new().unwrap();
+        let conf_file = temp_dir.path().join("conf.test");
+        let input = temp_dir.path().join("input.txt");
+        let expect = temp_dir.path().join("expect.ansi");
+        fs::write(&conf_file, "regexp=ERROR\ncolours=bold red\n").unwrap();
+        fs::write(&input, "ok\nERROR: disk full\n").unwrap();
+
+        let run = |update: bool| {
+            let mut command = Command::new(get_rgrv_binary());
+            command
+                .env("HOME", temp_dir.path())
+                .arg("test")
+                .arg(&conf_file)
+                .arg("--input")
+                .arg(&input)
+                .arg("--expect")
+                .arg(&expect);
+            if update {
+                command.arg("--update");
+            }
+            command.output().expect("Failed to execute rgrv")
+        };
+
+        // Nothing recorded yet
+        assert!(!run(false).status.success());
+
+        assert!(run(true).status.success());
+        assert_eq!(
+            fs::read_to_string(&expect).unwrap(),
+            "ok\n\x1b[1;31mERROR\x1b[0m: disk full\n"
+        );
+        assert!(run(false).status.success());
+
+        fs::write(&conf_file, "regexp=ERROR\ncolours=yellow\n").unwrap();
+        let output = run(false);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(!output.status.success());
+        assert!(stdout.contains("1 of 2 lines differ"));
+        assert!(stdout.contains("line 2"));
+        assert!(stdout.contains("[bold red]ERROR[/]: disk full"));
+        assert!(stdout.contains("[yellow]ERROR[/]: disk full"));
+
+        // A broken rule is reported and fails the test, but the others still run
+        fs::write(
+            &conf_file,
+            "regexp=(\ncolours=red\n-\nregexp=ERROR\ncolours=bold red\n",
+        )
+        .unwrap();
+        let output = run(false);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("rgrc: "));
+        assert!(stdout.contains("matches"));
+    }
+
+    /// Test explain traces each rule and the winning spans, reading stdin by default
+    #[test]
+    fn test_explain_traces_rules() {
+        use std::io::Write;
+        use std::process::Stdio;
+
+        let temp_dir = TempDir::new().unwrap();
+        let conf_file = temp_dir.path().join("conf.test");
+        fs::write(
+            &conf_file,
+            "regexp=disk (\\w+)\ncolours=yellow,bold\ncount=stop\n-\nregexp=(full)\ncolours=red,bold\n\
+             severity=error\ntags=disk\n",
+        )
+        .unwrap();
+
+        let mut child = Command::new(get_rgrv_binary())
+            .env("HOME", temp_dir.path())
+            .arg("explain")
+            .arg(&conf_file)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to execute rgrv");
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(b"ERROR: disk sda full\nok disk - full\n")
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        assert!(output.status.success());
+        assert!(stdout.contains("line 1: ERROR: disk sda full"));
+        assert!(stdout.contains("matched 1 time"));
+        assert!(stdout.contains(
+            "match 0 7..15 \"disk sda\" -> yellow (partly overridden by group 1 of this rule)"
+        ));
+        assert!(stdout.contains("group 1 12..15 \"sda\" -> bold"));
+        assert!(stdout.contains("count=stop"));
+        assert!(stdout.contains(
+            "rule #2 severity=error tags=disk (full): not run (an earlier rule has count=stop)"
+        ));
+        assert!(stdout.contains("12..15 \"sda\" <- rule #1 group 1 [bold]"));
+        assert!(stdout.contains("result: ERROR: [yellow]disk [/][bold]sda[/] full"));
+        assert!(stdout.contains("line 2: ok disk - full"));
+        assert!(
+            stdout.contains("match 0 10..14 \"full\" -> red (overridden by group 1 of this rule)")
+        );
+        assert!(stdout.contains("no match"));
+    }
+
+    /// Test explain reports a broken rule and explains the others
+    #[test]
+    fn test_explain_with_broken_rule() {
+        let temp_dir = TempDir::new().unwrap();
+        let conf_file = temp_dir.path().join("conf.test");
+        let input = temp_dir.path().join("input.txt");
+        fs::write(
+            &conf_file,
+            "regexp=(\ncolours=red\n-\nregexp=ERROR\ncolours=bold red\n",
+        )
+        .unwrap();
+        fs::write(&input, "ERROR: disk full\n").unwrap();
+
+        let output = Command::new(get_rgrv_binary())
+            .env("HOME", temp_dir.path())
+            .arg("explain")
+            .arg(&conf_file)
+            .arg("--input")
+            .arg(&input)
+            .output()
+            .expect("Failed to execute rgrv");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Regex syntax error"));
+        assert!(stdout.contains("result: [bold red]ERROR[/]: disk full"));
+    }
 }